@@ -30,7 +30,7 @@ impl BuilderExt for Builder {
     fn with_builder_into(self, path: &str, fields: &[&str]) -> Self {
         fields.iter().fold(self, |acc, field| {
             acc.field_attribute(
-                &format!("{}.{}", path, field),
+                format!("{}.{}", path, field),
                 "#[builder(setter(into),default)]",
             )
         })
@@ -39,7 +39,7 @@ impl BuilderExt for Builder {
     fn with_builder_option(self, path: &str, fields: &[&str]) -> Self {
         fields.iter().fold(self, |acc, field| {
             acc.field_attribute(
-                &format!("{}.{}", path, field),
+                format!("{}.{}", path, field),
                 "#[builder(setter(into,strip_option),default)]",
             )
         })
@@ -53,21 +53,44 @@ fn main() {
         .with_builder(&[
             "reservation.ReservationQuery",
             "reservation.ReservationFilter",
+            "reservation.AvailabilityQuery",
         ])
         .with_builder_into(
             "reservation.ReservationQuery",
-            &["resource_id", "user_id", "status", "page", "desc"],
+            &[
+                "resource_id",
+                "user_id",
+                "status",
+                "statuses",
+                "page",
+                "desc",
+                "match_mode",
+            ],
         )
         .with_builder_into(
             "reservation.ReservationFilter",
-            &["resource_id", "user_id", "status", "desc"],
+            &[
+                "resource_id",
+                "user_id",
+                "status",
+                "statuses",
+                "desc",
+                "backward",
+                "with_total",
+            ],
+        )
+        .with_builder_into(
+            "reservation.AvailabilityQuery",
+            &["resource_ids", "min_duration", "granularity", "quantity"],
         )
         .with_builder_option("reservation.ReservationQuery", &["start", "end"])
+        .with_builder_option("reservation.AvailabilityQuery", &["start", "end"])
         .with_builder_option("reservation.ReservationFilter", &["cursor"])
         .with_type_attributes(
             &[
                 "reservation.ReservationQuery",
                 "reservation.ReservationFilter",
+                "reservation.AvailabilityQuery",
             ],
             &[r#"#[builder(build_fn(name = "private_build"))]"#],
         )
@@ -80,7 +103,7 @@ fn main() {
 
     //fs::remove_file("src/pb/google.protobuf.rs").unwrap();
 
-    Command::new("cargo").args(&["fmt"]).output().unwrap();
+    Command::new("cargo").args(["fmt"]).output().unwrap();
 
     println!("cargo:rerun-if-changed=protos/reservation.proto");
 }
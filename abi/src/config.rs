@@ -30,6 +30,12 @@ fn default_pool_size() -> u32 {
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
+    /// secret to sign the page tokens of filter
+    #[serde(default)]
+    pub page_token_secret: String,
+    /// port of the http/json gateway. If not set, only grpc is served
+    #[serde(default)]
+    pub http_port: Option<u16>,
 }
 
 impl Config {
@@ -86,7 +92,9 @@ mod tests {
                 },
                 server: ServerConfig {
                     host: "0.0.0.0".to_string(),
-                    port: 50051
+                    port: 50051,
+                    page_token_secret: "reservation-page-token".to_string(),
+                    http_port: Some(8080),
                 }
             }
         );
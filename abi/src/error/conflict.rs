@@ -1,24 +1,58 @@
 use chrono::{DateTime, Utc};
-use regex::Regex;
-use std::{collections::HashMap, convert::Infallible, str::FromStr};
+use std::{convert::Infallible, fmt, str::FromStr};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+use super::literal::{self, RangeLiteral};
+use crate::{convert_to_utc_time, FreeSlot, Reservation, ReservationId, ResourceRelation};
+
+const CAPACITY_KEY: &str = "Key (resource_id, timespan)=";
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum ReservationConflictInfo {
-    Parsed(ReservationConflict),
+    Parsed(Box<ReservationConflict>),
     Unparsed(String),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ReservationConflict {
     pub new: ReservationWindow,
     pub old: ReservationWindow,
+    /// the resource of the existing reservation relative to the new one. postgres only names the
+    /// resource, so it's unknown for a different one until the manager fills it
+    pub relation: ResourceRelation,
+    /// all the existing reservations overlapping the new one, order by start time.
+    /// postgres only names the first one, so it's empty until the manager fills it
+    pub conflicts: Vec<Reservation>,
+    /// free windows of the same length as the new one, only filled if the request asks for them
+    pub suggestions: Vec<FreeSlot>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ReservationWindow {
+    /// id of the reservation, 0 if it is unknown, e.g. the new reservation
+    pub id: ReservationId,
     pub rid: String,
-    pub start: DateTime<Utc>,
-    pub end: DateTime<Utc>,
+    /// None if the window is unbounded at the start
+    pub start: Option<DateTime<Utc>>,
+    /// None if the window is unbounded at the end
+    pub end: Option<DateTime<Utc>>,
+}
+
+/// the conflict of a reservation in a batch, with the existing ones or with the capacity
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchConflictInfo {
+    Reservation(ReservationConflictInfo),
+    Capacity(CapacityConflict),
+}
+
+/// the new reservation exceeds the capacity of a shared resource
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapacityConflict {
+    pub new: ReservationWindow,
+    /// units requested by the new reservation
+    pub quantity: i32,
+    pub capacity: i32,
+    /// the most units reserved at the same time by the existing reservations overlapping the new one
+    pub reserved: i32,
 }
 
 impl FromStr for ReservationConflictInfo {
@@ -26,80 +60,179 @@ impl FromStr for ReservationConflictInfo {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if let Ok(conflict) = s.parse() {
-            Ok(ReservationConflictInfo::Parsed(conflict))
+            Ok(ReservationConflictInfo::Parsed(Box::new(conflict)))
         } else {
             Ok(ReservationConflictInfo::Unparsed(s.to_string()))
         }
     }
 }
 
-impl FromStr for ReservationConflict {
-    type Err = ();
+impl fmt::Display for ReservationConflictInfo {
+    /// the same format as the detail of the postgres exclusion error, so that it can be parsed back
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReservationConflictInfo::Parsed(conflict) => write!(
+                f,
+                "Key (resource_id, timespan)={} conflicts with existing key (resource_id, timespan)={}.",
+                conflict.new, conflict.old
+            ),
+            ReservationConflictInfo::Unparsed(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl From<ReservationConflictInfo> for BatchConflictInfo {
+    fn from(info: ReservationConflictInfo) -> Self {
+        Self::Reservation(info)
+    }
+}
+
+impl From<CapacityConflict> for BatchConflictInfo {
+    fn from(conflict: CapacityConflict) -> Self {
+        Self::Capacity(conflict)
+    }
+}
+
+impl FromStr for BatchConflictInfo {
+    type Err = Infallible;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        ParsedInfo::from_str(s)?.try_into()
+        match s.parse() {
+            Ok(conflict) => Ok(Self::Capacity(conflict)),
+            Err(_) => Ok(Self::Reservation(s.parse()?)),
+        }
     }
 }
 
-struct ParsedInfo {
-    new: HashMap<String, String>,
-    old: HashMap<String, String>,
+impl fmt::Display for BatchConflictInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reservation(info) => write!(f, "{}", info),
+            Self::Capacity(conflict) => write!(f, "{}", conflict),
+        }
+    }
+}
+
+impl fmt::Display for ReservationWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bound = |t: Option<DateTime<Utc>>| {
+            t.map(|t| format!("\"{}\"", t.format("%Y-%m-%d %H:%M:%S%.f+00")))
+                .unwrap_or_default()
+        };
+        let lower = if self.start.is_some() { '[' } else { '(' };
+        write!(
+            f,
+            "({}, {}{},{}))",
+            self.rid,
+            lower,
+            bound(self.start),
+            bound(self.end)
+        )
+    }
 }
 
-impl FromStr for ParsedInfo {
+impl FromStr for ReservationConflict {
     type Err = ();
 
     //Key (resource_id, timespan)=(ocean-view-room-713, ["2022-12-26 22:00:00+00","2022-12-30 19:00:00+00")) conflicts with existing key (resource_id, timespan)=(ocean-view-room-713, ["2022-12-26 22:00:00+00","2022-12-30 19:00:00+00")).
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // use regular expression to parse the string
-        let re = Regex::new(r#"\((?P<k1>[A-Za-z0-9_-]+)\s*,\s*(?P<k2>[A-za-z0-9_-]+)\)=\((?P<v1>[A-Za-z0-9-_]+)\s*,\s*\[(?P<v2>[^\)]+)\)"#).unwrap();
-        let mut maps = vec![];
-        for cap in re.captures_iter(s) {
-            let mut map = HashMap::new();
-            map.insert(cap["k1"].to_string(), cap["v1"].to_string());
-            map.insert(cap["k2"].to_string(), cap["v2"].to_string());
-            maps.push(Some(map));
-        }
+        // the resource id is not quoted, so try every way to split the detail until both keys parse
+        literal::conflict_keys(s)
+            .into_iter()
+            .find_map(|(new, old)| {
+                let (new, old) = (parse_key(new)?, parse_key(old)?);
+                let relation = if new.rid == old.rid {
+                    ResourceRelation::Same
+                } else {
+                    ResourceRelation::Unknown
+                };
+                Some(Self {
+                    new,
+                    old,
+                    relation,
+                    conflicts: vec![],
+                    suggestions: vec![],
+                })
+            })
+            .ok_or(())
+    }
+}
 
-        if maps.len() != 2 {
-            return Err(());
-        }
-        Ok(ParsedInfo {
-            new: maps[0].take().unwrap(),
-            old: maps[1].take().unwrap(),
-        })
+impl fmt::Display for CapacityConflict {
+    /// the same format as the detail of the capacity error raised by "rsvp.reservations_capacity"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{} requests {} of capacity {}, {} reserved.",
+            CAPACITY_KEY, self.new, self.quantity, self.capacity, self.reserved
+        )
     }
 }
 
-impl TryFrom<ParsedInfo> for ReservationConflict {
-    type Error = ();
+impl FromStr for CapacityConflict {
+    type Err = ();
 
-    fn try_from(value: ParsedInfo) -> Result<Self, Self::Error> {
-        Ok(Self {
-            new: value.new.try_into()?,
-            old: value.old.try_into()?,
-        })
+    //Key (resource_id, timespan)=(desk-1, ["2022-12-26 22:00:00+00","2022-12-30 19:00:00+00")) requests 2 of capacity 3, 2 reserved.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = || {
+            let (key, units) = s.strip_prefix(CAPACITY_KEY)?.rsplit_once(" requests ")?;
+            let key = key.strip_prefix('(')?.strip_suffix(')')?;
+            let (quantity, units) = units.split_once(" of capacity ")?;
+            let (capacity, reserved) = units.strip_suffix(" reserved.")?.split_once(", ")?;
+            Some(Self {
+                new: parse_key(key)?,
+                quantity: quantity.parse().ok()?,
+                capacity: capacity.parse().ok()?,
+                reserved: reserved.parse().ok()?,
+            })
+        };
+        parse().ok_or(())
     }
 }
 
-impl TryFrom<HashMap<String, String>> for ReservationWindow {
+fn parse_key(s: &str) -> Option<ReservationWindow> {
+    literal::key_values(s).find_map(|(rid, timespan)| (rid, timespan).try_into().ok())
+}
+
+impl TryFrom<(&str, RangeLiteral)> for ReservationWindow {
     type Error = ();
 
-    fn try_from(value: HashMap<String, String>) -> Result<Self, Self::Error> {
-        let timespan_str = value.get("timespan").ok_or(())?.replace('"', "");
-        let mut split = timespan_str.splitn(2, ',');
-        let start: DateTime<Utc> = parse_datetime(split.next().ok_or(())?)?;
-        let end: DateTime<Utc> = parse_datetime(split.next().ok_or(())?)?;
+    fn try_from((rid, timespan): (&str, RangeLiteral)) -> Result<Self, Self::Error> {
+        // an empty range never conflicts with others
+        let RangeLiteral::Range { lower, upper, .. } = timespan else {
+            return Err(());
+        };
         Ok(Self {
-            rid: value.get("resource_id").ok_or(())?.to_string(),
-            start,
-            end,
+            id: 0,
+            rid: rid.to_string(),
+            start: parse_bound(lower)?,
+            end: parse_bound(upper)?,
         })
     }
 }
 
+impl From<&Reservation> for ReservationWindow {
+    fn from(rsvp: &Reservation) -> Self {
+        Self {
+            id: rsvp.id,
+            rid: rsvp.resource_id.clone(),
+            start: rsvp.start.as_ref().map(convert_to_utc_time),
+            end: rsvp.end.as_ref().map(convert_to_utc_time),
+        }
+    }
+}
+
+/// None if the bound is unbounded or infinite
+fn parse_bound(bound: Option<String>) -> Result<Option<DateTime<Utc>>, ()> {
+    match bound.as_deref() {
+        None | Some("infinity") | Some("-infinity") => Ok(None),
+        Some(s) => parse_datetime(s).map(Some),
+    }
+}
+
+/// parse the timestamp in the ISO date style, which sqlx sets for its connections
 pub fn parse_datetime(s: &str) -> Result<DateTime<Utc>, ()> {
-    Ok(DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%#z")
+    Ok(DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f%#z")
         .map_err(|_| ())?
         .with_timezone(&Utc))
 }
@@ -110,39 +243,45 @@ mod tests {
 
     const ERR_MSG:&str="Key (resource_id, timespan)=(ocean-view-room-713, [\"2022-12-26 22:00:00+00\",\"2022-12-30 19:00:00+00\")) conflicts with existing key (resource_id, timespan)=(ocean-view-room-713, [\"2022-12-25 22:00:00+00\",\"2022-12-28 19:00:00+00\")).";
 
+    fn utc(s: &str) -> Option<DateTime<Utc>> {
+        Some(s.parse().unwrap())
+    }
+
+    fn window(rid: &str, start: Option<&str>, end: Option<&str>) -> ReservationWindow {
+        ReservationWindow {
+            id: 0,
+            rid: rid.to_string(),
+            start: start.and_then(utc),
+            end: end.and_then(utc),
+        }
+    }
+
+    fn parse(s: &str) -> Option<ReservationConflict> {
+        match s.parse().unwrap() {
+            ReservationConflictInfo::Parsed(conflict) => Some(*conflict),
+            ReservationConflictInfo::Unparsed(_) => None,
+        }
+    }
+
     #[test]
     fn parsed_datetime_should_work() {
         let dt = parse_datetime("1996-12-19 16:39:57-08:00").unwrap();
         assert_eq!(dt.to_rfc3339(), "1996-12-20T00:39:57+00:00");
+        let dt = parse_datetime("2022-12-28 19:00:00.25+00").unwrap();
+        assert_eq!(dt.to_rfc3339(), "2022-12-28T19:00:00.250+00:00");
+        let dt = parse_datetime("2022-12-21 03:30:00+05:30").unwrap();
+        assert_eq!(dt.to_rfc3339(), "2022-12-20T22:00:00+00:00");
+        assert!(parse_datetime("21.12.2022 03:30:00 IST").is_err());
     }
 
     #[test]
-    fn parsed_info_should_work() {
-        let info: ParsedInfo = ERR_MSG.parse().unwrap();
-        assert_eq!(info.new["resource_id"], "ocean-view-room-713");
-        assert_eq!(
-            info.new["timespan"],
-            "\"2022-12-26 22:00:00+00\",\"2022-12-30 19:00:00+00\""
-        );
-        assert_eq!(info.old["resource_id"], "ocean-view-room-713");
-        assert_eq!(
-            info.old["timespan"],
-            "\"2022-12-25 22:00:00+00\",\"2022-12-28 19:00:00+00\""
-        )
-    }
+    fn range_literal_to_reservation_window_should_work() {
+        let timespan = literal::parse_range(r#"["2022-12-26 22:00:00+00",infinity)"#).unwrap();
+        let parsed: ReservationWindow = ("room 1", timespan).try_into().unwrap();
+        assert_eq!(parsed, window("room 1", Some("2022-12-26T22:00:00Z"), None));
 
-    #[test]
-    fn hash_map_to_reservation_window_should_work() {
-        let mut map = HashMap::new();
-        map.insert("resource_id".to_string(), "ocean-view-room-713".to_string());
-        map.insert(
-            "timespan".to_string(),
-            "\"2022-12-26 22:00:00+00\",\"2022-12-30 19:00:00+00\"".to_string(),
-        );
-        let window: ReservationWindow = map.try_into().unwrap();
-        assert_eq!(window.rid, "ocean-view-room-713");
-        assert_eq!(window.start.to_rfc3339(), "2022-12-26T22:00:00+00:00");
-        assert_eq!(window.end.to_rfc3339(), "2022-12-30T19:00:00+00:00");
+        let timespan = literal::parse_range("empty").unwrap();
+        assert!(ReservationWindow::try_from(("room 1", timespan)).is_err());
     }
 
     #[test]
@@ -151,13 +290,186 @@ mod tests {
         match info {
             ReservationConflictInfo::Parsed(conflict) => {
                 assert_eq!(conflict.new.rid, "ocean-view-room-713");
-                assert_eq!(conflict.new.start.to_rfc3339(), "2022-12-26T22:00:00+00:00");
-                assert_eq!(conflict.new.end.to_rfc3339(), "2022-12-30T19:00:00+00:00");
+                assert_eq!(conflict.new.start, utc("2022-12-26T22:00:00Z"));
+                assert_eq!(conflict.new.end, utc("2022-12-30T19:00:00Z"));
                 assert_eq!(conflict.old.rid, "ocean-view-room-713");
-                assert_eq!(conflict.old.start.to_rfc3339(), "2022-12-25T22:00:00+00:00");
-                assert_eq!(conflict.old.end.to_rfc3339(), "2022-12-28T19:00:00+00:00");
+                assert_eq!(conflict.old.start, utc("2022-12-25T22:00:00Z"));
+                assert_eq!(conflict.old.end, utc("2022-12-28T19:00:00Z"));
+                assert_eq!(conflict.relation, ResourceRelation::Same);
             }
             ReservationConflictInfo::Unparsed(_) => panic!("should be parsed"),
         }
+
+        // raised by the trigger checking the ancestors and the descendants of the resource
+        let s = ERR_MSG.replacen("ocean-view-room-713", "ocean-view-floor-7", 1);
+        let conflict = parse(&s).unwrap();
+        assert_eq!(conflict.new.rid, "ocean-view-floor-7");
+        assert_eq!(conflict.old.rid, "ocean-view-room-713");
+        assert_eq!(conflict.relation, ResourceRelation::Unknown);
+    }
+
+    #[test]
+    fn conflict_info_should_display_as_error_message() {
+        let info: ReservationConflictInfo = ERR_MSG.parse().unwrap();
+        assert_eq!(info.to_string(), ERR_MSG);
+
+        let info = ReservationConflictInfo::Unparsed("unknown conflict".to_string());
+        assert_eq!(info.to_string(), "unknown conflict");
+    }
+
+    #[test]
+    fn postgres_error_messages_should_parse() {
+        // the details returned by postgres for the exclusion constraint of rsvp.reservations
+        let corpus = [
+            (
+                r#"Key (resource_id, timespan)=(room 1, "a" (b).c, ["2022-12-20 22:00:00+00",infinity)) conflicts with existing key (resource_id, timespan)=(room 1, "a" (b).c, (,"2022-12-28 19:00:00.25+00"))."#,
+                Some((
+                    window(r#"room 1, "a" (b).c"#, Some("2022-12-20T22:00:00Z"), None),
+                    window(
+                        r#"room 1, "a" (b).c"#,
+                        None,
+                        Some("2022-12-28T19:00:00.25Z"),
+                    ),
+                )),
+            ),
+            (
+                r#"Key (resource_id, timespan)=(x, ["2022-12-21 03:30:00+05:30","2022-12-22 03:30:00+05:30")) conflicts with existing key (resource_id, timespan)=(x, ["2022-12-21 03:30:00+05:30","2022-12-22 03:30:00+05:30"))."#,
+                Some((
+                    window(
+                        "x",
+                        Some("2022-12-20T22:00:00Z"),
+                        Some("2022-12-21T22:00:00Z"),
+                    ),
+                    window(
+                        "x",
+                        Some("2022-12-20T22:00:00Z"),
+                        Some("2022-12-21T22:00:00Z"),
+                    ),
+                )),
+            ),
+            // other date styles use the abbreviation of the time zone, which is ambiguous
+            (
+                r#"Key (resource_id, timespan)=(x, ["21.12.2022 03:30:00 IST","22.12.2022 03:30:00 IST")) conflicts with existing key (resource_id, timespan)=(x, ["21.12.2022 03:30:00 IST","22.12.2022 03:30:00 IST"))."#,
+                None,
+            ),
+            (
+                r#"Key (resource_id, timespan)=(x, ["Wed 21 Dec 03:30:00 2022 IST","Thu 22 Dec 03:30:00 2022 IST")) conflicts with existing key (resource_id, timespan)=(x, ["Wed 21 Dec 03:30:00 2022 IST","Thu 22 Dec 03:30:00 2022 IST"))."#,
+                None,
+            ),
+            (
+                r#"Key (resource_id)=(x) conflicts with existing key (resource_id)=(x)."#,
+                None,
+            ),
+        ];
+        for (s, expected) in corpus {
+            let conflict = parse(s).map(|c| (c.new, c.old));
+            assert_eq!(conflict, expected, "{}", s);
+        }
+    }
+
+    #[test]
+    fn conflict_should_parse_back_from_display() {
+        let rids = [
+            "ocean-view-room-713",
+            "room 1",
+            "room.1.2",
+            r#""quoted" id"#,
+            "a, b",
+            "(x, [y",
+            "a\\b",
+            "A-za-z^_`",
+            "房间-1",
+            "a) conflicts with existing key (resource_id, timespan)=(b",
+        ];
+        let bounds = [
+            (Some("2022-12-25T22:00:00Z"), Some("2022-12-28T19:00:00Z")),
+            (
+                Some("2022-12-25T22:00:00.123456Z"),
+                Some("2022-12-28T19:00:00.5Z"),
+            ),
+            (None, Some("2022-12-28T19:00:00Z")),
+            (Some("2022-12-25T22:00:00Z"), None),
+            (None, None),
+        ];
+        for rid in rids {
+            for (new, old) in bounds.iter().zip(bounds.iter().rev()) {
+                let conflict = ReservationConflict {
+                    new: window(rid, new.0, new.1),
+                    old: window(rid, old.0, old.1),
+                    relation: ResourceRelation::Same,
+                    conflicts: vec![],
+                    suggestions: vec![],
+                };
+                let info = ReservationConflictInfo::Parsed(Box::new(conflict.clone()));
+                let s = info.to_string();
+                assert_eq!(parse(&s), Some(conflict), "{}", s);
+            }
+        }
+    }
+
+    #[test]
+    fn capacity_conflict_should_parse_back_from_display() {
+        let s = r#"Key (resource_id, timespan)=(desk 1) requests 2, ["2022-12-26 22:00:00+00","2022-12-30 19:00:00+00")) requests 2 of capacity 3, 2 reserved."#;
+        let conflict: CapacityConflict = s.parse().unwrap();
+        assert_eq!(
+            conflict,
+            CapacityConflict {
+                new: window(
+                    "desk 1) requests 2",
+                    Some("2022-12-26T22:00:00Z"),
+                    Some("2022-12-30T19:00:00Z")
+                ),
+                quantity: 2,
+                capacity: 3,
+                reserved: 2,
+            }
+        );
+        assert_eq!(conflict.to_string(), s);
+
+        assert!(ERR_MSG.parse::<CapacityConflict>().is_err());
+        assert!(s
+            .replace(" 3,", " three,")
+            .parse::<CapacityConflict>()
+            .is_err());
+    }
+
+    #[test]
+    fn mutated_error_message_should_not_panic() {
+        // every truncated message misses the end of the detail
+        for (i, _) in ERR_MSG.char_indices() {
+            assert_eq!(parse(&ERR_MSG[..i]), None, "{}", &ERR_MSG[..i]);
+        }
+
+        // replace the chars by the special ones of the literals, with a fixed seed
+        let specials = [
+            '"', '\\', ',', '(', ')', '[', ']', ' ', '.', '-', '+', ':', '=',
+        ];
+        let chars: Vec<char> = ERR_MSG.chars().collect();
+        let mut seed: u64 = 0x2022_1225;
+        let mut next = |n: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % n
+        };
+        for _ in 0..5000 {
+            let mut mutated = chars.clone();
+            for _ in 0..=next(4) {
+                let i = next(mutated.len());
+                match next(3) {
+                    0 => mutated[i] = specials[next(specials.len())],
+                    1 => {
+                        mutated.remove(i);
+                    }
+                    _ => mutated.insert(i, specials[next(specials.len())]),
+                }
+            }
+            let s: String = mutated.into_iter().collect();
+            // anything parsed could be displayed and parsed again
+            if let Some(conflict) = parse(&s) {
+                let s = ReservationConflictInfo::Parsed(Box::new(conflict)).to_string();
+                assert!(parse(&s).is_some(), "{}", s);
+            }
+        }
     }
 }
@@ -0,0 +1,357 @@
+use prost::Message;
+
+use super::{
+    BatchConflictInfo, CapacityConflict, Error, ReservationConflict, ReservationConflictInfo,
+    ReservationWindow,
+};
+use crate::{
+    convert_to_timestamp, convert_to_utc_time, error_detail::Detail, BatchConflict,
+    BatchConflictDetail, CapacityConflictDetail, ConflictDetail, ConflictWindow, ErrorDetail,
+    InvalidArgumentDetail,
+};
+
+impl Error {
+    /// typed detail of the error, it's sent in the details of the status
+    pub fn detail(&self) -> Option<ErrorDetail> {
+        let detail = match self {
+            Error::ConflictReservation(ReservationConflictInfo::Parsed(conflict)) => {
+                Detail::Conflict(conflict.as_ref().into())
+            }
+            Error::BatchConflict(conflicts) => Detail::BatchConflict(BatchConflictDetail {
+                conflicts: conflicts
+                    .iter()
+                    .map(|(index, info)| BatchConflict {
+                        index: *index as i64,
+                        conflict: match info {
+                            BatchConflictInfo::Reservation(ReservationConflictInfo::Parsed(
+                                conflict,
+                            )) => Some(conflict.as_ref().into()),
+                            _ => None,
+                        },
+                        description: info.to_string(),
+                        capacity_conflict: match info {
+                            BatchConflictInfo::Capacity(conflict) => Some(conflict.into()),
+                            _ => None,
+                        },
+                    })
+                    .collect(),
+            }),
+            Error::CapacityExceeded(conflict) => Detail::CapacityConflict(conflict.into()),
+            e => Detail::InvalidArgument(InvalidArgumentDetail {
+                field: e.invalid_field()?.to_string(),
+                description: e.to_string(),
+            }),
+        };
+        Some(ErrorDetail {
+            detail: Some(detail),
+        })
+    }
+
+    /// the field of the request which is invalid
+    fn invalid_field(&self) -> Option<&'static str> {
+        let field = match self {
+            Error::InvalidTime => "timespan",
+            Error::InvalidUserId(_) => "user_id",
+            Error::InvalidResourceId(_) => "resource_id",
+            Error::InvalidReservationId(_) => "id",
+            Error::InvalidPage(_) => "page",
+            Error::InvalidPageSize(_) => "page_size",
+            Error::InvalidCursor(_) => "cursor",
+            Error::InvalidPageToken(_) => "page_token",
+            Error::InvalidStatus(_) => "status",
+            Error::InvalidMatchMode(_) => "match_mode",
+            Error::InvalidConsumer(_) => "consumer",
+            Error::InvalidDuration(_) => "min_duration",
+            Error::InvalidGranularity(_) => "granularity",
+            Error::InvalidRecurrence(_) => "rule",
+            Error::InvalidSeriesId(_) => "series_id",
+            Error::InvalidScope(_) => "scope",
+            Error::InvalidTimezone(_) => "timezone",
+            Error::InvalidQuantity(_) => "quantity",
+            Error::InvalidCapacity(_) => "capacity",
+            Error::InvalidPool(_) => "pool",
+            Error::InvalidParent(_) => "parent_id",
+            _ => return None,
+        };
+        Some(field)
+    }
+}
+
+impl ErrorDetail {
+    /// decode the detail from the status returned by the service
+    pub fn from_status(status: &tonic::Status) -> Option<Self> {
+        if status.details().is_empty() {
+            return None;
+        }
+        ErrorDetail::decode(status.details())
+            .ok()
+            .filter(|detail| detail.detail.is_some())
+    }
+
+    pub fn conflict(&self) -> Option<&ConflictDetail> {
+        match &self.detail {
+            Some(Detail::Conflict(conflict)) => Some(conflict),
+            _ => None,
+        }
+    }
+
+    pub fn batch_conflict(&self) -> Option<&BatchConflictDetail> {
+        match &self.detail {
+            Some(Detail::BatchConflict(detail)) => Some(detail),
+            _ => None,
+        }
+    }
+
+    pub fn capacity_conflict(&self) -> Option<&CapacityConflictDetail> {
+        match &self.detail {
+            Some(Detail::CapacityConflict(detail)) => Some(detail),
+            _ => None,
+        }
+    }
+
+    pub fn invalid_argument(&self) -> Option<&InvalidArgumentDetail> {
+        match &self.detail {
+            Some(Detail::InvalidArgument(detail)) => Some(detail),
+            _ => None,
+        }
+    }
+}
+
+impl From<&ReservationConflict> for ConflictDetail {
+    fn from(conflict: &ReservationConflict) -> Self {
+        Self {
+            new: Some((&conflict.new).into()),
+            existing: Some((&conflict.old).into()),
+            conflicts: conflict.conflicts.clone(),
+            suggestions: conflict.suggestions.clone(),
+            relation: conflict.relation as i32,
+        }
+    }
+}
+
+impl TryFrom<&ConflictDetail> for ReservationConflict {
+    type Error = ();
+
+    fn try_from(detail: &ConflictDetail) -> Result<Self, Self::Error> {
+        Ok(Self {
+            new: detail.new.as_ref().ok_or(())?.into(),
+            old: detail.existing.as_ref().ok_or(())?.into(),
+            relation: detail.relation(),
+            conflicts: detail.conflicts.clone(),
+            suggestions: detail.suggestions.clone(),
+        })
+    }
+}
+
+impl From<&CapacityConflict> for CapacityConflictDetail {
+    fn from(conflict: &CapacityConflict) -> Self {
+        Self {
+            new: Some((&conflict.new).into()),
+            quantity: conflict.quantity,
+            capacity: conflict.capacity,
+            reserved: conflict.reserved,
+        }
+    }
+}
+
+impl TryFrom<&CapacityConflictDetail> for CapacityConflict {
+    type Error = ();
+
+    fn try_from(detail: &CapacityConflictDetail) -> Result<Self, Self::Error> {
+        Ok(Self {
+            new: detail.new.as_ref().ok_or(())?.into(),
+            quantity: detail.quantity,
+            capacity: detail.capacity,
+            reserved: detail.reserved,
+        })
+    }
+}
+
+impl From<&BatchConflictDetail> for Vec<(usize, BatchConflictInfo)> {
+    fn from(detail: &BatchConflictDetail) -> Self {
+        detail
+            .conflicts
+            .iter()
+            .map(|c| (c.index as usize, c.info()))
+            .collect()
+    }
+}
+
+impl BatchConflict {
+    /// the conflict is parsed from the description if it's not in the detail
+    pub fn info(&self) -> BatchConflictInfo {
+        if let Some(Ok(conflict)) = self
+            .capacity_conflict
+            .as_ref()
+            .map(CapacityConflict::try_from)
+        {
+            return BatchConflictInfo::Capacity(conflict);
+        }
+        match self.conflict.as_ref().map(ReservationConflict::try_from) {
+            Some(Ok(conflict)) => ReservationConflictInfo::Parsed(Box::new(conflict)).into(),
+            _ => self.description.parse().unwrap(),
+        }
+    }
+}
+
+impl From<&ReservationWindow> for ConflictWindow {
+    fn from(window: &ReservationWindow) -> Self {
+        Self {
+            id: window.id,
+            resource_id: window.rid.clone(),
+            start: window.start.as_ref().map(convert_to_timestamp),
+            end: window.end.as_ref().map(convert_to_timestamp),
+        }
+    }
+}
+
+impl From<&ConflictWindow> for ReservationWindow {
+    fn from(window: &ConflictWindow) -> Self {
+        Self {
+            id: window.id,
+            rid: window.resource_id.clone(),
+            start: window.start.as_ref().map(convert_to_utc_time),
+            end: window.end.as_ref().map(convert_to_utc_time),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FreeSlot, Reservation, ResourceRelation};
+
+    fn conflict() -> ReservationConflict {
+        ReservationConflict {
+            new: ReservationWindow {
+                id: 0,
+                rid: "ocean-view-room-713".to_string(),
+                start: Some("2022-12-26T15:00:00-0700".parse().unwrap()),
+                end: Some("2022-12-30T12:00:00-0700".parse().unwrap()),
+            },
+            old: ReservationWindow {
+                id: 1,
+                rid: "ocean-view-room-713".to_string(),
+                start: Some("2022-12-25T15:00:00-0700".parse().unwrap()),
+                end: Some("2022-12-28T12:00:00-0700".parse().unwrap()),
+            },
+            relation: ResourceRelation::Same,
+            conflicts: vec![Reservation {
+                id: 1,
+                ..Reservation::new_pending(
+                    "chalanziId",
+                    "ocean-view-room-713",
+                    "2022-12-25T15:00:00-0700".parse().unwrap(),
+                    "2022-12-28T12:00:00-0700".parse().unwrap(),
+                    "",
+                )
+            }],
+            suggestions: vec![FreeSlot {
+                resource_id: "ocean-view-room-713".to_string(),
+                start: Some(convert_to_timestamp(
+                    &"2022-12-28T12:00:00-0700".parse().unwrap(),
+                )),
+                end: Some(convert_to_timestamp(
+                    &"2023-01-01T09:00:00-0700".parse().unwrap(),
+                )),
+            }],
+        }
+    }
+
+    #[test]
+    fn conflict_should_be_sent_in_status_details() {
+        let err = Error::ConflictReservation(ReservationConflictInfo::Parsed(Box::new(conflict())));
+        let status: tonic::Status = err.into();
+        assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+
+        let detail = ErrorDetail::from_status(&status).unwrap();
+        let detail = detail.conflict().unwrap();
+        assert_eq!(detail.existing.as_ref().unwrap().id, 1);
+        assert_eq!(detail.new.as_ref().unwrap().id, 0);
+        assert_eq!(detail.conflicts.len(), 1);
+        assert_eq!(detail.suggestions.len(), 1);
+        assert_eq!(ReservationConflict::try_from(detail).unwrap(), conflict());
+
+        // the existing id is kept when the status is converted back
+        let err: Error = status.into();
+        assert_eq!(
+            err,
+            Error::ConflictReservation(ReservationConflictInfo::Parsed(Box::new(conflict())))
+        );
+    }
+
+    #[test]
+    fn batch_conflict_should_be_sent_in_status_details() {
+        let capacity = CapacityConflict {
+            new: conflict().new,
+            quantity: 2,
+            capacity: 3,
+            reserved: 2,
+        };
+        let conflicts = vec![
+            (
+                1,
+                ReservationConflictInfo::Parsed(Box::new(conflict())).into(),
+            ),
+            (
+                3,
+                ReservationConflictInfo::Unparsed("conflict".into()).into(),
+            ),
+            (4, capacity.into()),
+        ];
+        let status: tonic::Status = Error::BatchConflict(conflicts.clone()).into();
+        assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+
+        let detail = ErrorDetail::from_status(&status).unwrap();
+        let detail = detail.batch_conflict().unwrap();
+        assert_eq!(detail.conflicts.len(), 3);
+        assert_eq!(detail.conflicts[0].index, 1);
+        assert!(detail.conflicts[1].conflict.is_none());
+        assert_eq!(detail.conflicts[1].description, "conflict");
+        assert!(detail.conflicts[2].conflict.is_none());
+        assert!(detail.conflicts[2].capacity_conflict.is_some());
+
+        // the existing ids are kept, they are not in the message
+        let err: Error = status.into();
+        assert_eq!(err, Error::BatchConflict(conflicts));
+    }
+
+    #[test]
+    fn capacity_conflict_should_be_sent_in_status_details() {
+        let conflict = CapacityConflict {
+            new: conflict().new,
+            quantity: 2,
+            capacity: 3,
+            reserved: 2,
+        };
+        let status: tonic::Status = Error::CapacityExceeded(conflict.clone()).into();
+        assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+
+        let detail = ErrorDetail::from_status(&status).unwrap();
+        let detail = detail.capacity_conflict().unwrap();
+        assert_eq!(
+            detail.new.as_ref().unwrap().resource_id,
+            "ocean-view-room-713"
+        );
+        assert_eq!(CapacityConflict::try_from(detail).unwrap(), conflict);
+    }
+
+    #[test]
+    fn invalid_argument_should_be_sent_in_status_details() {
+        let status: tonic::Status = Error::InvalidPageSize(5).into();
+        let detail = ErrorDetail::from_status(&status).unwrap();
+        let detail = detail.invalid_argument().unwrap();
+        assert_eq!(detail.field, "page_size");
+        assert_eq!(detail.description, "Invalid page size: 5");
+
+        let status: tonic::Status = Error::InvalidTime.into();
+        let detail = ErrorDetail::from_status(&status).unwrap();
+        assert_eq!(detail.invalid_argument().unwrap().field, "timespan");
+
+        let status: tonic::Status = Error::RowNotFound.into();
+        assert_eq!(ErrorDetail::from_status(&status), None);
+        let status: tonic::Status =
+            Error::ConflictReservation(ReservationConflictInfo::Unparsed("conflict".into())).into();
+        assert_eq!(ErrorDetail::from_status(&status), None);
+    }
+}
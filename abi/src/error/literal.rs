@@ -0,0 +1,228 @@
+use std::{iter::Peekable, str::Chars};
+
+const KEY_PREFIX: &str = "Key ";
+const EXISTING_KEY: &str = " conflicts with existing key ";
+const COLUMNS: [&str; 2] = ["resource_id", "timespan"];
+
+/// text of a postgres range literal, the bounds are unquoted but not parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RangeLiteral {
+    Empty,
+    Range {
+        /// None if the range is unbounded at the start
+        lower: Option<String>,
+        /// None if the range is unbounded at the end
+        upper: Option<String>,
+        lower_inc: bool,
+        upper_inc: bool,
+    },
+}
+
+/// split the detail of the exclusion error into the candidates of (new key, existing key), e.g.
+/// Key (resource_id, timespan)=(room-1, [...)) conflicts with existing key (resource_id, timespan)=(room-1, [...)).
+/// the values are not quoted by postgres, so the resource id may contain the separator as well
+pub fn conflict_keys(s: &str) -> Vec<(&str, &str)> {
+    let Some((rest, separator)) = split_keys(s) else {
+        return vec![];
+    };
+    rest.match_indices(&separator)
+        .map(|(i, _)| (&rest[..i], &rest[i + separator.len()..]))
+        .collect()
+}
+
+/// candidates of (resource id, timespan) of a key, e.g. room-1, ["2022-12-26 22:00:00+00",infinity)
+pub fn key_values(s: &str) -> impl Iterator<Item = (&str, RangeLiteral)> {
+    s.match_indices(", ")
+        .filter_map(move |(i, sep)| Some((&s[..i], parse_range(&s[i + sep.len()..])?)))
+}
+
+/// parse a range literal in the output format of postgres, the whole string must be consumed
+pub fn parse_range(s: &str) -> Option<RangeLiteral> {
+    let s = s.trim();
+    if s.eq_ignore_ascii_case("empty") {
+        return Some(RangeLiteral::Empty);
+    }
+
+    let mut chars = s.chars().peekable();
+    let lower_inc = match chars.next()? {
+        '[' => true,
+        '(' => false,
+        _ => return None,
+    };
+    let lower = parse_bound(&mut chars)?;
+    if chars.next()? != ',' {
+        return None;
+    }
+    let upper = parse_bound(&mut chars)?;
+    let upper_inc = match chars.next()? {
+        ']' => true,
+        ')' => false,
+        _ => return None,
+    };
+    if chars.next().is_some() {
+        return None;
+    }
+
+    Some(RangeLiteral::Range {
+        lower,
+        upper,
+        lower_inc,
+        upper_inc,
+    })
+}
+
+/// the body of the two keys after the column list, and the separator between them
+fn split_keys(s: &str) -> Option<(&str, String)> {
+    let s = s.strip_prefix(KEY_PREFIX)?.strip_prefix('(')?;
+    let (columns, rest) = s.split_once(")=(")?;
+    if !columns.split(", ").eq(COLUMNS) {
+        return None;
+    }
+    let rest = rest.trim_end().strip_suffix(").")?;
+    let separator = format!("){}({})=(", EXISTING_KEY, columns);
+    Some((rest, separator))
+}
+
+/// parse a bound until the delimiter, which is not consumed. Some(None) if the bound is empty
+fn parse_bound(chars: &mut Peekable<Chars>) -> Option<Option<String>> {
+    let mut bound = String::new();
+    let mut quoted = false;
+    let mut in_quote = false;
+    loop {
+        match *chars.peek()? {
+            ',' | ')' | ']' | '(' | '[' if !in_quote => break,
+            _ => {}
+        }
+        match chars.next()? {
+            '\\' => bound.push(chars.next()?),
+            '"' if in_quote && chars.peek() == Some(&'"') => {
+                chars.next();
+                bound.push('"');
+            }
+            '"' => {
+                in_quote = !in_quote;
+                quoted = true;
+            }
+            ch => bound.push(ch),
+        }
+    }
+
+    // an empty quoted bound is an empty string rather than unbounded
+    if bound.is_empty() && !quoted {
+        return Some(None);
+    }
+    Some(Some(bound))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(lower: Option<&str>, upper: Option<&str>, inc: (bool, bool)) -> RangeLiteral {
+        RangeLiteral::Range {
+            lower: lower.map(|s| s.to_string()),
+            upper: upper.map(|s| s.to_string()),
+            lower_inc: inc.0,
+            upper_inc: inc.1,
+        }
+    }
+
+    #[test]
+    fn parse_range_should_work() {
+        let cases = [
+            (
+                r#"["2022-12-26 22:00:00+00","2022-12-30 19:00:00+00")"#,
+                range(
+                    Some("2022-12-26 22:00:00+00"),
+                    Some("2022-12-30 19:00:00+00"),
+                    (true, false),
+                ),
+            ),
+            (
+                r#"(,"2022-12-28 19:00:00.25+00")"#,
+                range(None, Some("2022-12-28 19:00:00.25+00"), (false, false)),
+            ),
+            (
+                r#"["2022-12-20 22:00:00+00",infinity)"#,
+                range(
+                    Some("2022-12-20 22:00:00+00"),
+                    Some("infinity"),
+                    (true, false),
+                ),
+            ),
+            ("(,)", range(None, None, (false, false))),
+            ("[1,2]", range(Some("1"), Some("2"), (true, true))),
+            (r#"["",""]"#, range(Some(""), Some(""), (true, true))),
+            (
+                r#"["a""b","c\\d"]"#,
+                range(Some("a\"b"), Some("c\\d"), (true, true)),
+            ),
+            (
+                r#"[a\,b,"x,)]"]"#,
+                range(Some("a,b"), Some("x,)]"), (true, true)),
+            ),
+            ("empty", RangeLiteral::Empty),
+            (" EMPTY ", RangeLiteral::Empty),
+        ];
+        for (s, expected) in cases {
+            assert_eq!(parse_range(s), Some(expected), "{}", s);
+        }
+    }
+
+    #[test]
+    fn parse_range_should_reject_invalid_literal() {
+        let cases = [
+            "",
+            "[",
+            "[1",
+            "[1,",
+            "[1,2",
+            "{1,2)",
+            "[1,2)x",
+            "[1,2))",
+            "[1;2)",
+            r#"["1,2)"#,
+            "[1,(2)",
+            "emptyish",
+        ];
+        for s in cases {
+            assert_eq!(parse_range(s), None, "{}", s);
+        }
+    }
+
+    #[test]
+    fn conflict_keys_should_split_on_every_candidate() {
+        let s = "Key (resource_id, timespan)=(a) conflicts with existing key (resource_id, timespan)=(b, (,)) conflicts with existing key (resource_id, timespan)=(c, (,)).";
+        assert_eq!(
+            conflict_keys(s),
+            vec![
+                (
+                    "a",
+                    "b, (,)) conflicts with existing key (resource_id, timespan)=(c, (,)"
+                ),
+                (
+                    "a) conflicts with existing key (resource_id, timespan)=(b, (,)",
+                    "c, (,)"
+                ),
+            ]
+        );
+
+        assert_eq!(
+            conflict_keys("Key (id)=(1) conflicts with existing key (id)=(1).").len(),
+            0
+        );
+        assert_eq!(conflict_keys("unknown").len(), 0);
+    }
+
+    #[test]
+    fn key_values_should_try_every_separator() {
+        let values: Vec<_> = key_values(r#"room 1, "a" (b).c, (,"x")"#).collect();
+        assert_eq!(
+            values,
+            vec![(
+                r#"room 1, "a" (b).c"#,
+                range(None, Some("x"), (false, false))
+            )]
+        );
+    }
+}
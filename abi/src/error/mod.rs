@@ -1,8 +1,19 @@
 mod conflict;
-pub use conflict::{ReservationConflict, ReservationConflictInfo, ReservationWindow};
+mod detail;
+mod literal;
+pub use conflict::{
+    BatchConflictInfo, CapacityConflict, ReservationConflict, ReservationConflictInfo,
+    ReservationWindow,
+};
 
+use prost::Message;
 use sqlx::postgres::PgDatabaseError;
 
+/// prefix of the status message of a conflict reservation, followed by the conflict info
+const CONFLICT_PREFIX: &str = "Conflict reservation: ";
+const BATCH_CONFLICT_PREFIX: &str = "Conflict reservations in batch: ";
+const CAPACITY_PREFIX: &str = "Capacity exceeded: ";
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("Database error")]
@@ -19,6 +30,11 @@ pub enum Error {
 
     #[error("Conflict reservation")]
     ConflictReservation(ReservationConflictInfo),
+    /// the conflicts of a batch with the index of the reservation in it, order by the index
+    #[error("Conflict reservations in batch")]
+    BatchConflict(Vec<(usize, BatchConflictInfo)>),
+    #[error("Capacity exceeded: {0}")]
+    CapacityExceeded(CapacityConflict),
     #[error("Invalid user id: {0}")]
     InvalidUserId(String),
     #[error("Invalid resource id: {0}")]
@@ -27,12 +43,50 @@ pub enum Error {
     InvalidReservationId(i64),
     #[error("Invalid header (expected {0}, found {1})")]
     InvalidHeader(String, String),
+    #[error("Invalid page: {0}")]
+    InvalidPage(i64),
     #[error("Invalid page size: {0}")]
     InvalidPageSize(i64),
     #[error("Invalid cursor: {0}")]
     InvalidCursor(i64),
+    #[error("Invalid page token: {0}")]
+    InvalidPageToken(String),
     #[error("Invalid reservation status: {0}")]
     InvalidStatus(i32),
+    #[error("Invalid match mode: {0}")]
+    InvalidMatchMode(i32),
+    #[error("Invalid consumer name: {0}")]
+    InvalidConsumer(String),
+    #[error("Invalid duration: {0}")]
+    InvalidDuration(i64),
+    #[error("Invalid granularity: {0}")]
+    InvalidGranularity(i64),
+    #[error("Invalid recurrence rule: {0}")]
+    InvalidRecurrence(String),
+    #[error("Invalid series id: {0}")]
+    InvalidSeriesId(i64),
+    #[error("Invalid series scope: {0}")]
+    InvalidScope(i32),
+    #[error("Invalid timezone: {0}")]
+    InvalidTimezone(String),
+    #[error("Resource not found: {0}")]
+    ResourceNotFound(String),
+    #[error("Resource already exists: {0}")]
+    ResourceExists(String),
+    #[error("Inactive resource: {0}")]
+    InactiveResource(String),
+    #[error("Invalid quantity: {0}")]
+    InvalidQuantity(i32),
+    #[error("Invalid capacity: {0}")]
+    InvalidCapacity(i32),
+    #[error("Invalid resource pool: {0}")]
+    InvalidPool(String),
+    #[error("Invalid parent resource: {0}")]
+    InvalidParent(String),
+    #[error("No available resource of type: {0}")]
+    NoAvailableResource(String),
+    #[error("Rpc error ({0:?}): {1}")]
+    RpcError(tonic::Code, String),
     #[error("unknown data store error")]
     Unknown,
 }
@@ -44,10 +98,34 @@ impl PartialEq for Error {
             (Self::DbError(_), Self::DbError(_)) => true,
             (Self::InvalidTime, Self::InvalidTime) => true,
             (Self::ConflictReservation(v1), Self::ConflictReservation(v2)) => v1 == v2,
+            (Self::BatchConflict(v1), Self::BatchConflict(v2)) => v1 == v2,
             (Self::RowNotFound, Self::RowNotFound) => true,
             (Self::InvalidReservationId(v1), Self::InvalidReservationId(v2)) => v1 == v2,
             (Self::InvalidUserId(v1), Self::InvalidUserId(v2)) => v1 == v2,
             (Self::InvalidResourceId(v1), Self::InvalidResourceId(v2)) => v1 == v2,
+            (Self::InvalidPage(v1), Self::InvalidPage(v2)) => v1 == v2,
+            (Self::InvalidPageSize(v1), Self::InvalidPageSize(v2)) => v1 == v2,
+            (Self::InvalidCursor(v1), Self::InvalidCursor(v2)) => v1 == v2,
+            (Self::InvalidStatus(v1), Self::InvalidStatus(v2)) => v1 == v2,
+            (Self::InvalidMatchMode(v1), Self::InvalidMatchMode(v2)) => v1 == v2,
+            (Self::InvalidPageToken(v1), Self::InvalidPageToken(v2)) => v1 == v2,
+            (Self::InvalidConsumer(v1), Self::InvalidConsumer(v2)) => v1 == v2,
+            (Self::InvalidDuration(v1), Self::InvalidDuration(v2)) => v1 == v2,
+            (Self::InvalidGranularity(v1), Self::InvalidGranularity(v2)) => v1 == v2,
+            (Self::InvalidRecurrence(v1), Self::InvalidRecurrence(v2)) => v1 == v2,
+            (Self::InvalidSeriesId(v1), Self::InvalidSeriesId(v2)) => v1 == v2,
+            (Self::InvalidScope(v1), Self::InvalidScope(v2)) => v1 == v2,
+            (Self::InvalidTimezone(v1), Self::InvalidTimezone(v2)) => v1 == v2,
+            (Self::ResourceNotFound(v1), Self::ResourceNotFound(v2)) => v1 == v2,
+            (Self::ResourceExists(v1), Self::ResourceExists(v2)) => v1 == v2,
+            (Self::InactiveResource(v1), Self::InactiveResource(v2)) => v1 == v2,
+            (Self::InvalidQuantity(v1), Self::InvalidQuantity(v2)) => v1 == v2,
+            (Self::InvalidCapacity(v1), Self::InvalidCapacity(v2)) => v1 == v2,
+            (Self::InvalidPool(v1), Self::InvalidPool(v2)) => v1 == v2,
+            (Self::InvalidParent(v1), Self::InvalidParent(v2)) => v1 == v2,
+            (Self::NoAvailableResource(v1), Self::NoAvailableResource(v2)) => v1 == v2,
+            (Self::CapacityExceeded(v1), Self::CapacityExceeded(v2)) => v1 == v2,
+            (Self::RpcError(c1, m1), Self::RpcError(c2, m2)) => c1 == c2 && m1 == m2,
             (Self::Unknown, Self::Unknown) => true,
             _ => false,
         }
@@ -63,6 +141,28 @@ impl From<sqlx::Error> for Error {
                     ("23P01", Some("rsvp"), Some("reservations")) => {
                         Error::ConflictReservation(err.detail().unwrap().parse().unwrap())
                     }
+                    // raised by the trigger checking the capacity of a shared resource
+                    ("23514", Some("rsvp"), Some("reservations"))
+                        if err.constraint() == Some("reservations_capacity") =>
+                    {
+                        match err.detail().and_then(|d| d.parse().ok()) {
+                            Some(conflict) => Error::CapacityExceeded(conflict),
+                            None => Error::DbError(sqlx::Error::Database(e)),
+                        }
+                    }
+                    // raised by the trigger checking the reserved resource is active
+                    ("23514", Some("rsvp"), Some("reservations"))
+                        if err.constraint() == Some("reservations_resource_active") =>
+                    {
+                        Error::InactiveResource(parse_key_value(err.detail().unwrap_or_default()))
+                    }
+                    // the reserved resource, or the parent of a resource, is not in the catalog
+                    ("23503", Some("rsvp"), Some("reservations" | "resources")) => {
+                        Error::ResourceNotFound(parse_key_value(err.detail().unwrap_or_default()))
+                    }
+                    ("23505", Some("rsvp"), Some("resources")) => {
+                        Error::ResourceExists(parse_key_value(err.detail().unwrap_or_default()))
+                    }
                     _ => Error::DbError(sqlx::Error::Database(e)),
                 }
             }
@@ -74,7 +174,8 @@ impl From<sqlx::Error> for Error {
 
 impl From<crate::Error> for tonic::Status {
     fn from(e: crate::Error) -> Self {
-        match e {
+        let detail = e.detail();
+        let status = match e {
             Error::DbError(_) | Error::ConfigReadError | Error::ConfigParseError => {
                 tonic::Status::internal(e.to_string())
             }
@@ -82,11 +183,41 @@ impl From<crate::Error> for tonic::Status {
             | Error::InvalidReservationId(_)
             | Error::InvalidUserId(_)
             | Error::InvalidResourceId(_)
+            | Error::InvalidPage(_)
             | Error::InvalidPageSize(_)
             | Error::InvalidCursor(_)
-            | Error::InvalidStatus(_) => tonic::Status::invalid_argument(e.to_string()),
+            | Error::InvalidPageToken(_)
+            | Error::InvalidStatus(_)
+            | Error::InvalidMatchMode(_)
+            | Error::InvalidConsumer(_)
+            | Error::InvalidDuration(_)
+            | Error::InvalidGranularity(_)
+            | Error::InvalidRecurrence(_)
+            | Error::InvalidSeriesId(_)
+            | Error::InvalidScope(_)
+            | Error::InvalidTimezone(_)
+            | Error::InvalidQuantity(_)
+            | Error::InvalidCapacity(_)
+            | Error::InvalidPool(_)
+            | Error::InvalidParent(_) => tonic::Status::invalid_argument(e.to_string()),
+            Error::ResourceNotFound(_) => tonic::Status::not_found(e.to_string()),
+            Error::ResourceExists(_) => tonic::Status::already_exists(e.to_string()),
+            Error::InactiveResource(_)
+            | Error::CapacityExceeded(_)
+            | Error::NoAvailableResource(_) => tonic::Status::failed_precondition(e.to_string()),
             Error::ConflictReservation(info) => {
-                tonic::Status::failed_precondition(format!("Conflict reservation: {:?}", info))
+                tonic::Status::failed_precondition(format!("{}{}", CONFLICT_PREFIX, info))
+            }
+            Error::BatchConflict(conflicts) => {
+                let conflicts: Vec<_> = conflicts
+                    .iter()
+                    .map(|(index, info)| format!("#{} {}", index, info))
+                    .collect();
+                tonic::Status::failed_precondition(format!(
+                    "{}{}",
+                    BATCH_CONFLICT_PREFIX,
+                    conflicts.join("\n")
+                ))
             }
             Error::RowNotFound => {
                 tonic::Status::not_found("No reservation found by the given condition")
@@ -95,7 +226,206 @@ impl From<crate::Error> for tonic::Status {
                 "Invalid header (expected {:?}, found {:?})",
                 expected, found
             )),
+            Error::RpcError(code, message) => tonic::Status::new(code, message),
             Error::Unknown => tonic::Status::unknown("unknown error"),
+        };
+
+        match detail {
+            Some(detail) => tonic::Status::with_details(
+                status.code(),
+                status.message(),
+                detail.encode_to_vec().into(),
+            ),
+            None => status,
         }
     }
 }
+
+/// convert the status returned by the server back to the error, the status not converted from an
+/// error is kept as RpcError
+impl From<tonic::Status> for Error {
+    fn from(status: tonic::Status) -> Self {
+        let err = match status.code() {
+            tonic::Code::NotFound => match parse_resource_error(status.message()) {
+                Some(e @ Error::ResourceNotFound(_)) => Some(e),
+                _ => Some(Error::RowNotFound),
+            },
+            tonic::Code::AlreadyExists => parse_resource_error(status.message()),
+            tonic::Code::FailedPrecondition => {
+                if let Some(e) = parse_resource_error(status.message()) {
+                    return e;
+                }
+                if let Some(s) = status.message().strip_prefix(CAPACITY_PREFIX) {
+                    if let Ok(conflict) = s.parse() {
+                        return Error::CapacityExceeded(conflict);
+                    }
+                }
+                let detail = crate::ErrorDetail::from_status(&status);
+                if let Some(batch) = detail.as_ref().and_then(|d| d.batch_conflict()) {
+                    return Error::BatchConflict(batch.into());
+                }
+                if let Some(s) = status.message().strip_prefix(BATCH_CONFLICT_PREFIX) {
+                    if let Some(conflicts) = parse_batch_conflicts(s) {
+                        return Error::BatchConflict(conflicts);
+                    }
+                }
+
+                let conflict = detail.and_then(|detail| detail.conflict()?.try_into().ok());
+                match conflict {
+                    Some(conflict) => Some(Error::ConflictReservation(
+                        ReservationConflictInfo::Parsed(Box::new(conflict)),
+                    )),
+                    None => status
+                        .message()
+                        .strip_prefix(CONFLICT_PREFIX)
+                        .map(|info| Error::ConflictReservation(info.parse().unwrap())),
+                }
+            }
+            tonic::Code::InvalidArgument => parse_invalid_argument(status.message()),
+            _ => None,
+        };
+        err.unwrap_or_else(|| Error::RpcError(status.code(), status.message().to_string()))
+    }
+}
+
+/// one conflict a line, e.g. #1 Key (resource_id, timespan)=...
+fn parse_batch_conflicts(s: &str) -> Option<Vec<(usize, BatchConflictInfo)>> {
+    s.split('\n')
+        .map(|line| {
+            let (index, info) = line.strip_prefix('#')?.split_once(' ')?;
+            Some((index.parse().ok()?, info.parse().unwrap()))
+        })
+        .collect()
+}
+
+/// the value of the key in the detail of a constraint violation,
+/// e.g. Key (resource_id)=(room-1) is not present in table "resources".
+fn parse_key_value(detail: &str) -> String {
+    detail
+        .split_once(")=(")
+        .and_then(|(_, s)| s.rsplit_once(')'))
+        .map(|(value, _)| value.to_string())
+        .unwrap_or_default()
+}
+
+fn parse_resource_error(msg: &str) -> Option<Error> {
+    let (kind, value) = msg.split_once(": ")?;
+    let err = match kind {
+        "Resource not found" => Error::ResourceNotFound(value.to_string()),
+        "Resource already exists" => Error::ResourceExists(value.to_string()),
+        "Inactive resource" => Error::InactiveResource(value.to_string()),
+        "No available resource of type" => Error::NoAvailableResource(value.to_string()),
+        _ => return None,
+    };
+    Some(err)
+}
+
+fn parse_invalid_argument(msg: &str) -> Option<Error> {
+    if msg == Error::InvalidTime.to_string() {
+        return Some(Error::InvalidTime);
+    }
+
+    let (kind, value) = msg.split_once(": ")?;
+    let err = match kind {
+        "Invalid user id" => Error::InvalidUserId(value.to_string()),
+        "Invalid resource id" => Error::InvalidResourceId(value.to_string()),
+        "Invalid reservation id" => Error::InvalidReservationId(value.parse().ok()?),
+        "Invalid page" => Error::InvalidPage(value.parse().ok()?),
+        "Invalid page size" => Error::InvalidPageSize(value.parse().ok()?),
+        "Invalid cursor" => Error::InvalidCursor(value.parse().ok()?),
+        "Invalid page token" => Error::InvalidPageToken(value.to_string()),
+        "Invalid reservation status" => Error::InvalidStatus(value.parse().ok()?),
+        "Invalid match mode" => Error::InvalidMatchMode(value.parse().ok()?),
+        "Invalid consumer name" => Error::InvalidConsumer(value.to_string()),
+        "Invalid duration" => Error::InvalidDuration(value.parse().ok()?),
+        "Invalid granularity" => Error::InvalidGranularity(value.parse().ok()?),
+        "Invalid recurrence rule" => Error::InvalidRecurrence(value.to_string()),
+        "Invalid series id" => Error::InvalidSeriesId(value.parse().ok()?),
+        "Invalid series scope" => Error::InvalidScope(value.parse().ok()?),
+        "Invalid timezone" => Error::InvalidTimezone(value.to_string()),
+        "Invalid quantity" => Error::InvalidQuantity(value.parse().ok()?),
+        "Invalid capacity" => Error::InvalidCapacity(value.parse().ok()?),
+        "Invalid resource pool" => Error::InvalidPool(value.to_string()),
+        "Invalid parent resource" => Error::InvalidParent(value.to_string()),
+        _ => return None,
+    };
+    Some(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_should_convert_back_from_status() {
+        let conflict = "Key (resource_id, timespan)=(ocean-view-room-713, [\"2022-12-26 22:00:00+00\",\"2022-12-30 19:00:00+00\")) conflicts with existing key (resource_id, timespan)=(ocean-view-room-713, [\"2022-12-25 22:00:00+00\",\"2022-12-28 19:00:00+00\")).";
+        let capacity = "Key (resource_id, timespan)=(desk-1, [\"2022-12-26 22:00:00+00\",\"2022-12-30 19:00:00+00\")) requests 2 of capacity 3, 2 reserved.";
+        let errors = || {
+            vec![
+                Error::RowNotFound,
+                Error::InvalidTime,
+                Error::ConflictReservation(conflict.parse().unwrap()),
+                Error::ConflictReservation(ReservationConflictInfo::Unparsed("conflict".into())),
+                Error::BatchConflict(vec![
+                    (0, conflict.parse().unwrap()),
+                    (
+                        2,
+                        ReservationConflictInfo::Unparsed("conflict".into()).into(),
+                    ),
+                    (3, capacity.parse().unwrap()),
+                ]),
+                Error::InvalidUserId("".into()),
+                Error::InvalidResourceId("room: 1".into()),
+                Error::InvalidReservationId(-1),
+                Error::InvalidPage(-2),
+                Error::InvalidPageSize(5),
+                Error::InvalidCursor(-3),
+                Error::InvalidPageToken("bad token".into()),
+                Error::InvalidStatus(10),
+                Error::InvalidMatchMode(3),
+                Error::InvalidConsumer("".into()),
+                Error::InvalidDuration(-1),
+                Error::InvalidGranularity(-60),
+                Error::InvalidRecurrence("count or until is required".into()),
+                Error::InvalidSeriesId(7),
+                Error::InvalidScope(5),
+                Error::InvalidTimezone("Mars/Olympus".into()),
+                Error::ResourceNotFound("room-404".into()),
+                Error::ResourceExists("room-1".into()),
+                Error::InactiveResource("room-2".into()),
+                Error::InvalidQuantity(-1),
+                Error::InvalidCapacity(-2),
+                Error::InvalidPool("type is required".into()),
+                Error::InvalidParent("room-1".into()),
+                Error::NoAvailableResource("room".into()),
+                Error::CapacityExceeded(CapacityConflict {
+                    new: ReservationWindow {
+                        id: 0,
+                        rid: "desk, 1".into(),
+                        start: Some("2022-12-26T22:00:00Z".parse().unwrap()),
+                        end: Some("2022-12-30T19:00:00Z".parse().unwrap()),
+                    },
+                    quantity: 2,
+                    capacity: 3,
+                    reserved: 2,
+                }),
+            ]
+        };
+        for (err, expected) in errors().into_iter().zip(errors()) {
+            let status: tonic::Status = err.into();
+            assert_eq!(Error::from(status), expected);
+        }
+
+        assert_eq!(
+            parse_key_value(r#"Key (resource_id)=(room-(1)) is not present in table "resources"."#),
+            "room-(1)"
+        );
+
+        let status = tonic::Status::unavailable("connection refused");
+        let err = Error::from(status);
+        assert_eq!(
+            err,
+            Error::RpcError(tonic::Code::Unavailable, "connection refused".into())
+        );
+    }
+}
@@ -6,14 +6,21 @@ mod types;
 mod utils;
 
 pub use config::*;
-pub use error::{Error, ReservationConflict, ReservationConflictInfo, ReservationWindow};
+pub use error::{
+    BatchConflictInfo, CapacityConflict, Error, ReservationConflict, ReservationConflictInfo,
+    ReservationWindow,
+};
 pub use pb::*;
 pub use types::*;
 pub use utils::*;
 
+use sqlx::{Postgres, QueryBuilder};
+
 pub type ReservationId = i64;
 pub type UserId = String;
 pub type ResourceId = String;
+pub type ChangeId = i64;
+pub type SeriesId = i64;
 
 pub trait Validator {
     fn validate(&self) -> Result<(), Error>;
@@ -32,8 +39,10 @@ pub trait Normalizer: Validator {
     fn do_normalize(&mut self);
 }
 
+/// build the sql for the data structure, the values given by users are bound as arguments
+/// instead of being formatted into the sql
 pub trait ToSql {
-    fn to_sql(&self) -> String;
+    fn to_query(&self) -> QueryBuilder<'static, Postgres>;
 }
 
 /// database equivalent of the "reservation_status" enum
@@ -47,6 +56,17 @@ pub enum RsvpStatus {
     Blocked,
 }
 
+/// database equivalent of the "reservation_update_type" enum
+/// 数据库 reservation_update_type 枚举
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "reservation_update_type", rename_all = "lowercase")]
+pub enum RsvpUpdateType {
+    Unknown,
+    Create,
+    Update,
+    Delete,
+}
+
 impl Validator for ReservationId {
     fn validate(&self) -> Result<(), Error> {
         if *self <= 0 {
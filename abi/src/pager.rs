@@ -4,6 +4,8 @@ pub struct PageInfo {
     pub cursor: Option<i64>,
     pub page_size: i64,
     pub desc: bool,
+    /// page backward from the cursor, the data is fetched in the reversed direction
+    pub backward: bool,
 }
 
 pub struct Pager {
@@ -24,20 +26,37 @@ pub trait Id {
 
 impl Paginator for PageInfo {
     fn get_pager<T: Id>(&self, data: &mut VecDeque<T>) -> Pager {
-        let has_prev = self.cursor.is_some();
-        let prev = if has_prev {
-            data.pop_front();
-            data.front().map(|v| v.id())
+        // the row at the cursor belongs to the adjacent page, and one more row is fetched
+        // to check if there is another page in the paging direction
+        let (prev, next) = if self.backward {
+            data.make_contiguous().reverse();
+            let next = if self.cursor.is_some() {
+                data.pop_back();
+                data.back().map(|v| v.id())
+            } else {
+                None
+            };
+            let prev = if data.len() as i64 > self.page_size {
+                data.pop_front();
+                data.front().map(|v| v.id())
+            } else {
+                None
+            };
+            (prev, next)
         } else {
-            None
-        };
-
-        let has_next = data.len() as i64 > self.page_size;
-        let next = if has_next {
-            data.pop_back();
-            data.back().map(|v| v.id())
-        } else {
-            None
+            let prev = if self.cursor.is_some() {
+                data.pop_front();
+                data.front().map(|v| v.id())
+            } else {
+                None
+            };
+            let next = if data.len() as i64 > self.page_size {
+                data.pop_back();
+                data.back().map(|v| v.id())
+            } else {
+                None
+            };
+            (prev, next)
         };
 
         Pager {
@@ -53,6 +72,7 @@ impl Paginator for PageInfo {
                 cursor: pager.next,
                 page_size: self.page_size,
                 desc: self.desc,
+                backward: false,
             })
         } else {
             None
@@ -65,6 +85,7 @@ impl Paginator for PageInfo {
                 cursor: pager.prev,
                 page_size: self.page_size,
                 desc: self.desc,
+                backward: true,
             })
         } else {
             None
@@ -99,6 +120,7 @@ mod tests {
             cursor: None,
             page_size: 10,
             desc: false,
+            backward: false,
         };
 
         let mut items = pager_test_utils::generate_test_ids(1, 11);
@@ -133,4 +155,35 @@ mod tests {
             assert_eq!(prev_page.unwrap().cursor, Some(21));
         }
     }
+
+    #[test]
+    fn paginator_should_page_backward() {
+        let page = PageInfo {
+            cursor: Some(21),
+            page_size: 10,
+            desc: false,
+            backward: true,
+        };
+
+        // fetched in the reversed direction
+        let mut items = pager_test_utils::generate_test_ids(10, 21)
+            .into_iter()
+            .rev()
+            .collect();
+        let pager = page.get_pager(&mut items);
+        assert_eq!(pager.prev, Some(11));
+        assert_eq!(pager.next, Some(20));
+        assert_eq!(items.front().unwrap().id(), 11);
+        assert_eq!(items.back().unwrap().id(), 20);
+
+        let page = page.prev_page(&pager).unwrap();
+        let mut items = pager_test_utils::generate_test_ids(1, 11)
+            .into_iter()
+            .rev()
+            .collect();
+        let pager = page.get_pager(&mut items);
+        assert_eq!(pager.prev, None);
+        assert_eq!(pager.next, Some(10));
+        assert_eq!(items.len(), 10);
+    }
 }
@@ -1,4 +1,5 @@
 /// 预约资源信息
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Reservation {
     #[prost(int64, tag = "1")]
@@ -17,54 +18,197 @@ pub struct Reservation {
     /// 额外信息
     #[prost(string, tag = "7")]
     pub note: ::prost::alloc::string::String,
+    /// id of the series if the reservation is an occurrence of a recurring one, 0 otherwise
+    #[prost(int64, tag = "8")]
+    pub series_id: i64,
+    /// units of the resource reserved, only a resource of capacity more than 1 could be reserved
+    /// by more than 1 unit. 1 if it's 0
+    #[prost(int32, tag = "9")]
+    pub quantity: i32,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ReservationRequest {
     #[prost(message, optional, tag = "1")]
     pub reservation: ::core::option::Option<Reservation>,
+    /// if the reservation conflicts, the nearest free windows of the same length are suggested in
+    /// the conflict detail
+    #[prost(bool, tag = "2")]
+    pub suggest: bool,
+    /// the resources to try the same window on when suggesting, e.g. the rooms of the same type
+    #[prost(string, repeated, tag = "3")]
+    pub alternative_resource_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// validate the reservation and test it against the existing ones without making it. The id of
+    /// the returned reservation is 0
+    #[prost(bool, tag = "4")]
+    pub dry_run: bool,
+    /// reserve any free resource of the pool instead of the resource_id of the reservation, which
+    /// must be empty. The returned reservation carries the chosen one. It can't be combined with
+    /// suggest or alternative_resource_ids
+    #[prost(message, optional, tag = "5")]
+    pub pool: ::core::option::Option<ResourcePool>,
+}
+/// the active resources of a type in the catalog
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResourcePool {
+    #[prost(string, tag = "1")]
+    pub r#type: ::prost::alloc::string::String,
+    #[prost(enumeration = "AllocationStrategy", tag = "2")]
+    pub strategy: i32,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ReservationResponse {
     #[prost(message, optional, tag = "1")]
     pub reservation: ::core::option::Option<Reservation>,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReserveBatchRequest {
+    /// made in one transaction, either all of them or none
+    #[prost(message, repeated, tag = "1")]
+    pub reservations: ::prost::alloc::vec::Vec<Reservation>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReserveBatchResponse {
+    /// in the same order as the request
+    #[prost(message, repeated, tag = "1")]
+    pub reservations: ::prost::alloc::vec::Vec<Reservation>,
+}
+/// RRULE-style recurrence of a reservation, the reservation itself is the first occurrence
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RecurrenceRule {
+    #[prost(enumeration = "RecurrenceFrequency", tag = "1")]
+    pub frequency: i32,
+    /// every `interval` days, weeks or months. 1 if it's 0
+    #[prost(int64, tag = "2")]
+    pub interval: i64,
+    /// the weekdays of a weekly rule, the weekday of the reservation if empty
+    #[prost(enumeration = "Weekday", repeated, tag = "3")]
+    pub by_day: ::prost::alloc::vec::Vec<i32>,
+    /// the number of occurrences, including the exceptions. Either count or until is required
+    #[prost(int64, tag = "4")]
+    pub count: i64,
+    /// the last occurrence starts before or at it
+    #[prost(message, optional, tag = "5")]
+    pub until: ::core::option::Option<::prost_types::Timestamp>,
+    /// the start times of the occurrences which are skipped
+    #[prost(message, repeated, tag = "6")]
+    pub exceptions: ::prost::alloc::vec::Vec<::prost_types::Timestamp>,
+    /// offset from UTC in seconds, the days, weeks and months are counted in it. Only used if the
+    /// timezone is empty
+    #[prost(int64, tag = "7")]
+    pub utc_offset: i64,
+    /// IANA name of the timezone, e.g. America/Los_Angeles. The days, weeks and months are counted in
+    /// it and the occurrences keep their local time across the daylight saving changes
+    #[prost(string, tag = "8")]
+    pub timezone: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReserveSeriesRequest {
+    /// the first occurrence
+    #[prost(message, optional, tag = "1")]
+    pub reservation: ::core::option::Option<Reservation>,
+    #[prost(message, optional, tag = "2")]
+    pub rule: ::core::option::Option<RecurrenceRule>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReserveSeriesResponse {
+    /// order by start time, the index of the conflicts is the one in it
+    #[prost(message, repeated, tag = "1")]
+    pub reservations: ::prost::alloc::vec::Vec<Reservation>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateSeriesRequest {
+    /// id of an occurrence
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    #[prost(enumeration = "SeriesScope", tag = "2")]
+    pub scope: i32,
+    /// the new note, not changed if not set
+    #[prost(string, optional, tag = "3")]
+    pub note: ::core::option::Option<::prost::alloc::string::String>,
+    /// the new start and end time of the given occurrence. The other occurrences in the scope are
+    /// moved by the same offsets. Not changed if not set
+    #[prost(message, optional, tag = "4")]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "5")]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateSeriesResponse {
+    /// order by start time
+    #[prost(message, repeated, tag = "1")]
+    pub reservations: ::prost::alloc::vec::Vec<Reservation>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelSeriesRequest {
+    /// id of an occurrence
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    #[prost(enumeration = "SeriesScope", tag = "2")]
+    pub scope: i32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelSeriesResponse {
+    /// the canceled ones, order by start time
+    #[prost(message, repeated, tag = "1")]
+    pub reservations: ::prost::alloc::vec::Vec<Reservation>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateRequest {
     #[prost(int64, tag = "1")]
     pub id: i64,
     #[prost(string, tag = "2")]
-    pub node: ::prost::alloc::string::String,
+    pub note: ::prost::alloc::string::String,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateResponse {
     #[prost(message, optional, tag = "1")]
     pub reservation: ::core::option::Option<Reservation>,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ConfirmRequest {
     #[prost(int64, tag = "1")]
     pub id: i64,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ConfirmResponse {
     #[prost(message, optional, tag = "1")]
     pub reservation: ::core::option::Option<Reservation>,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CancelRequest {
     #[prost(int64, tag = "1")]
     pub id: i64,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CancelResponse {
     #[prost(message, optional, tag = "1")]
     pub reservation: ::core::option::Option<Reservation>,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetRequest {
     #[prost(int64, tag = "1")]
     pub id: i64,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetResponse {
     #[prost(message, optional, tag = "1")]
@@ -73,6 +217,7 @@ pub struct GetResponse {
 /// query reservation with user_id, resource_id, start time, end time and status
 #[derive(derive_builder::Builder)]
 #[builder(build_fn(name = "private_build"))]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ReservationQuery {
     /// resource id for the reservation query. If empty, query all resources
@@ -95,11 +240,11 @@ pub struct ReservationQuery {
     #[prost(message, optional, tag = "5")]
     #[builder(setter(into, strip_option), default)]
     pub end: ::core::option::Option<::prost_types::Timestamp>,
-    /// current page for the query
+    /// current page for the query, start from 1
     #[prost(int64, tag = "6")]
     #[builder(setter(into), default)]
     pub page: i64,
-    /// page size for the query
+    /// page size for the query. If 0, return all reservations without paging
     #[prost(int64, tag = "7")]
     #[builder(setter(into), default = "10")]
     pub page_size: i64,
@@ -107,8 +252,17 @@ pub struct ReservationQuery {
     #[prost(bool, tag = "8")]
     #[builder(setter(into), default)]
     pub desc: bool,
+    /// match any of the statuses (and status). If all of them are UNKNOWN, return all reservations
+    #[prost(enumeration = "ReservationStatus", repeated, tag = "9")]
+    #[builder(setter(into), default)]
+    pub statuses: ::prost::alloc::vec::Vec<i32>,
+    /// how the reservations match the start and end time. If CONTAINED, the reservations shall be entirely inside
+    #[prost(enumeration = "ReservationMatchMode", tag = "10")]
+    #[builder(setter(into), default)]
+    pub match_mode: i32,
 }
 /// To query reservations,send a QueryRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct QueryRequest {
     #[prost(message, optional, tag = "1")]
@@ -117,6 +271,7 @@ pub struct QueryRequest {
 /// To query reservations,order by reservation id
 #[derive(derive_builder::Builder)]
 #[builder(build_fn(name = "private_build"))]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ReservationFilter {
     /// resource id for the reservation query. If empty, query all resources
@@ -143,14 +298,27 @@ pub struct ReservationFilter {
     #[prost(bool, tag = "6")]
     #[builder(setter(into), default)]
     pub desc: bool,
+    /// page backward from the cursor (to the previous page)
+    #[prost(bool, tag = "7")]
+    #[builder(setter(into), default)]
+    pub backward: bool,
+    /// also count all the reservations matching the filter
+    #[prost(bool, tag = "8")]
+    #[builder(setter(into), default)]
+    pub with_total: bool,
+    /// match any of the statuses (and status). If all of them are UNKNOWN, return all reservations
+    #[prost(enumeration = "ReservationStatus", repeated, tag = "9")]
+    #[builder(setter(into), default)]
+    pub statuses: ::prost::alloc::vec::Vec<i32>,
 }
 /// 分页数据
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FilterPager {
-    /// 前一页数据
+    /// 前一页数据 (cursor for filter, page number for query)
     #[prost(int64, optional, tag = "1")]
     pub prev: ::core::option::Option<i64>,
-    /// 后一页数据
+    /// 后一页数据 (cursor for filter, page number for query)
     #[prost(int64, optional, tag = "2")]
     pub next: ::core::option::Option<i64>,
     /// 总数
@@ -158,26 +326,335 @@ pub struct FilterPager {
     pub total: ::core::option::Option<i64>,
 }
 /// To query reservation,send a QueryOrderById
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FilterRequest {
+    /// the cursor and backward of the filter are ignored, use page_token to get another page
     #[prost(message, optional, tag = "1")]
     pub filter: ::core::option::Option<ReservationFilter>,
+    /// prev_page_token or next_page_token of the last response, it could only be used with the same filter
+    #[prost(string, tag = "2")]
+    pub page_token: ::prost::alloc::string::String,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FilterResponse {
     #[prost(message, repeated, tag = "1")]
     pub reservations: ::prost::alloc::vec::Vec<Reservation>,
+    /// only total is set, prev and next are given as page tokens
     #[prost(message, optional, tag = "2")]
     pub pager: ::core::option::Option<FilterPager>,
+    /// opaque token of the previous page. If empty, there is no previous page
+    #[prost(string, tag = "3")]
+    pub prev_page_token: ::prost::alloc::string::String,
+    /// opaque token of the next page. If empty, there is no next page
+    #[prost(string, tag = "4")]
+    pub next_page_token: ::prost::alloc::string::String,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ListenRequest {}
+pub struct ListenRequest {
+    /// replay the changes after this change id. If not set, resume from the last acknowledged change of the consumer,
+    /// or only listen to new changes
+    #[prost(int64, optional, tag = "1")]
+    pub since: ::core::option::Option<i64>,
+    /// name of a durable consumer. If empty, nothing is recorded for the listener
+    #[prost(string, tag = "2")]
+    pub consumer: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListenResponse {
+    /// the type of the change (create/update/delete)
     #[prost(enumeration = "ReservationUpdateType", tag = "1")]
     pub op: i32,
+    /// the reservation at the time of the change
     #[prost(message, optional, tag = "2")]
     pub reservation: ::core::option::Option<Reservation>,
+    /// change id, could be used as `since` to resume the listening
+    #[prost(int64, tag = "3")]
+    pub id: i64,
+}
+/// acknowledge the changes received by a durable consumer
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AckRequest {
+    /// name of the consumer
+    #[prost(string, tag = "1")]
+    pub consumer: ::prost::alloc::string::String,
+    /// the last change id processed by the consumer
+    #[prost(int64, tag = "2")]
+    pub id: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AckResponse {}
+/// search the free slots of the resources in a time window
+#[derive(derive_builder::Builder)]
+#[builder(build_fn(name = "private_build"))]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AvailabilityQuery {
+    /// the resources to search, at least one is required. A resource is not free while any of its
+    /// ancestors or descendants is reserved
+    #[prost(string, repeated, tag = "1")]
+    #[builder(setter(into), default)]
+    pub resource_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// start time of the search window
+    #[prost(message, optional, tag = "2")]
+    #[builder(setter(into, strip_option), default)]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    /// end time of the search window
+    #[prost(message, optional, tag = "3")]
+    #[builder(setter(into, strip_option), default)]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
+    /// minimum duration of a free slot in seconds, at most the window. If 0, all the free slots are returned
+    #[prost(int64, tag = "4")]
+    #[builder(setter(into), default)]
+    pub min_duration: i64,
+    /// granularity of the slots in seconds, the slots are aligned to it from the start of the window.
+    /// At most the window. If 0, the free slots are not aligned
+    #[prost(int64, tag = "5")]
+    #[builder(setter(into), default)]
+    pub granularity: i64,
+    /// units of a shared resource needed in the slots, 1 if it's 0. The reserved units are taken from
+    /// the capacity of the resource
+    #[prost(int32, tag = "6")]
+    #[builder(setter(into), default)]
+    pub quantity: i32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AvailabilityRequest {
+    #[prost(message, optional, tag = "1")]
+    pub query: ::core::option::Option<AvailabilityQuery>,
+}
+/// a free time slot of a resource
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FreeSlot {
+    #[prost(string, tag = "1")]
+    pub resource_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "3")]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AvailabilityResponse {
+    /// order by the resource ids of the query, then by the start time
+    #[prost(message, repeated, tag = "1")]
+    pub slots: ::prost::alloc::vec::Vec<FreeSlot>,
+}
+/// the time window of a reservation on a resource
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ConflictWindow {
+    /// id of the reservation. 0 if it is unknown, e.g. the new reservation
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    #[prost(string, tag = "2")]
+    pub resource_id: ::prost::alloc::string::String,
+    /// not set if the window is unbounded at the start
+    #[prost(message, optional, tag = "3")]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    /// not set if the window is unbounded at the end
+    #[prost(message, optional, tag = "4")]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// the new reservation conflicts with an existing one
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ConflictDetail {
+    #[prost(message, optional, tag = "1")]
+    pub new: ::core::option::Option<ConflictWindow>,
+    #[prost(message, optional, tag = "2")]
+    pub existing: ::core::option::Option<ConflictWindow>,
+    /// all the existing reservations overlapping the new one, order by start time.
+    /// the notes of the reservations of other users are not included
+    #[prost(message, repeated, tag = "3")]
+    pub conflicts: ::prost::alloc::vec::Vec<Reservation>,
+    /// only if suggested by the request. The nearest free windows of the same length on the same
+    /// resource before and after the new one, then the same window on the free alternative resources
+    #[prost(message, repeated, tag = "4")]
+    pub suggestions: ::prost::alloc::vec::Vec<FreeSlot>,
+    /// the existing one is on the same resource, or on an ancestor or a descendant of it
+    #[prost(enumeration = "ResourceRelation", tag = "5")]
+    pub relation: i32,
+}
+/// a reservation of the batch conflicts with an existing one or an earlier one of the batch
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchConflict {
+    /// index of the reservation in the batch
+    #[prost(int64, tag = "1")]
+    pub index: i64,
+    /// not set if the conflict couldn't be parsed, or the reservation exceeds the capacity
+    #[prost(message, optional, tag = "2")]
+    pub conflict: ::core::option::Option<ConflictDetail>,
+    /// the detail of the conflict given by the database
+    #[prost(string, tag = "3")]
+    pub description: ::prost::alloc::string::String,
+    /// set if the reservation exceeds the capacity of its resource
+    #[prost(message, optional, tag = "4")]
+    pub capacity_conflict: ::core::option::Option<CapacityConflictDetail>,
+}
+/// all the conflicts of a batch, none of the reservations is made
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchConflictDetail {
+    #[prost(message, repeated, tag = "1")]
+    pub conflicts: ::prost::alloc::vec::Vec<BatchConflict>,
+}
+/// the new reservation exceeds the capacity of the resource
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CapacityConflictDetail {
+    #[prost(message, optional, tag = "1")]
+    pub new: ::core::option::Option<ConflictWindow>,
+    /// units requested by the new reservation
+    #[prost(int32, tag = "2")]
+    pub quantity: i32,
+    #[prost(int32, tag = "3")]
+    pub capacity: i32,
+    /// the most units reserved at the same time by the existing reservations overlapping the new one
+    #[prost(int32, tag = "4")]
+    pub reserved: i32,
+}
+/// an argument of the request is invalid
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InvalidArgumentDetail {
+    /// name of the invalid field, e.g. page_size
+    #[prost(string, tag = "1")]
+    pub field: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub description: ::prost::alloc::string::String,
+}
+/// a resource which could be reserved
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Resource {
+    /// the resource_id of its reservations
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// the id if it's empty
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+    /// e.g. room, desk, projector
+    #[prost(string, tag = "3")]
+    pub r#type: ::prost::alloc::string::String,
+    #[prost(map = "string, string", tag = "4")]
+    pub attributes:
+        ::std::collections::HashMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
+    /// only an active resource could be reserved
+    #[prost(bool, tag = "5")]
+    pub active: bool,
+    /// IANA name of the timezone, e.g. Asia/Shanghai. UTC if it's empty
+    #[prost(string, tag = "6")]
+    pub timezone: ::prost::alloc::string::String,
+    /// units which could be reserved at the same time, e.g. the seats of a class. 1 if it's 0
+    #[prost(int32, tag = "7")]
+    pub capacity: i32,
+    /// the resource it belongs to, e.g. the floor of a room. A root resource if it's empty.
+    /// a reservation conflicts with the overlapping ones on its ancestors and descendants
+    #[prost(string, tag = "8")]
+    pub parent_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateResourceRequest {
+    /// a new resource is always active
+    #[prost(message, optional, tag = "1")]
+    pub resource: ::core::option::Option<Resource>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateResourceResponse {
+    #[prost(message, optional, tag = "1")]
+    pub resource: ::core::option::Option<Resource>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateResourceRequest {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// the fields not set are not changed
+    #[prost(string, optional, tag = "2")]
+    pub name: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "3")]
+    pub r#type: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "4")]
+    pub timezone: ::core::option::Option<::prost::alloc::string::String>,
+    /// merged into the attributes, the key of an empty value is removed
+    #[prost(map = "string, string", tag = "5")]
+    pub attributes:
+        ::std::collections::HashMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
+    /// the existing reservations must fit in the new capacity
+    #[prost(int32, optional, tag = "6")]
+    pub capacity: ::core::option::Option<i32>,
+    /// empty to make it a root. The existing reservations of it and its descendants must not
+    /// overlap the ones of its new ancestors
+    #[prost(string, optional, tag = "7")]
+    pub parent_id: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateResourceResponse {
+    #[prost(message, optional, tag = "1")]
+    pub resource: ::core::option::Option<Resource>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeactivateResourceRequest {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeactivateResourceResponse {
+    #[prost(message, optional, tag = "1")]
+    pub resource: ::core::option::Option<Resource>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListResourcesRequest {
+    /// if empty, list all types
+    #[prost(string, tag = "1")]
+    pub r#type: ::prost::alloc::string::String,
+    /// list the inactive resources too
+    #[prost(bool, tag = "2")]
+    pub include_inactive: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListResourcesResponse {
+    /// order by id
+    #[prost(message, repeated, tag = "1")]
+    pub resources: ::prost::alloc::vec::Vec<Resource>,
+}
+/// typed detail of the error status returned by the service, it's encoded in the details of the status
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ErrorDetail {
+    #[prost(oneof = "error_detail::Detail", tags = "1, 2, 3, 4")]
+    pub detail: ::core::option::Option<error_detail::Detail>,
+}
+/// Nested message and enum types in `ErrorDetail`.
+pub mod error_detail {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Detail {
+        #[prost(message, tag = "1")]
+        Conflict(super::ConflictDetail),
+        #[prost(message, tag = "2")]
+        InvalidArgument(super::InvalidArgumentDetail),
+        #[prost(message, tag = "3")]
+        BatchConflict(super::BatchConflictDetail),
+        #[prost(message, tag = "4")]
+        CapacityConflict(super::CapacityConflictDetail),
+    }
 }
 /// 预约状态
 #[derive(
@@ -207,6 +684,16 @@ impl ReservationStatus {
             ReservationStatus::Blocked => "RESERVATION_STATUS_BLOCKED",
         }
     }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "RESERVATION_STATUS_UNKNOWN" => Some(Self::Unknown),
+            "RESERVATION_STATUS_PENDING" => Some(Self::Pending),
+            "RESERVATION_STATUS_CONFIRMED" => Some(Self::Confirmed),
+            "RESERVATION_STATUS_BLOCKED" => Some(Self::Blocked),
+            _ => None,
+        }
+    }
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -229,6 +716,229 @@ impl ReservationUpdateType {
             ReservationUpdateType::Delete => "RESERVATION_UPDATE_TYPE_DELETE",
         }
     }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "RESERVATION_UPDATE_TYPE_UNKNOWN" => Some(Self::Unknown),
+            "RESERVATION_UPDATE_TYPE_CREATE" => Some(Self::Create),
+            "RESERVATION_UPDATE_TYPE_UPDATE" => Some(Self::Update),
+            "RESERVATION_UPDATE_TYPE_DELETE" => Some(Self::Delete),
+            _ => None,
+        }
+    }
+}
+/// how the reservations match the time window of a query
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ReservationMatchMode {
+    /// the reservation is entirely inside the window
+    Contained = 0,
+    /// the reservation overlaps the window
+    Overlapping = 1,
+    /// the reservation starts within the window
+    StartingWithin = 2,
+}
+impl ReservationMatchMode {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ReservationMatchMode::Contained => "RESERVATION_MATCH_MODE_CONTAINED",
+            ReservationMatchMode::Overlapping => "RESERVATION_MATCH_MODE_OVERLAPPING",
+            ReservationMatchMode::StartingWithin => "RESERVATION_MATCH_MODE_STARTING_WITHIN",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "RESERVATION_MATCH_MODE_CONTAINED" => Some(Self::Contained),
+            "RESERVATION_MATCH_MODE_OVERLAPPING" => Some(Self::Overlapping),
+            "RESERVATION_MATCH_MODE_STARTING_WITHIN" => Some(Self::StartingWithin),
+            _ => None,
+        }
+    }
+}
+/// how often a recurring reservation repeats
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum RecurrenceFrequency {
+    Unknown = 0,
+    Daily = 1,
+    Weekly = 2,
+    /// on the same day of the month, the months without the day are skipped
+    Monthly = 3,
+}
+impl RecurrenceFrequency {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            RecurrenceFrequency::Unknown => "RECURRENCE_FREQUENCY_UNKNOWN",
+            RecurrenceFrequency::Daily => "RECURRENCE_FREQUENCY_DAILY",
+            RecurrenceFrequency::Weekly => "RECURRENCE_FREQUENCY_WEEKLY",
+            RecurrenceFrequency::Monthly => "RECURRENCE_FREQUENCY_MONTHLY",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "RECURRENCE_FREQUENCY_UNKNOWN" => Some(Self::Unknown),
+            "RECURRENCE_FREQUENCY_DAILY" => Some(Self::Daily),
+            "RECURRENCE_FREQUENCY_WEEKLY" => Some(Self::Weekly),
+            "RECURRENCE_FREQUENCY_MONTHLY" => Some(Self::Monthly),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Weekday {
+    Unknown = 0,
+    Monday = 1,
+    Tuesday = 2,
+    Wednesday = 3,
+    Thursday = 4,
+    Friday = 5,
+    Saturday = 6,
+    Sunday = 7,
+}
+impl Weekday {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Weekday::Unknown => "WEEKDAY_UNKNOWN",
+            Weekday::Monday => "WEEKDAY_MONDAY",
+            Weekday::Tuesday => "WEEKDAY_TUESDAY",
+            Weekday::Wednesday => "WEEKDAY_WEDNESDAY",
+            Weekday::Thursday => "WEEKDAY_THURSDAY",
+            Weekday::Friday => "WEEKDAY_FRIDAY",
+            Weekday::Saturday => "WEEKDAY_SATURDAY",
+            Weekday::Sunday => "WEEKDAY_SUNDAY",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "WEEKDAY_UNKNOWN" => Some(Self::Unknown),
+            "WEEKDAY_MONDAY" => Some(Self::Monday),
+            "WEEKDAY_TUESDAY" => Some(Self::Tuesday),
+            "WEEKDAY_WEDNESDAY" => Some(Self::Wednesday),
+            "WEEKDAY_THURSDAY" => Some(Self::Thursday),
+            "WEEKDAY_FRIDAY" => Some(Self::Friday),
+            "WEEKDAY_SATURDAY" => Some(Self::Saturday),
+            "WEEKDAY_SUNDAY" => Some(Self::Sunday),
+            _ => None,
+        }
+    }
+}
+/// which occurrences of a series are changed, relative to the given one
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum SeriesScope {
+    /// only the given occurrence
+    One = 0,
+    /// the given occurrence and the following ones
+    Following = 1,
+    /// all the occurrences of the series
+    All = 2,
+}
+impl SeriesScope {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            SeriesScope::One => "SERIES_SCOPE_ONE",
+            SeriesScope::Following => "SERIES_SCOPE_FOLLOWING",
+            SeriesScope::All => "SERIES_SCOPE_ALL",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "SERIES_SCOPE_ONE" => Some(Self::One),
+            "SERIES_SCOPE_FOLLOWING" => Some(Self::Following),
+            "SERIES_SCOPE_ALL" => Some(Self::All),
+            _ => None,
+        }
+    }
+}
+/// how a resource of a pool is chosen among the free ones
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum AllocationStrategy {
+    /// the first one by id
+    FirstFit = 0,
+    /// the one with the least reserved time around the window
+    LeastUsed = 1,
+    /// the one whose free gap around the window is the smallest, so that the larger gaps are kept
+    /// for the longer reservations
+    BestFit = 2,
+}
+impl AllocationStrategy {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            AllocationStrategy::FirstFit => "ALLOCATION_STRATEGY_FIRST_FIT",
+            AllocationStrategy::LeastUsed => "ALLOCATION_STRATEGY_LEAST_USED",
+            AllocationStrategy::BestFit => "ALLOCATION_STRATEGY_BEST_FIT",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ALLOCATION_STRATEGY_FIRST_FIT" => Some(Self::FirstFit),
+            "ALLOCATION_STRATEGY_LEAST_USED" => Some(Self::LeastUsed),
+            "ALLOCATION_STRATEGY_BEST_FIT" => Some(Self::BestFit),
+            _ => None,
+        }
+    }
+}
+/// the resource of an existing reservation relative to the resource of the new one
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ResourceRelation {
+    Unknown = 0,
+    Same = 1,
+    /// e.g. the building of the room
+    Ancestor = 2,
+    /// e.g. a room of the floor
+    Descendant = 3,
+}
+impl ResourceRelation {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ResourceRelation::Unknown => "RESOURCE_RELATION_UNKNOWN",
+            ResourceRelation::Same => "RESOURCE_RELATION_SAME",
+            ResourceRelation::Ancestor => "RESOURCE_RELATION_ANCESTOR",
+            ResourceRelation::Descendant => "RESOURCE_RELATION_DESCENDANT",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "RESOURCE_RELATION_UNKNOWN" => Some(Self::Unknown),
+            "RESOURCE_RELATION_SAME" => Some(Self::Same),
+            "RESOURCE_RELATION_ANCESTOR" => Some(Self::Ancestor),
+            "RESOURCE_RELATION_DESCENDANT" => Some(Self::Descendant),
+            _ => None,
+        }
+    }
 }
 /// Generated client implementations.
 pub mod reservation_service_client {
@@ -313,10 +1023,12 @@ pub mod reservation_service_client {
                 http::uri::PathAndQuery::from_static("/reservation.ReservationService/reserve");
             self.inner.unary(request.into_request(), path, codec).await
         }
-        pub async fn confirm(
+        /// make several reservations, possibly on different resources, in one transaction. If any of
+        /// them fails, none is made and all the conflicts are reported together
+        pub async fn reserve_batch(
             &mut self,
-            request: impl tonic::IntoRequest<super::ConfirmRequest>,
-        ) -> Result<tonic::Response<super::ConfirmResponse>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::ReserveBatchRequest>,
+        ) -> Result<tonic::Response<super::ReserveBatchResponse>, tonic::Status> {
             self.inner.ready().await.map_err(|e| {
                 tonic::Status::new(
                     tonic::Code::Unknown,
@@ -324,14 +1036,17 @@ pub mod reservation_service_client {
                 )
             })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path =
-                http::uri::PathAndQuery::from_static("/reservation.ReservationService/confirm");
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/reserve_batch",
+            );
             self.inner.unary(request.into_request(), path, codec).await
         }
-        pub async fn update(
+        /// make the occurrences of a recurring reservation in one transaction, linked by a series id.
+        /// If any of them conflicts, none is made and all the conflicts are reported together
+        pub async fn reserve_series(
             &mut self,
-            request: impl tonic::IntoRequest<super::UpdateRequest>,
-        ) -> Result<tonic::Response<super::UpdateResponse>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::ReserveSeriesRequest>,
+        ) -> Result<tonic::Response<super::ReserveSeriesResponse>, tonic::Status> {
             self.inner.ready().await.map_err(|e| {
                 tonic::Status::new(
                     tonic::Code::Unknown,
@@ -339,14 +1054,16 @@ pub mod reservation_service_client {
                 )
             })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path =
-                http::uri::PathAndQuery::from_static("/reservation.ReservationService/update");
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/reserve_series",
+            );
             self.inner.unary(request.into_request(), path, codec).await
         }
-        pub async fn cancel(
+        /// change the note and/or move the occurrences of a series in the scope, either all or none
+        pub async fn update_series(
             &mut self,
-            request: impl tonic::IntoRequest<super::CancelRequest>,
-        ) -> Result<tonic::Response<super::CancelResponse>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::UpdateSeriesRequest>,
+        ) -> Result<tonic::Response<super::UpdateSeriesResponse>, tonic::Status> {
             self.inner.ready().await.map_err(|e| {
                 tonic::Status::new(
                     tonic::Code::Unknown,
@@ -354,7 +1071,69 @@ pub mod reservation_service_client {
                 )
             })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path =
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/update_series",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn cancel_series(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CancelSeriesRequest>,
+        ) -> Result<tonic::Response<super::CancelSeriesResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/cancel_series",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn confirm(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ConfirmRequest>,
+        ) -> Result<tonic::Response<super::ConfirmResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/confirm");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn update(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateRequest>,
+        ) -> Result<tonic::Response<super::UpdateResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/update");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn cancel(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CancelRequest>,
+        ) -> Result<tonic::Response<super::CancelResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
                 http::uri::PathAndQuery::from_static("/reservation.ReservationService/cancel");
             self.inner.unary(request.into_request(), path, codec).await
         }
@@ -373,6 +1152,7 @@ pub mod reservation_service_client {
             self.inner.unary(request.into_request(), path, codec).await
         }
         /// query reservations by resource id, user id, status, start time,end time.
+        /// for a paged query, the FilterPager is sent in the response metadata (x-pager-prev, x-pager-next, x-pager-total)
         pub async fn query(
             &mut self,
             request: impl tonic::IntoRequest<super::QueryRequest>,
@@ -411,7 +1191,7 @@ pub mod reservation_service_client {
         pub async fn listen(
             &mut self,
             request: impl tonic::IntoRequest<super::ListenRequest>,
-        ) -> Result<tonic::Response<tonic::codec::Streaming<super::Reservation>>, tonic::Status>
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::ListenResponse>>, tonic::Status>
         {
             self.inner.ready().await.map_err(|e| {
                 tonic::Status::new(
@@ -426,19 +1206,201 @@ pub mod reservation_service_client {
                 .server_streaming(request.into_request(), path, codec)
                 .await
         }
+        /// a durable consumer acknowledges the changes it has processed
+        pub async fn ack(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AckRequest>,
+        ) -> Result<tonic::Response<super::AckResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/reservation.ReservationService/ack");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// the free slots of the resources, computed from the gaps between their reservations
+        pub async fn availability(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AvailabilityRequest>,
+        ) -> Result<tonic::Response<super::AvailabilityResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/availability",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+    }
+}
+/// Generated client implementations.
+pub mod resource_service_client {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::http::Uri;
+    use tonic::codegen::*;
+    /// the catalog of the resources, a reservation could only be made on an active resource in it
+    #[derive(Debug, Clone)]
+    pub struct ResourceServiceClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl ResourceServiceClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: std::convert::TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> ResourceServiceClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> ResourceServiceClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<http::Request<tonic::body::BoxBody>>>::Error:
+                Into<StdError> + Send + Sync,
+        {
+            ResourceServiceClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        pub async fn create(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateResourceRequest>,
+        ) -> Result<tonic::Response<super::CreateResourceResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/reservation.ResourceService/create");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn update(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateResourceRequest>,
+        ) -> Result<tonic::Response<super::UpdateResourceResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/reservation.ResourceService/update");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// the resource could not be reserved any more, its reservations are kept
+        pub async fn deactivate(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeactivateResourceRequest>,
+        ) -> Result<tonic::Response<super::DeactivateResourceResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ResourceService/deactivate");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn list(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListResourcesRequest>,
+        ) -> Result<tonic::Response<super::ListResourcesResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/reservation.ResourceService/list");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
     }
 }
 /// Generated server implementations.
 pub mod reservation_service_server {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
     use tonic::codegen::*;
-    ///Generated trait containing gRPC methods that should be implemented for use with ReservationServiceServer.
+    /// Generated trait containing gRPC methods that should be implemented for use with ReservationServiceServer.
     #[async_trait]
     pub trait ReservationService: Send + Sync + 'static {
         async fn reserve(
             &self,
             request: tonic::Request<super::ReservationRequest>,
         ) -> Result<tonic::Response<super::ReservationResponse>, tonic::Status>;
+        /// make several reservations, possibly on different resources, in one transaction. If any of
+        /// them fails, none is made and all the conflicts are reported together
+        async fn reserve_batch(
+            &self,
+            request: tonic::Request<super::ReserveBatchRequest>,
+        ) -> Result<tonic::Response<super::ReserveBatchResponse>, tonic::Status>;
+        /// make the occurrences of a recurring reservation in one transaction, linked by a series id.
+        /// If any of them conflicts, none is made and all the conflicts are reported together
+        async fn reserve_series(
+            &self,
+            request: tonic::Request<super::ReserveSeriesRequest>,
+        ) -> Result<tonic::Response<super::ReserveSeriesResponse>, tonic::Status>;
+        /// change the note and/or move the occurrences of a series in the scope, either all or none
+        async fn update_series(
+            &self,
+            request: tonic::Request<super::UpdateSeriesRequest>,
+        ) -> Result<tonic::Response<super::UpdateSeriesResponse>, tonic::Status>;
+        async fn cancel_series(
+            &self,
+            request: tonic::Request<super::CancelSeriesRequest>,
+        ) -> Result<tonic::Response<super::CancelSeriesResponse>, tonic::Status>;
         async fn confirm(
             &self,
             request: tonic::Request<super::ConfirmRequest>,
@@ -455,11 +1417,12 @@ pub mod reservation_service_server {
             &self,
             request: tonic::Request<super::GetRequest>,
         ) -> Result<tonic::Response<super::GetResponse>, tonic::Status>;
-        ///Server streaming response type for the query method.
+        /// Server streaming response type for the query method.
         type queryStream: futures_core::Stream<Item = Result<super::Reservation, tonic::Status>>
             + Send
             + 'static;
         /// query reservations by resource id, user id, status, start time,end time.
+        /// for a paged query, the FilterPager is sent in the response metadata (x-pager-prev, x-pager-next, x-pager-total)
         async fn query(
             &self,
             request: tonic::Request<super::QueryRequest>,
@@ -469,8 +1432,8 @@ pub mod reservation_service_server {
             &self,
             request: tonic::Request<super::FilterRequest>,
         ) -> Result<tonic::Response<super::FilterResponse>, tonic::Status>;
-        ///Server streaming response type for the listen method.
-        type listenStream: futures_core::Stream<Item = Result<super::Reservation, tonic::Status>>
+        /// Server streaming response type for the listen method.
+        type listenStream: futures_core::Stream<Item = Result<super::ListenResponse, tonic::Status>>
             + Send
             + 'static;
         /// another system could monitor newly added/confirmed/cancelled reservations
@@ -478,6 +1441,16 @@ pub mod reservation_service_server {
             &self,
             request: tonic::Request<super::ListenRequest>,
         ) -> Result<tonic::Response<Self::listenStream>, tonic::Status>;
+        /// a durable consumer acknowledges the changes it has processed
+        async fn ack(
+            &self,
+            request: tonic::Request<super::AckRequest>,
+        ) -> Result<tonic::Response<super::AckResponse>, tonic::Status>;
+        /// the free slots of the resources, computed from the gaps between their reservations
+        async fn availability(
+            &self,
+            request: tonic::Request<super::AvailabilityRequest>,
+        ) -> Result<tonic::Response<super::AvailabilityResponse>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct ReservationServiceServer<T: ReservationService> {
@@ -565,18 +1538,21 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/reservation.ReservationService/confirm" => {
+                "/reservation.ReservationService/reserve_batch" => {
                     #[allow(non_camel_case_types)]
-                    struct confirmSvc<T: ReservationService>(pub Arc<T>);
-                    impl<T: ReservationService> tonic::server::UnaryService<super::ConfirmRequest> for confirmSvc<T> {
-                        type Response = super::ConfirmResponse;
+                    struct reserve_batchSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::ReserveBatchRequest>
+                        for reserve_batchSvc<T>
+                    {
+                        type Response = super::ReserveBatchResponse;
                         type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ConfirmRequest>,
+                            request: tonic::Request<super::ReserveBatchRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).confirm(request).await };
+                            let fut = async move { (*inner).reserve_batch(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -585,7 +1561,7 @@ pub mod reservation_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = confirmSvc(inner);
+                        let method = reserve_batchSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
                             accept_compression_encodings,
@@ -596,18 +1572,21 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/reservation.ReservationService/update" => {
+                "/reservation.ReservationService/reserve_series" => {
                     #[allow(non_camel_case_types)]
-                    struct updateSvc<T: ReservationService>(pub Arc<T>);
-                    impl<T: ReservationService> tonic::server::UnaryService<super::UpdateRequest> for updateSvc<T> {
-                        type Response = super::UpdateResponse;
+                    struct reserve_seriesSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::ReserveSeriesRequest>
+                        for reserve_seriesSvc<T>
+                    {
+                        type Response = super::ReserveSeriesResponse;
                         type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::UpdateRequest>,
+                            request: tonic::Request<super::ReserveSeriesRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).update(request).await };
+                            let fut = async move { (*inner).reserve_series(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -616,7 +1595,7 @@ pub mod reservation_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = updateSvc(inner);
+                        let method = reserve_seriesSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
                             accept_compression_encodings,
@@ -627,18 +1606,21 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/reservation.ReservationService/cancel" => {
+                "/reservation.ReservationService/update_series" => {
                     #[allow(non_camel_case_types)]
-                    struct cancelSvc<T: ReservationService>(pub Arc<T>);
-                    impl<T: ReservationService> tonic::server::UnaryService<super::CancelRequest> for cancelSvc<T> {
-                        type Response = super::CancelResponse;
+                    struct update_seriesSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::UpdateSeriesRequest>
+                        for update_seriesSvc<T>
+                    {
+                        type Response = super::UpdateSeriesResponse;
                         type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::CancelRequest>,
+                            request: tonic::Request<super::UpdateSeriesRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).cancel(request).await };
+                            let fut = async move { (*inner).update_series(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -647,7 +1629,7 @@ pub mod reservation_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = cancelSvc(inner);
+                        let method = update_seriesSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
                             accept_compression_encodings,
@@ -658,18 +1640,21 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/reservation.ReservationService/get" => {
+                "/reservation.ReservationService/cancel_series" => {
                     #[allow(non_camel_case_types)]
-                    struct getSvc<T: ReservationService>(pub Arc<T>);
-                    impl<T: ReservationService> tonic::server::UnaryService<super::GetRequest> for getSvc<T> {
-                        type Response = super::GetResponse;
+                    struct cancel_seriesSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::CancelSeriesRequest>
+                        for cancel_seriesSvc<T>
+                    {
+                        type Response = super::CancelSeriesResponse;
                         type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetRequest>,
+                            request: tonic::Request<super::CancelSeriesRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).get(request).await };
+                            let fut = async move { (*inner).cancel_series(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -678,7 +1663,7 @@ pub mod reservation_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = getSvc(inner);
+                        let method = cancel_seriesSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
                             accept_compression_encodings,
@@ -689,22 +1674,18 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/reservation.ReservationService/query" => {
+                "/reservation.ReservationService/confirm" => {
                     #[allow(non_camel_case_types)]
-                    struct querySvc<T: ReservationService>(pub Arc<T>);
-                    impl<T: ReservationService>
-                        tonic::server::ServerStreamingService<super::QueryRequest> for querySvc<T>
-                    {
-                        type Response = super::Reservation;
-                        type ResponseStream = T::queryStream;
-                        type Future =
-                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                    struct confirmSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::ConfirmRequest> for confirmSvc<T> {
+                        type Response = super::ConfirmResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::QueryRequest>,
+                            request: tonic::Request<super::ConfirmRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).query(request).await };
+                            let fut = async move { (*inner).confirm(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -713,29 +1694,157 @@ pub mod reservation_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = querySvc(inner);
+                        let method = confirmSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
                             accept_compression_encodings,
                             send_compression_encodings,
                         );
-                        let res = grpc.server_streaming(method, req).await;
+                        let res = grpc.unary(method, req).await;
                         Ok(res)
                     };
                     Box::pin(fut)
                 }
-                "/reservation.ReservationService/filter" => {
+                "/reservation.ReservationService/update" => {
                     #[allow(non_camel_case_types)]
-                    struct filterSvc<T: ReservationService>(pub Arc<T>);
-                    impl<T: ReservationService> tonic::server::UnaryService<super::FilterRequest> for filterSvc<T> {
-                        type Response = super::FilterResponse;
+                    struct updateSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::UpdateRequest> for updateSvc<T> {
+                        type Response = super::UpdateResponse;
                         type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::FilterRequest>,
+                            request: tonic::Request<super::UpdateRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).filter(request).await };
+                            let fut = async move { (*inner).update(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = updateSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/cancel" => {
+                    #[allow(non_camel_case_types)]
+                    struct cancelSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::CancelRequest> for cancelSvc<T> {
+                        type Response = super::CancelResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CancelRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).cancel(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = cancelSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/get" => {
+                    #[allow(non_camel_case_types)]
+                    struct getSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::GetRequest> for getSvc<T> {
+                        type Response = super::GetResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).get(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = getSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/query" => {
+                    #[allow(non_camel_case_types)]
+                    struct querySvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::ServerStreamingService<super::QueryRequest> for querySvc<T>
+                    {
+                        type Response = super::Reservation;
+                        type ResponseStream = T::queryStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::QueryRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).query(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = querySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/filter" => {
+                    #[allow(non_camel_case_types)]
+                    struct filterSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::FilterRequest> for filterSvc<T> {
+                        type Response = super::FilterResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::FilterRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).filter(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -762,7 +1871,7 @@ pub mod reservation_service_server {
                         tonic::server::ServerStreamingService<super::ListenRequest>
                         for listenSvc<T>
                     {
-                        type Response = super::Reservation;
+                        type Response = super::ListenResponse;
                         type ResponseStream = T::listenStream;
                         type Future =
                             BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
@@ -791,6 +1900,71 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/reservation.ReservationService/ack" => {
+                    #[allow(non_camel_case_types)]
+                    struct ackSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::AckRequest> for ackSvc<T> {
+                        type Response = super::AckResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AckRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).ack(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ackSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/availability" => {
+                    #[allow(non_camel_case_types)]
+                    struct availabilitySvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::AvailabilityRequest>
+                        for availabilitySvc<T>
+                    {
+                        type Response = super::AvailabilityResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AvailabilityRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).availability(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = availabilitySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => Box::pin(async move {
                     Ok(http::Response::builder()
                         .status(200)
@@ -826,3 +2000,250 @@ pub mod reservation_service_server {
         const NAME: &'static str = "reservation.ReservationService";
     }
 }
+/// Generated server implementations.
+pub mod resource_service_server {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with ResourceServiceServer.
+    #[async_trait]
+    pub trait ResourceService: Send + Sync + 'static {
+        async fn create(
+            &self,
+            request: tonic::Request<super::CreateResourceRequest>,
+        ) -> Result<tonic::Response<super::CreateResourceResponse>, tonic::Status>;
+        async fn update(
+            &self,
+            request: tonic::Request<super::UpdateResourceRequest>,
+        ) -> Result<tonic::Response<super::UpdateResourceResponse>, tonic::Status>;
+        /// the resource could not be reserved any more, its reservations are kept
+        async fn deactivate(
+            &self,
+            request: tonic::Request<super::DeactivateResourceRequest>,
+        ) -> Result<tonic::Response<super::DeactivateResourceResponse>, tonic::Status>;
+        async fn list(
+            &self,
+            request: tonic::Request<super::ListResourcesRequest>,
+        ) -> Result<tonic::Response<super::ListResourcesResponse>, tonic::Status>;
+    }
+    /// the catalog of the resources, a reservation could only be made on an active resource in it
+    #[derive(Debug)]
+    pub struct ResourceServiceServer<T: ResourceService> {
+        inner: _Inner<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+    }
+    struct _Inner<T>(Arc<T>);
+    impl<T: ResourceService> ResourceServiceServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            let inner = _Inner(inner);
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+            }
+        }
+        pub fn with_interceptor<F>(inner: T, interceptor: F) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for ResourceServiceServer<T>
+    where
+        T: ResourceService,
+        B: Body + Send + 'static,
+        B::Error: Into<StdError> + Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            let inner = self.inner.clone();
+            match req.uri().path() {
+                "/reservation.ResourceService/create" => {
+                    #[allow(non_camel_case_types)]
+                    struct createSvc<T: ResourceService>(pub Arc<T>);
+                    impl<T: ResourceService>
+                        tonic::server::UnaryService<super::CreateResourceRequest> for createSvc<T>
+                    {
+                        type Response = super::CreateResourceResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateResourceRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).create(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = createSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ResourceService/update" => {
+                    #[allow(non_camel_case_types)]
+                    struct updateSvc<T: ResourceService>(pub Arc<T>);
+                    impl<T: ResourceService>
+                        tonic::server::UnaryService<super::UpdateResourceRequest> for updateSvc<T>
+                    {
+                        type Response = super::UpdateResourceResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateResourceRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).update(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = updateSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ResourceService/deactivate" => {
+                    #[allow(non_camel_case_types)]
+                    struct deactivateSvc<T: ResourceService>(pub Arc<T>);
+                    impl<T: ResourceService>
+                        tonic::server::UnaryService<super::DeactivateResourceRequest>
+                        for deactivateSvc<T>
+                    {
+                        type Response = super::DeactivateResourceResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeactivateResourceRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).deactivate(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = deactivateSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ResourceService/list" => {
+                    #[allow(non_camel_case_types)]
+                    struct listSvc<T: ResourceService>(pub Arc<T>);
+                    impl<T: ResourceService>
+                        tonic::server::UnaryService<super::ListResourcesRequest> for listSvc<T>
+                    {
+                        type Response = super::ListResourcesResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListResourcesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).list(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = listSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => Box::pin(async move {
+                    Ok(http::Response::builder()
+                        .status(200)
+                        .header("grpc-status", "12")
+                        .header("content-type", "application/grpc")
+                        .body(empty_body())
+                        .unwrap())
+                }),
+            }
+        }
+    }
+    impl<T: ResourceService> Clone for ResourceServiceServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self {
+                inner,
+                accept_compression_encodings: self.accept_compression_encodings,
+                send_compression_encodings: self.send_compression_encodings,
+            }
+        }
+    }
+    impl<T: ResourceService> Clone for _Inner<T> {
+        fn clone(&self) -> Self {
+            Self(self.0.clone())
+        }
+    }
+    impl<T: std::fmt::Debug> std::fmt::Debug for _Inner<T> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+    impl<T: ResourceService> tonic::server::NamedService for ResourceServiceServer<T> {
+        const NAME: &'static str = "reservation.ResourceService";
+    }
+}
@@ -0,0 +1,414 @@
+use std::{collections::HashSet, ops::Bound};
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{postgres::types::PgRange, Postgres, QueryBuilder};
+
+use super::{get_timestamp, validate_range};
+use crate::{
+    convert_to_timestamp, convert_to_utc_time, AvailabilityQuery, AvailabilityQueryBuilder, Error,
+    FreeSlot, Normalizer, ToSql, Validator,
+};
+
+impl AvailabilityQueryBuilder {
+    pub fn build(&self) -> Result<AvailabilityQuery, Error> {
+        let mut query = self
+            .private_build()
+            .expect("failed to build AvailabilityQuery");
+        query.normalize()?;
+        Ok(query)
+    }
+}
+
+impl AvailabilityQuery {
+    /// the search window of the query
+    pub fn get_timespan(&self) -> PgRange<DateTime<Utc>> {
+        get_timestamp(self.start.as_ref(), self.end.as_ref())
+    }
+
+    /// the free slots of a resource in the window, `busy` are the timespans of its reservations
+    pub fn free_slots(&self, rid: &str, busy: &[PgRange<DateTime<Utc>>]) -> Vec<FreeSlot> {
+        let start = convert_to_utc_time(self.start.as_ref().unwrap());
+        let end = convert_to_utc_time(self.end.as_ref().unwrap());
+        let mut busy: Vec<_> = busy
+            .iter()
+            .map(|r| {
+                (
+                    bound(&r.start).unwrap_or(start),
+                    bound(&r.end).unwrap_or(end),
+                )
+            })
+            .collect();
+        busy.sort();
+
+        let mut gaps = vec![];
+        let mut cursor = start;
+        for (busy_start, busy_end) in busy {
+            if busy_start > cursor {
+                gaps.push((cursor, busy_start.min(end)));
+            }
+            cursor = cursor.max(busy_end);
+            if cursor >= end {
+                break;
+            }
+        }
+        if cursor < end {
+            gaps.push((cursor, end));
+        }
+
+        let Some(min_duration) = Duration::try_seconds(self.min_duration) else {
+            return vec![];
+        };
+        gaps.into_iter()
+            .filter_map(|(gap_start, gap_end)| self.align(start, gap_start, gap_end))
+            .filter(|(gap_start, gap_end)| *gap_end - *gap_start >= min_duration)
+            .map(|(gap_start, gap_end)| FreeSlot {
+                resource_id: rid.to_string(),
+                start: Some(convert_to_timestamp(&gap_start)),
+                end: Some(convert_to_timestamp(&gap_end)),
+            })
+            .collect()
+    }
+
+    /// the timespans in which a resource of the capacity has less units left than the query needs,
+    /// `rsvps` are the timespans of its reservations with their quantities
+    pub fn full_spans(
+        &self,
+        capacity: i32,
+        rsvps: &[(PgRange<DateTime<Utc>>, i32)],
+    ) -> Vec<PgRange<DateTime<Utc>>> {
+        let start = convert_to_utc_time(self.start.as_ref().unwrap());
+        let end = convert_to_utc_time(self.end.as_ref().unwrap());
+        let mut events: Vec<_> = rsvps
+            .iter()
+            .flat_map(|(r, quantity)| {
+                [
+                    (bound(&r.start).unwrap_or(start), *quantity),
+                    (bound(&r.end).unwrap_or(end), -*quantity),
+                ]
+            })
+            .collect();
+        // a reservation ends before the next one starts at the same time
+        events.sort();
+
+        let needed = self.quantity.max(1);
+        let mut reserved = 0;
+        let mut full_since = None;
+        let mut spans = vec![];
+        for (at, quantity) in events {
+            reserved += quantity;
+            let full = reserved + needed > capacity;
+            match full_since {
+                None if full => full_since = Some(at),
+                Some(since) if !full => {
+                    spans.push(PgRange {
+                        start: Bound::Included(since),
+                        end: Bound::Excluded(at),
+                    });
+                    full_since = None;
+                }
+                _ => {}
+            }
+        }
+        spans
+    }
+
+    /// shrink the gap to the granularity counted from the start of the window, None if nothing is left
+    fn align(
+        &self,
+        origin: DateTime<Utc>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        if self.granularity == 0 {
+            return Some((start, end));
+        }
+
+        let step = self.granularity;
+        let offset = start - origin;
+        let mut first = offset.num_seconds();
+        if offset > Duration::try_seconds(first)? {
+            first = first.checked_add(1)?;
+        }
+        if first % step != 0 {
+            first = first.checked_add(step - first % step)?;
+        }
+        let last = (end - origin).num_seconds() / step * step;
+        if first >= last {
+            return None;
+        }
+        Some((
+            origin.checked_add_signed(Duration::try_seconds(first)?)?,
+            origin.checked_add_signed(Duration::try_seconds(last)?)?,
+        ))
+    }
+}
+
+fn bound(bound: &Bound<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+    match bound {
+        Bound::Included(v) | Bound::Excluded(v) => Some(*v),
+        Bound::Unbounded => None,
+    }
+}
+
+impl Validator for AvailabilityQuery {
+    fn validate(&self) -> Result<(), Error> {
+        if self.resource_ids.is_empty() {
+            return Err(Error::InvalidResourceId(String::new()));
+        }
+        if let Some(rid) = self.resource_ids.iter().find(|rid| rid.is_empty()) {
+            return Err(Error::InvalidResourceId(rid.clone()));
+        }
+
+        validate_range(self.start.as_ref(), self.end.as_ref())?;
+
+        // neither could be longer than the search window
+        let window = self.end.as_ref().unwrap().seconds;
+        let window = window.saturating_sub(self.start.as_ref().unwrap().seconds);
+        if self.min_duration < 0 || self.min_duration > window {
+            return Err(Error::InvalidDuration(self.min_duration));
+        }
+        if self.granularity < 0 || self.granularity > window {
+            return Err(Error::InvalidGranularity(self.granularity));
+        }
+        if self.quantity < 0 {
+            return Err(Error::InvalidQuantity(self.quantity));
+        }
+
+        Ok(())
+    }
+}
+
+impl Normalizer for AvailabilityQuery {
+    fn do_normalize(&mut self) {
+        // the duplicated resources are removed, the order is kept
+        let mut seen = HashSet::new();
+        self.resource_ids.retain(|rid| seen.insert(rid.clone()));
+    }
+}
+
+impl ToSql for AvailabilityQuery {
+    fn to_query(&self) -> QueryBuilder<'static, Postgres> {
+        // a resource is busy when its ancestors or descendants are reserved too, the units of its
+        // own reservations are taken from its capacity
+        let mut query = QueryBuilder::new("SELECT q.rid AS resource_id, r.timespan, r.quantity, r.resource_id = q.rid AS own, c.capacity FROM unnest(");
+        query.push_bind(self.resource_ids.clone());
+        query.push("::varchar[]) AS q(rid) JOIN rsvp.resources c ON c.id = q.rid CROSS JOIN LATERAL (SELECT q.rid AS id UNION SELECT id FROM rsvp.resource_family(q.rid)) f JOIN rsvp.reservations r ON r.resource_id = f.id WHERE r.timespan && ");
+        query.push_bind(self.get_timespan());
+        query.push(" ORDER BY q.rid, lower(r.timespan)");
+        query
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prost_types::Timestamp;
+
+    use super::*;
+
+    fn query(min_duration: i64, granularity: i64) -> AvailabilityQuery {
+        AvailabilityQueryBuilder::default()
+            .resource_ids(vec!["room-1".to_string()])
+            .start("2022-12-25T08:00:00Z".parse::<Timestamp>().unwrap())
+            .end("2022-12-25T18:00:00Z".parse::<Timestamp>().unwrap())
+            .min_duration(min_duration)
+            .granularity(granularity)
+            .build()
+            .unwrap()
+    }
+
+    fn range(start: Option<&str>, end: Option<&str>) -> PgRange<DateTime<Utc>> {
+        let f = |t: Option<&str>| match t {
+            Some(t) => Bound::Included(t.parse().unwrap()),
+            None => Bound::Unbounded,
+        };
+        PgRange {
+            start: f(start),
+            end: f(end),
+        }
+    }
+
+    fn slots(slots: Vec<FreeSlot>) -> Vec<(String, String)> {
+        slots
+            .iter()
+            .map(|slot| {
+                let f = |ts: &Option<Timestamp>| convert_to_utc_time(ts.as_ref().unwrap());
+                (
+                    f(&slot.start).format("%H:%M:%S").to_string(),
+                    f(&slot.end).format("%H:%M:%S").to_string(),
+                )
+            })
+            .collect()
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(s, e)| (s.to_string(), e.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn availability_query_should_generate_correct_sql() {
+        let query = query(0, 0).to_query();
+        assert_eq!(query.sql(), "SELECT q.rid AS resource_id, r.timespan, r.quantity, r.resource_id = q.rid AS own, c.capacity FROM unnest($1::varchar[]) AS q(rid) JOIN rsvp.resources c ON c.id = q.rid CROSS JOIN LATERAL (SELECT q.rid AS id UNION SELECT id FROM rsvp.resource_family(q.rid)) f JOIN rsvp.reservations r ON r.resource_id = f.id WHERE r.timespan && $2 ORDER BY q.rid, lower(r.timespan)");
+    }
+
+    #[test]
+    fn availability_query_should_be_validated() {
+        let mut builder = AvailabilityQueryBuilder::default();
+        builder
+            .start("2022-12-25T08:00:00Z".parse::<Timestamp>().unwrap())
+            .end("2022-12-25T18:00:00Z".parse::<Timestamp>().unwrap());
+        assert_eq!(builder.build(), Err(Error::InvalidResourceId("".into())));
+
+        builder.resource_ids(vec!["room-1".to_string(), "".to_string()]);
+        assert_eq!(builder.build(), Err(Error::InvalidResourceId("".into())));
+
+        builder.resource_ids(vec!["room-1".to_string(), "room-1".to_string()]);
+        assert_eq!(builder.build().unwrap().resource_ids, vec!["room-1"]);
+
+        builder.min_duration(-1);
+        assert_eq!(builder.build(), Err(Error::InvalidDuration(-1)));
+        builder.min_duration(0).granularity(-1);
+        assert_eq!(builder.build(), Err(Error::InvalidGranularity(-1)));
+
+        // longer than the 10 hours of the window
+        builder.granularity(0).min_duration(36001);
+        assert_eq!(builder.build(), Err(Error::InvalidDuration(36001)));
+        builder.min_duration(i64::MAX);
+        assert_eq!(builder.build(), Err(Error::InvalidDuration(i64::MAX)));
+        builder.min_duration(36000).granularity(i64::MAX);
+        assert_eq!(builder.build(), Err(Error::InvalidGranularity(i64::MAX)));
+        builder.min_duration(0).granularity(36000);
+        assert!(builder.build().is_ok());
+
+        builder.quantity(-1);
+        assert_eq!(builder.build(), Err(Error::InvalidQuantity(-1)));
+        builder.quantity(0);
+
+        builder
+            .granularity(0)
+            .end("2022-12-25T08:00:00Z".parse::<Timestamp>().unwrap());
+        assert_eq!(builder.build(), Err(Error::InvalidTime));
+    }
+
+    #[test]
+    fn free_slots_should_be_the_gaps_between_reservations() {
+        let busy = [
+            range(Some("2022-12-25T12:00:00Z"), Some("2022-12-25T13:00:00Z")),
+            range(Some("2022-12-25T07:00:00Z"), Some("2022-12-25T09:00:00Z")),
+            range(Some("2022-12-25T12:30:00Z"), Some("2022-12-25T14:00:00Z")),
+            range(Some("2022-12-25T14:00:00Z"), Some("2022-12-25T14:15:00Z")),
+            range(Some("2022-12-25T17:50:00Z"), Some("2022-12-26T00:00:00Z")),
+        ];
+        let free = query(0, 0).free_slots("room-1", &busy);
+        assert!(free.iter().all(|slot| slot.resource_id == "room-1"));
+        assert_eq!(
+            slots(free),
+            pairs(&[("09:00:00", "12:00:00"), ("14:15:00", "17:50:00")])
+        );
+
+        // no reservation, the whole window is free
+        assert_eq!(
+            slots(query(0, 0).free_slots("room-1", &[])),
+            pairs(&[("08:00:00", "18:00:00")])
+        );
+
+        // unbounded reservations
+        let busy = [
+            range(None, Some("2022-12-25T10:00:00Z")),
+            range(Some("2022-12-25T16:00:00Z"), None),
+        ];
+        assert_eq!(
+            slots(query(0, 0).free_slots("room-1", &busy)),
+            pairs(&[("10:00:00", "16:00:00")])
+        );
+        let busy = [range(None, None)];
+        assert!(query(0, 0).free_slots("room-1", &busy).is_empty());
+    }
+
+    #[test]
+    fn full_spans_should_take_the_reserved_units_from_capacity() {
+        let rsvps = [
+            (
+                range(Some("2022-12-25T09:00:00Z"), Some("2022-12-25T12:00:00Z")),
+                2,
+            ),
+            (
+                range(Some("2022-12-25T10:00:00Z"), Some("2022-12-25T11:00:00Z")),
+                1,
+            ),
+            (
+                range(Some("2022-12-25T11:00:00Z"), Some("2022-12-25T13:00:00Z")),
+                1,
+            ),
+            (range(Some("2022-12-25T17:00:00Z"), None), 3),
+        ];
+        let spans = |query: AvailabilityQuery, capacity| {
+            let busy = query.full_spans(capacity, &rsvps);
+            slots(query.free_slots("desk-1", &busy))
+        };
+        // all 3 units are reserved from 10:00 to 12:00, 1 is left from 9:00 to 10:00
+        assert_eq!(
+            spans(query(0, 0), 3),
+            pairs(&[("08:00:00", "10:00:00"), ("12:00:00", "17:00:00")])
+        );
+        let mut two = query(0, 0);
+        two.quantity = 2;
+        assert_eq!(
+            spans(two, 3),
+            pairs(&[("08:00:00", "09:00:00"), ("12:00:00", "17:00:00")])
+        );
+        // an exclusive resource is busy while it's reserved at all
+        assert_eq!(
+            spans(query(0, 0), 1),
+            pairs(&[("08:00:00", "09:00:00"), ("13:00:00", "17:00:00")])
+        );
+    }
+
+    #[test]
+    fn free_slots_should_respect_min_duration_and_granularity() {
+        let busy = [
+            range(Some("2022-12-25T09:10:00Z"), Some("2022-12-25T09:20:00Z")),
+            range(Some("2022-12-25T10:45:00Z"), Some("2022-12-25T11:10:00Z")),
+            range(Some("2022-12-25T11:40:00Z"), Some("2022-12-25T15:00:30Z")),
+        ];
+        assert_eq!(
+            slots(query(3600, 0).free_slots("room-1", &busy)),
+            pairs(&[
+                ("08:00:00", "09:10:00"),
+                ("09:20:00", "10:45:00"),
+                ("15:00:30", "18:00:00"),
+            ])
+        );
+
+        // aligned to the half hours from the start of the window
+        assert_eq!(
+            slots(query(0, 1800).free_slots("room-1", &busy)),
+            pairs(&[
+                ("08:00:00", "09:00:00"),
+                ("09:30:00", "10:30:00"),
+                ("15:30:00", "18:00:00"),
+            ])
+        );
+        assert_eq!(
+            slots(query(3600, 1800).free_slots("room-1", &busy)),
+            pairs(&[
+                ("08:00:00", "09:00:00"),
+                ("09:30:00", "10:30:00"),
+                ("15:30:00", "18:00:00"),
+            ])
+        );
+        assert_eq!(
+            slots(query(5400, 1800).free_slots("room-1", &busy)),
+            pairs(&[("15:30:00", "18:00:00")])
+        );
+
+        // the whole window as the step and as the shortest slot
+        assert_eq!(
+            slots(query(36000, 36000).free_slots("room-1", &[])),
+            pairs(&[("08:00:00", "18:00:00")])
+        );
+        assert!(query(36000, 36000).free_slots("room-1", &busy).is_empty());
+    }
+}
@@ -1,18 +1,22 @@
+mod availability;
+mod page_token;
+mod recurrence;
 mod reservation;
+mod reservation_change;
 mod reservation_filter;
 mod reservation_query;
 mod reservation_status;
+mod resource;
 
 use std::ops::Bound;
 
 use chrono::{DateTime, Utc};
 use prost_types::Timestamp;
-pub use reservation::*;
-pub use reservation_query::*;
-pub use reservation_status::*;
-use sqlx::postgres::types::PgRange;
+use sqlx::{postgres::types::PgRange, Postgres, QueryBuilder};
 
-use crate::{convert_to_utc_time, Error};
+pub use page_token::PageToken;
+
+use crate::{convert_to_utc_time, Error, ReservationStatus};
 
 pub fn validate_range(start: Option<&Timestamp>, end: Option<&Timestamp>) -> Result<(), Error> {
     if start.is_none() || end.is_none() {
@@ -38,6 +42,63 @@ pub fn get_timestamp(start: Option<&Timestamp>, end: Option<&Timestamp>) -> PgRa
     }
 }
 
+/// the statuses to match, sorted and without UNKNOWN. An empty list matches all statuses
+fn get_statuses(status: i32, statuses: &[i32]) -> Vec<ReservationStatus> {
+    let mut ret: Vec<_> = statuses
+        .iter()
+        .chain([status].iter())
+        .filter_map(|s| ReservationStatus::from_i32(*s))
+        .filter(|s| *s != ReservationStatus::Unknown)
+        .collect();
+    ret.sort();
+    ret.dedup();
+    ret
+}
+
+fn validate_statuses(status: i32, statuses: &[i32]) -> Result<(), Error> {
+    for s in statuses.iter().chain([status].iter()) {
+        ReservationStatus::from_i32(*s).ok_or(Error::InvalidStatus(*s))?;
+    }
+    Ok(())
+}
+
+/// push the condition of statuses followed by "AND", nothing is pushed if all statuses are matched
+fn push_status_cond(query: &mut QueryBuilder<'_, Postgres>, statuses: &[ReservationStatus]) {
+    let to_sql = |s: &ReservationStatus| format!("'{}'::rsvp.reservation_status", s);
+    match statuses {
+        [] => {}
+        [status] => {
+            query.push(format!("status = {} AND ", to_sql(status)));
+        }
+        _ => {
+            let statuses: Vec<_> = statuses.iter().map(to_sql).collect();
+            query.push(format!("status IN ({}) AND ", statuses.join(", ")));
+        }
+    }
+}
+
+/// push the condition of user id and resource id, an empty id matches all
+fn push_user_resource_cond(query: &mut QueryBuilder<'_, Postgres>, uid: &str, rid: &str) {
+    match (uid.is_empty(), rid.is_empty()) {
+        (true, true) => {
+            query.push("TRUE");
+        }
+        (true, false) => {
+            query.push("resource_id = ").push_bind(rid.to_string());
+        }
+        (false, true) => {
+            query.push("user_id = ").push_bind(uid.to_string());
+        }
+        (false, false) => {
+            query
+                .push("user_id = ")
+                .push_bind(uid.to_string())
+                .push(" AND resource_id = ")
+                .push_bind(rid.to_string());
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,194 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::{Error, ReservationFilter};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const TOKEN_VERSION: &str = "v1";
+/// reservations are paged by id for now
+const SORT_KEY_ID: &str = "id";
+
+/// opaque token of a filter page. It is signed with the server secret, so that the cursor
+/// could not be forged, and it could only be used with the filter it is issued for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageToken {
+    pub cursor: i64,
+    pub sort_key: String,
+    pub desc: bool,
+    pub backward: bool,
+    pub filter_hash: String,
+}
+
+impl PageToken {
+    /// token of the page given by `ReservationFilter::next_page` or `ReservationFilter::prev_page`
+    pub fn new(filter: &ReservationFilter) -> Option<Self> {
+        filter.cursor.map(|cursor| Self {
+            cursor,
+            sort_key: SORT_KEY_ID.to_string(),
+            desc: filter.desc,
+            backward: filter.backward,
+            filter_hash: filter_hash(filter),
+        })
+    }
+
+    pub fn encode(&self, secret: &[u8]) -> String {
+        let payload = format!(
+            "{}:{}:{}:{}:{}:{}",
+            TOKEN_VERSION,
+            self.cursor,
+            self.sort_key,
+            self.desc as u8,
+            self.backward as u8,
+            self.filter_hash
+        );
+        let signature = new_mac(secret, payload.as_bytes()).finalize().into_bytes();
+        format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(payload),
+            URL_SAFE_NO_PAD.encode(signature)
+        )
+    }
+
+    pub fn decode(token: &str, secret: &[u8]) -> Result<Self, Error> {
+        let invalid = || Error::InvalidPageToken(token.to_string());
+
+        let (payload, signature) = token.split_once('.').ok_or_else(invalid)?;
+        let payload = URL_SAFE_NO_PAD.decode(payload).map_err(|_| invalid())?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| invalid())?;
+        new_mac(secret, &payload)
+            .verify_slice(&signature)
+            .map_err(|_| invalid())?;
+
+        let payload = String::from_utf8(payload).map_err(|_| invalid())?;
+        let parts: Vec<_> = payload.split(':').collect();
+        match parts.as_slice() {
+            [TOKEN_VERSION, cursor, sort_key, desc, backward, filter_hash] => Ok(Self {
+                cursor: cursor.parse().map_err(|_| invalid())?,
+                sort_key: sort_key.to_string(),
+                desc: *desc == "1",
+                backward: *backward == "1",
+                filter_hash: filter_hash.to_string(),
+            }),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// set the cursor and the paging direction of the filter, if the token is issued for it
+    pub fn apply(&self, filter: &mut ReservationFilter) -> Result<(), Error> {
+        if self.filter_hash != filter_hash(filter) || self.desc != filter.desc {
+            return Err(Error::InvalidPageToken(
+                "the page token is issued for another filter".to_string(),
+            ));
+        }
+
+        if self.sort_key != SORT_KEY_ID {
+            return Err(Error::InvalidPageToken(format!(
+                "unsupported sort key: {}",
+                self.sort_key
+            )));
+        }
+
+        filter.cursor = Some(self.cursor);
+        filter.backward = self.backward;
+        Ok(())
+    }
+}
+
+fn new_mac(secret: &[u8], payload: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any size");
+    mac.update(payload);
+    mac
+}
+
+/// hash of the conditions which decide the rows of the pages, page_size could be changed between pages
+fn filter_hash(filter: &ReservationFilter) -> String {
+    let mut hasher = Sha256::new();
+    let statuses: Vec<_> = filter
+        .get_statuses()
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    for value in [
+        filter.resource_id.as_str(),
+        filter.user_id.as_str(),
+        &statuses.join(","),
+        if filter.desc { "desc" } else { "asc" },
+    ] {
+        hasher.update(value.len().to_be_bytes());
+        hasher.update(value);
+    }
+    hasher.finalize()[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ReservationFilterBuilder;
+
+    const SECRET: &[u8] = b"page-token-secret";
+
+    #[test]
+    fn page_token_should_round_trip() {
+        let filter = ReservationFilterBuilder::default()
+            .user_id("chalanzi")
+            .cursor(10)
+            .backward(true)
+            .build()
+            .unwrap();
+        let token = PageToken::new(&filter).unwrap();
+        let decoded = PageToken::decode(&token.encode(SECRET), SECRET).unwrap();
+        assert_eq!(decoded, token);
+
+        let mut next = ReservationFilterBuilder::default()
+            .user_id("chalanzi")
+            .page_size(20)
+            .build()
+            .unwrap();
+        decoded.apply(&mut next).unwrap();
+        assert_eq!(next.cursor, Some(10));
+        assert!(next.backward);
+    }
+
+    #[test]
+    fn page_token_should_reject_tampered_token() {
+        let filter = ReservationFilterBuilder::default()
+            .cursor(10)
+            .build()
+            .unwrap();
+        let token = PageToken::new(&filter).unwrap().encode(SECRET);
+        assert!(PageToken::decode(&token, b"another-secret").is_err());
+
+        let (_, signature) = token.split_once('.').unwrap();
+        let forged = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode("v1:1:id:0:0:0000000000000000"),
+            signature
+        );
+        assert_eq!(
+            PageToken::decode(&forged, SECRET).unwrap_err(),
+            Error::InvalidPageToken(forged.clone())
+        );
+    }
+
+    #[test]
+    fn page_token_should_reject_another_filter() {
+        let filter = ReservationFilterBuilder::default()
+            .user_id("chalanzi")
+            .cursor(10)
+            .build()
+            .unwrap();
+        let token = PageToken::new(&filter).unwrap();
+
+        let mut other = ReservationFilterBuilder::default()
+            .user_id("waner")
+            .build()
+            .unwrap();
+        assert!(token.apply(&mut other).is_err());
+        assert_eq!(other.cursor, None);
+    }
+}
@@ -0,0 +1,571 @@
+use std::collections::HashSet;
+
+use chrono::{
+    DateTime, Datelike, Days, FixedOffset, LocalResult, Months, NaiveDate, NaiveDateTime, Offset,
+    TimeZone, Utc,
+};
+use chrono_tz::Tz;
+
+use crate::{
+    convert_to_timestamp, convert_to_utc_time, CancelSeriesRequest, Error, RecurrenceFrequency,
+    RecurrenceRule, Reservation, SeriesScope, UpdateSeriesRequest, Validator, Weekday,
+};
+
+/// the most occurrences a rule could be expanded to
+const MAX_OCCURRENCES: usize = 500;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+impl RecurrenceRule {
+    /// the occurrences of the reservation order by start time. The reservation is the first one
+    /// unless it's an exception, the weeks of a weekly rule start on Monday. The periods are
+    /// counted in the timezone of the rule if it's set, otherwise in the utc offset
+    pub fn expand(&self, rsvp: &Reservation) -> Result<Vec<Reservation>, Error> {
+        self.validate()?;
+        rsvp.validate()?;
+
+        match self.timezone.parse::<Tz>() {
+            Ok(tz) => self.expand_in(rsvp, &tz),
+            Err(_) => self.expand_in(
+                rsvp,
+                &FixedOffset::east_opt(self.utc_offset as i32).unwrap(),
+            ),
+        }
+    }
+
+    fn expand_in<T: TimeZone>(
+        &self,
+        rsvp: &Reservation,
+        tz: &T,
+    ) -> Result<Vec<Reservation>, Error> {
+        let start = convert_to_utc_time(rsvp.start.as_ref().unwrap()).with_timezone(tz);
+        let duration = convert_to_utc_time(rsvp.end.as_ref().unwrap()) - start.with_timezone(&Utc);
+        let until = self.until.as_ref().map(convert_to_utc_time);
+
+        let mut starts = vec![];
+        'periods: for period in 0.. {
+            // the dates are out of range
+            let Some(candidates) = self.period_starts(&start, period) else {
+                break;
+            };
+            for candidate in candidates.into_iter().filter(|c| *c >= start) {
+                if self.count > 0 && starts.len() as i64 >= self.count {
+                    break 'periods;
+                }
+                if until.is_some_and(|until| candidate > until) {
+                    break 'periods;
+                }
+                if starts.len() >= MAX_OCCURRENCES {
+                    return Err(too_many_occurrences());
+                }
+                starts.push(candidate.with_timezone(&Utc));
+            }
+        }
+
+        // the exceptions are counted as occurrences, as RRULE does with EXDATE
+        let exceptions: HashSet<_> = self.exceptions.iter().map(convert_to_utc_time).collect();
+        let occurrences: Vec<_> = starts
+            .into_iter()
+            .filter(|start| !exceptions.contains(start))
+            .map(|start| Reservation {
+                start: Some(convert_to_timestamp(&start)),
+                end: Some(convert_to_timestamp(&(start + duration))),
+                ..rsvp.clone()
+            })
+            .collect();
+        if occurrences.is_empty() {
+            return Err(Error::InvalidRecurrence("no occurrence".into()));
+        }
+        Ok(occurrences)
+    }
+
+    fn interval(&self) -> i64 {
+        self.interval.max(1)
+    }
+
+    /// the candidate starts in the nth period (day, week or month) from the start, None if the
+    /// period is out of the range of dates
+    fn period_starts<T: TimeZone>(
+        &self,
+        start: &DateTime<T>,
+        period: i64,
+    ) -> Option<Vec<DateTime<T>>> {
+        let n = u64::try_from(period.checked_mul(self.interval())?).ok()?;
+        let frequency = RecurrenceFrequency::from_i32(self.frequency).unwrap();
+        let dates = match frequency {
+            RecurrenceFrequency::Daily => {
+                vec![start.date_naive().checked_add_days(Days::new(n))?]
+            }
+            RecurrenceFrequency::Weekly => {
+                let monday =
+                    start.date_naive() - Days::new(start.weekday().num_days_from_monday() as u64);
+                let monday = monday.checked_add_days(Days::new(n.checked_mul(7)?))?;
+                self.weekdays(start)
+                    .into_iter()
+                    .map(|day| monday.checked_add_days(Days::new(day as u64 - 1)))
+                    .collect::<Option<_>>()?
+            }
+            // the months without the day are skipped
+            RecurrenceFrequency::Monthly => {
+                let month = start
+                    .date_naive()
+                    .with_day(1)?
+                    .checked_add_months(Months::new(u32::try_from(n).ok()?))?;
+                NaiveDate::from_ymd_opt(month.year(), month.month(), start.day())
+                    .into_iter()
+                    .collect()
+            }
+            RecurrenceFrequency::Unknown => unreachable!("the rule is validated"),
+        };
+        dates
+            .into_iter()
+            .map(|date| local_time(&start.timezone(), date.and_time(start.time())))
+            .collect()
+    }
+
+    /// the weekdays of a weekly rule sorted from Monday, 1 for Monday
+    fn weekdays<T: TimeZone>(&self, start: &DateTime<T>) -> Vec<i32> {
+        if self.by_day.is_empty() {
+            return vec![start.weekday().number_from_monday() as i32];
+        }
+        let mut days = self.by_day.clone();
+        days.sort();
+        days.dedup();
+        days
+    }
+}
+
+/// the local time in the timezone. The earlier one is taken if it's repeated by a daylight saving
+/// change, and the offset before the change is used if it's skipped, as RFC 5545 does
+fn local_time<T: TimeZone>(tz: &T, local: NaiveDateTime) -> Option<DateTime<T>> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(time) | LocalResult::Ambiguous(time, _) => Some(time),
+        LocalResult::None => {
+            let before = tz.from_local_datetime(&local.checked_sub_days(Days::new(1))?);
+            let offset = before.earliest()?.offset().fix();
+            Some(tz.from_utc_datetime(&local.checked_sub_offset(offset)?))
+        }
+    }
+}
+
+fn too_many_occurrences() -> Error {
+    Error::InvalidRecurrence(format!("more than {} occurrences", MAX_OCCURRENCES))
+}
+
+impl Validator for RecurrenceRule {
+    fn validate(&self) -> Result<(), Error> {
+        let invalid = |s: &str| Err(Error::InvalidRecurrence(s.into()));
+
+        match RecurrenceFrequency::from_i32(self.frequency) {
+            None | Some(RecurrenceFrequency::Unknown) => return invalid("unknown frequency"),
+            Some(frequency) => {
+                if frequency != RecurrenceFrequency::Weekly && !self.by_day.is_empty() {
+                    return invalid("by_day is only for a weekly rule");
+                }
+            }
+        }
+        if self.interval < 0 {
+            return invalid("negative interval");
+        }
+        if self
+            .by_day
+            .iter()
+            .any(|day| matches!(Weekday::from_i32(*day), None | Some(Weekday::Unknown)))
+        {
+            return invalid("unknown weekday");
+        }
+
+        match (self.count, self.until.is_some()) {
+            (count, _) if count < 0 => return invalid("negative count"),
+            (0, false) => return invalid("count or until is required"),
+            (count, true) if count > 0 => return invalid("count and until are both set"),
+            (count, _) if count > MAX_OCCURRENCES as i64 => return Err(too_many_occurrences()),
+            _ => {}
+        }
+
+        if self.utc_offset.abs() >= SECONDS_PER_DAY {
+            return invalid("utc offset out of range");
+        }
+        if !self.timezone.is_empty() {
+            if self.timezone.parse::<Tz>().is_err() {
+                return invalid("unknown timezone");
+            }
+            if self.utc_offset != 0 {
+                return invalid("timezone and utc offset are both set");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Validator for UpdateSeriesRequest {
+    fn validate(&self) -> Result<(), Error> {
+        self.id.validate()?;
+        SeriesScope::from_i32(self.scope).ok_or(Error::InvalidScope(self.scope))?;
+        Ok(())
+    }
+}
+
+impl Validator for CancelSeriesRequest {
+    fn validate(&self) -> Result<(), Error> {
+        self.id.validate()?;
+        SeriesScope::from_i32(self.scope).ok_or(Error::InvalidScope(self.scope))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prost_types::Timestamp;
+
+    use super::*;
+
+    fn rsvp(start: &str, end: &str) -> Reservation {
+        Reservation::new_pending(
+            "chalanziId",
+            "room-1",
+            start.parse().unwrap(),
+            end.parse().unwrap(),
+            "standup",
+        )
+    }
+
+    fn new_rule(frequency: RecurrenceFrequency) -> RecurrenceRule {
+        RecurrenceRule {
+            frequency: frequency as i32,
+            ..Default::default()
+        }
+    }
+
+    fn starts(occurrences: &[Reservation]) -> Vec<String> {
+        occurrences
+            .iter()
+            .map(|r| r.start.clone().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn daily_rule_should_expand_by_count() {
+        let rule = RecurrenceRule {
+            interval: 2,
+            count: 3,
+            ..new_rule(RecurrenceFrequency::Daily)
+        };
+        let occurrences = rule
+            .expand(&rsvp("2022-12-25T10:00:00Z", "2022-12-25T11:30:00Z"))
+            .unwrap();
+        assert_eq!(
+            starts(&occurrences),
+            vec![
+                "2022-12-25T10:00:00Z",
+                "2022-12-27T10:00:00Z",
+                "2022-12-29T10:00:00Z"
+            ]
+        );
+        // the same reservation at another time
+        let last = occurrences.last().unwrap();
+        assert_eq!(
+            last.end.clone().unwrap().to_string(),
+            "2022-12-29T11:30:00Z"
+        );
+        assert_eq!(last.note, "standup");
+        assert_eq!(last.resource_id, "room-1");
+    }
+
+    #[test]
+    fn weekly_rule_should_expand_by_day_until() {
+        // 2022-12-27 is a Tuesday
+        let rule = RecurrenceRule {
+            by_day: vec![Weekday::Thursday as i32, Weekday::Tuesday as i32],
+            until: Some("2023-01-10T10:00:00Z".parse::<Timestamp>().unwrap()),
+            ..new_rule(RecurrenceFrequency::Weekly)
+        };
+        let occurrences = rule
+            .expand(&rsvp("2022-12-27T10:00:00Z", "2022-12-27T11:00:00Z"))
+            .unwrap();
+        assert_eq!(
+            starts(&occurrences),
+            vec![
+                "2022-12-27T10:00:00Z",
+                "2022-12-29T10:00:00Z",
+                "2023-01-03T10:00:00Z",
+                "2023-01-05T10:00:00Z",
+                "2023-01-10T10:00:00Z"
+            ]
+        );
+
+        // the weekdays before the start in the first week are skipped, every other week
+        let rule = RecurrenceRule {
+            interval: 2,
+            by_day: vec![Weekday::Monday as i32, Weekday::Friday as i32],
+            count: 3,
+            ..new_rule(RecurrenceFrequency::Weekly)
+        };
+        let occurrences = rule
+            .expand(&rsvp("2022-12-27T10:00:00Z", "2022-12-27T11:00:00Z"))
+            .unwrap();
+        assert_eq!(
+            starts(&occurrences),
+            vec![
+                "2022-12-30T10:00:00Z",
+                "2023-01-09T10:00:00Z",
+                "2023-01-13T10:00:00Z"
+            ]
+        );
+    }
+
+    #[test]
+    fn weekly_rule_should_count_days_in_utc_offset() {
+        // Tuesday 20:00 at -07:00 is Wednesday in UTC
+        let rule = RecurrenceRule {
+            by_day: vec![Weekday::Tuesday as i32],
+            count: 2,
+            utc_offset: -7 * 3600,
+            ..new_rule(RecurrenceFrequency::Weekly)
+        };
+        let occurrences = rule
+            .expand(&rsvp(
+                "2022-12-27T20:00:00-07:00",
+                "2022-12-27T21:00:00-07:00",
+            ))
+            .unwrap();
+        assert_eq!(
+            starts(&occurrences),
+            vec!["2022-12-28T03:00:00Z", "2023-01-04T03:00:00Z"]
+        );
+
+        let rule = RecurrenceRule {
+            utc_offset: 0,
+            ..rule
+        };
+        let occurrences = rule
+            .expand(&rsvp(
+                "2022-12-27T20:00:00-07:00",
+                "2022-12-27T21:00:00-07:00",
+            ))
+            .unwrap();
+        assert_eq!(
+            starts(&occurrences),
+            vec!["2023-01-03T03:00:00Z", "2023-01-10T03:00:00Z"]
+        );
+    }
+
+    #[test]
+    fn rule_in_timezone_should_keep_local_time_across_dst() {
+        // the daylight saving starts on 2023-03-12 in Los Angeles
+        let rule = RecurrenceRule {
+            count: 4,
+            timezone: "America/Los_Angeles".into(),
+            ..new_rule(RecurrenceFrequency::Daily)
+        };
+        let occurrences = rule
+            .expand(&rsvp(
+                "2023-03-10T10:00:00-08:00",
+                "2023-03-10T11:00:00-08:00",
+            ))
+            .unwrap();
+        assert_eq!(
+            starts(&occurrences),
+            vec![
+                "2023-03-10T18:00:00Z",
+                "2023-03-11T18:00:00Z",
+                "2023-03-12T17:00:00Z",
+                "2023-03-13T17:00:00Z"
+            ]
+        );
+
+        // a fixed offset doesn't
+        let rule = RecurrenceRule {
+            timezone: "".into(),
+            utc_offset: -8 * 3600,
+            ..rule
+        };
+        let occurrences = rule
+            .expand(&rsvp(
+                "2023-03-10T10:00:00-08:00",
+                "2023-03-10T11:00:00-08:00",
+            ))
+            .unwrap();
+        assert_eq!(starts(&occurrences)[3], "2023-03-13T18:00:00Z");
+
+        // 02:30 is skipped on 2023-03-12, it's counted in the offset before
+        let rule = RecurrenceRule {
+            count: 2,
+            timezone: "America/Los_Angeles".into(),
+            ..new_rule(RecurrenceFrequency::Daily)
+        };
+        let occurrences = rule
+            .expand(&rsvp(
+                "2023-03-11T02:30:00-08:00",
+                "2023-03-11T03:00:00-08:00",
+            ))
+            .unwrap();
+        assert_eq!(
+            starts(&occurrences),
+            vec!["2023-03-11T10:30:00Z", "2023-03-12T10:30:00Z"]
+        );
+
+        // 01:30 is repeated on 2023-11-05, the earlier one is taken
+        let occurrences = rule
+            .expand(&rsvp(
+                "2023-11-04T01:30:00-07:00",
+                "2023-11-04T02:00:00-07:00",
+            ))
+            .unwrap();
+        assert_eq!(
+            starts(&occurrences),
+            vec!["2023-11-04T08:30:00Z", "2023-11-05T08:30:00Z"]
+        );
+    }
+
+    #[test]
+    fn monthly_rule_should_skip_months_without_the_day() {
+        let rule = RecurrenceRule {
+            count: 4,
+            ..new_rule(RecurrenceFrequency::Monthly)
+        };
+        let occurrences = rule
+            .expand(&rsvp("2023-01-31T10:00:00Z", "2023-01-31T11:00:00Z"))
+            .unwrap();
+        assert_eq!(
+            starts(&occurrences),
+            vec![
+                "2023-01-31T10:00:00Z",
+                "2023-03-31T10:00:00Z",
+                "2023-05-31T10:00:00Z",
+                "2023-07-31T10:00:00Z"
+            ]
+        );
+    }
+
+    #[test]
+    fn exceptions_should_be_skipped_but_counted() {
+        let rule = RecurrenceRule {
+            count: 3,
+            exceptions: vec![
+                "2022-12-26T10:00:00Z".parse().unwrap(),
+                // not an occurrence
+                "2022-12-27T11:00:00Z".parse().unwrap(),
+            ],
+            ..new_rule(RecurrenceFrequency::Daily)
+        };
+        let occurrences = rule
+            .expand(&rsvp("2022-12-25T10:00:00Z", "2022-12-25T11:00:00Z"))
+            .unwrap();
+        assert_eq!(
+            starts(&occurrences),
+            vec!["2022-12-25T10:00:00Z", "2022-12-27T10:00:00Z"]
+        );
+
+        let rule = RecurrenceRule {
+            count: 1,
+            exceptions: vec!["2022-12-25T10:00:00Z".parse().unwrap()],
+            ..rule
+        };
+        let err = rule
+            .expand(&rsvp("2022-12-25T10:00:00Z", "2022-12-25T11:00:00Z"))
+            .unwrap_err();
+        assert_eq!(err, Error::InvalidRecurrence("no occurrence".into()));
+    }
+
+    #[test]
+    fn invalid_rule_should_be_rejected() {
+        let daily = RecurrenceRule {
+            count: 1,
+            ..new_rule(RecurrenceFrequency::Daily)
+        };
+        let until = Some("2023-01-10T10:00:00Z".parse::<Timestamp>().unwrap());
+        let cases = [
+            (
+                RecurrenceRule {
+                    frequency: 0,
+                    ..daily.clone()
+                },
+                "unknown frequency",
+            ),
+            (
+                RecurrenceRule {
+                    by_day: vec![Weekday::Monday as i32],
+                    ..daily.clone()
+                },
+                "by_day is only for a weekly rule",
+            ),
+            (
+                RecurrenceRule {
+                    by_day: vec![8],
+                    ..new_rule(RecurrenceFrequency::Weekly)
+                },
+                "unknown weekday",
+            ),
+            (
+                RecurrenceRule {
+                    interval: -1,
+                    ..daily.clone()
+                },
+                "negative interval",
+            ),
+            (
+                RecurrenceRule {
+                    count: 0,
+                    ..daily.clone()
+                },
+                "count or until is required",
+            ),
+            (
+                RecurrenceRule {
+                    until: until.clone(),
+                    ..daily.clone()
+                },
+                "count and until are both set",
+            ),
+            (
+                RecurrenceRule {
+                    count: 501,
+                    ..daily.clone()
+                },
+                "more than 500 occurrences",
+            ),
+            (
+                RecurrenceRule {
+                    utc_offset: 86400,
+                    ..daily.clone()
+                },
+                "utc offset out of range",
+            ),
+            (
+                RecurrenceRule {
+                    timezone: "Mars/Olympus_Mons".into(),
+                    ..daily.clone()
+                },
+                "unknown timezone",
+            ),
+            (
+                RecurrenceRule {
+                    timezone: "America/Los_Angeles".into(),
+                    utc_offset: -8 * 3600,
+                    ..daily.clone()
+                },
+                "timezone and utc offset are both set",
+            ),
+        ];
+        for (rule, expected) in cases {
+            assert_eq!(
+                rule.validate(),
+                Err(Error::InvalidRecurrence(expected.into())),
+                "{}",
+                expected
+            );
+        }
+
+        // too many occurrences before until
+        let rule = RecurrenceRule {
+            count: 0,
+            until: Some("2025-01-01T00:00:00Z".parse::<Timestamp>().unwrap()),
+            ..daily
+        };
+        let err = rule
+            .expand(&rsvp("2022-12-25T10:00:00Z", "2022-12-25T11:00:00Z"))
+            .unwrap_err();
+        assert_eq!(err, too_many_occurrences());
+    }
+}
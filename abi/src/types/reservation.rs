@@ -7,8 +7,8 @@ use sqlx::{
 };
 
 use crate::{
-    convert_to_timestamp, get_timestamp, pager::Id, validate_range, Error, Reservation,
-    ReservationStatus, RsvpStatus, Validator,
+    convert_to_timestamp, get_timestamp, pager::Id, validate_range, Error, Normalizer, Reservation,
+    ReservationStatus, RsvpStatus, SeriesId, Validator,
 };
 
 impl Reservation {
@@ -27,6 +27,8 @@ impl Reservation {
             start: Some(convert_to_timestamp(&start.with_timezone(&Utc))),
             end: Some(convert_to_timestamp(&end.with_timezone(&Utc))),
             note: note.into(),
+            series_id: 0,
+            quantity: 1,
         }
     }
 
@@ -53,10 +55,22 @@ impl Validator for Reservation {
 
         validate_range(self.start.as_ref(), self.end.as_ref())?;
 
+        if self.quantity < 0 {
+            return Err(Error::InvalidQuantity(self.quantity));
+        }
+
         Ok(())
     }
 }
 
+impl Normalizer for Reservation {
+    fn do_normalize(&mut self) {
+        if self.quantity == 0 {
+            self.quantity = 1;
+        }
+    }
+}
+
 impl FromRow<'_, PgRow> for Reservation {
     fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
         let range: PgRange<DateTime<Utc>> = row.get("timespan");
@@ -74,6 +88,10 @@ impl FromRow<'_, PgRow> for Reservation {
             end: Some(convert_to_timestamp(&end)),
             note: row.get("note"),
             status: ReservationStatus::from(status) as i32,
+            series_id: row
+                .get::<Option<SeriesId>, _>("series_id")
+                .unwrap_or_default(),
+            quantity: row.get("quantity"),
         })
     }
 }
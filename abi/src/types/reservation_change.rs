@@ -0,0 +1,96 @@
+use sqlx::{postgres::PgRow, FromRow, Row};
+
+use crate::{
+    AckRequest, Error, ListenRequest, ListenResponse, Reservation, ReservationUpdateType,
+    RsvpUpdateType, Validator,
+};
+
+/// the same length as "reservation_consumers.name"
+const MAX_CONSUMER_LEN: usize = 64;
+
+/// 转换数据库"reservation_update_type" 枚举值 到pb 定义的 "reservation_update_type"
+impl From<RsvpUpdateType> for ReservationUpdateType {
+    fn from(op: RsvpUpdateType) -> Self {
+        match op {
+            RsvpUpdateType::Unknown => ReservationUpdateType::Unknown,
+            RsvpUpdateType::Create => ReservationUpdateType::Create,
+            RsvpUpdateType::Update => ReservationUpdateType::Update,
+            RsvpUpdateType::Delete => ReservationUpdateType::Delete,
+        }
+    }
+}
+
+/// a row of "reservation_changes", the change id shall be selected as "change_id", and the
+/// reservation columns with the same names as "reservations" (e.g. reservation_id AS id)
+impl FromRow<'_, PgRow> for ListenResponse {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let op: RsvpUpdateType = row.get("op");
+        Ok(Self {
+            op: ReservationUpdateType::from(op) as i32,
+            reservation: Some(Reservation::from_row(row)?),
+            id: row.get("change_id"),
+        })
+    }
+}
+
+impl Validator for ListenRequest {
+    fn validate(&self) -> Result<(), Error> {
+        if let Some(since) = self.since {
+            if since < 0 {
+                return Err(Error::InvalidCursor(since));
+            }
+        }
+
+        if self.consumer.len() > MAX_CONSUMER_LEN {
+            return Err(Error::InvalidConsumer(self.consumer.clone()));
+        }
+
+        Ok(())
+    }
+}
+
+impl Validator for AckRequest {
+    fn validate(&self) -> Result<(), Error> {
+        if self.consumer.is_empty() || self.consumer.len() > MAX_CONSUMER_LEN {
+            return Err(Error::InvalidConsumer(self.consumer.clone()));
+        }
+
+        if self.id < 0 {
+            return Err(Error::InvalidCursor(self.id));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listen_request_should_reject_invalid_since() {
+        let req = ListenRequest {
+            since: Some(-1),
+            consumer: "billing".to_string(),
+        };
+        assert_eq!(req.validate().unwrap_err(), Error::InvalidCursor(-1));
+
+        let req = ListenRequest {
+            since: None,
+            consumer: "".to_string(),
+        };
+        assert!(req.validate().is_ok());
+    }
+
+    #[test]
+    fn ack_request_should_reject_empty_consumer() {
+        let req = AckRequest {
+            consumer: "".to_string(),
+            id: 1,
+        };
+        assert_eq!(
+            req.validate().unwrap_err(),
+            Error::InvalidConsumer("".to_string())
+        );
+    }
+}
@@ -1,5 +1,9 @@
 use std::collections::VecDeque;
 
+use sqlx::{Postgres, QueryBuilder};
+use tonic::metadata::MetadataMap;
+
+use super::{get_statuses, push_status_cond, push_user_resource_cond, validate_statuses};
 use crate::{
     pager::{Id, PageInfo, Pager, Paginator},
     Error, FilterPager, Normalizer, ReservationFilter, ReservationFilterBuilder, ReservationStatus,
@@ -27,7 +31,7 @@ impl Validator for ReservationFilter {
             }
         }
 
-        ReservationStatus::from_i32(self.status).ok_or(Error::InvalidStatus(self.status))?;
+        validate_statuses(self.status, &self.statuses)?;
 
         Ok(())
     }
@@ -35,9 +39,7 @@ impl Validator for ReservationFilter {
 
 impl Normalizer for ReservationFilter {
     fn do_normalize(&mut self) {
-        if self.status == ReservationStatus::Unknown as i32 {
-            self.status = ReservationStatus::Pending as i32;
-        }
+        self.statuses = self.get_statuses().into_iter().map(|s| s as i32).collect();
     }
 }
 
@@ -61,6 +63,40 @@ impl From<&FilterPager> for Pager {
     }
 }
 
+/// metadata keys of the pager, for the streaming responses which could not carry it in the message
+const PAGER_PREV_KEY: &str = "x-pager-prev";
+const PAGER_NEXT_KEY: &str = "x-pager-next";
+const PAGER_TOTAL_KEY: &str = "x-pager-total";
+
+impl FilterPager {
+    pub fn to_metadata(&self, metadata: &mut MetadataMap) {
+        let pairs = [
+            (PAGER_PREV_KEY, self.prev),
+            (PAGER_NEXT_KEY, self.next),
+            (PAGER_TOTAL_KEY, self.total),
+        ];
+        for (key, value) in pairs {
+            if let Some(value) = value {
+                metadata.insert(key, value.into());
+            }
+        }
+    }
+
+    pub fn from_metadata(metadata: &MetadataMap) -> Self {
+        let get = |key| {
+            metadata
+                .get(key)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse().ok())
+        };
+        Self {
+            prev: get(PAGER_PREV_KEY),
+            next: get(PAGER_NEXT_KEY),
+            total: get(PAGER_TOTAL_KEY),
+        }
+    }
+}
+
 impl ReservationFilter {
     pub fn get_pager<T: Id>(&self, data: &mut VecDeque<T>) -> FilterPager {
         let page_info = self.page_info();
@@ -69,25 +105,32 @@ impl ReservationFilter {
     }
 
     pub fn get_cursor(&self) -> i64 {
-        self.cursor.unwrap_or(if self.desc { i64::MAX } else { 0 })
+        self.cursor
+            .unwrap_or(if self.is_desc_query() { i64::MAX } else { 0 })
     }
 
-    pub fn get_status(&self) -> ReservationStatus {
-        ReservationStatus::from_i32(self.status).unwrap()
+    /// the statuses to match, an empty list matches all statuses
+    pub fn get_statuses(&self) -> Vec<ReservationStatus> {
+        get_statuses(self.status, &self.statuses)
     }
 
     pub fn next_page(&self, pager: &FilterPager) -> Option<Self> {
         let page_info = self.page_info();
         let pager = pager.into();
         let page_info = page_info.next_page(&pager);
-        page_info.map(|page_info| Self {
-            cursor: page_info.cursor,
-            resource_id: self.resource_id.clone(),
-            user_id: self.user_id.clone(),
-            status: self.status,
-            page_size: page_info.page_size,
-            desc: page_info.desc,
-        })
+        page_info.map(|page_info| self.with_page_info(page_info))
+    }
+
+    pub fn prev_page(&self, pager: &FilterPager) -> Option<Self> {
+        let page_info = self.page_info();
+        let pager = pager.into();
+        let page_info = page_info.prev_page(&pager);
+        page_info.map(|page_info| self.with_page_info(page_info))
+    }
+
+    /// the direction of the sql, a backward page is fetched in the reversed direction
+    fn is_desc_query(&self) -> bool {
+        self.desc != self.backward
     }
 
     fn page_info(&self) -> PageInfo {
@@ -95,36 +138,63 @@ impl ReservationFilter {
             cursor: self.cursor,
             page_size: self.page_size,
             desc: self.desc,
+            backward: self.backward,
         }
     }
-}
 
-impl ToSql for ReservationFilter {
-    fn to_sql(&self) -> String {
+    fn with_page_info(&self, page_info: PageInfo) -> Self {
+        Self {
+            cursor: page_info.cursor,
+            resource_id: self.resource_id.clone(),
+            user_id: self.user_id.clone(),
+            status: self.status,
+            statuses: self.statuses.clone(),
+            page_size: page_info.page_size,
+            desc: page_info.desc,
+            backward: page_info.backward,
+            with_total: self.with_total,
+        }
+    }
+
+    fn push_page_query(&self, query: &mut QueryBuilder<'static, Postgres>) {
         let middle_plus = if self.cursor.is_none() { 0 } else { 1 };
         let limit = self.page_size + 1 + middle_plus;
 
-        let status = self.get_status();
-
-        let cursor_cond = if self.desc {
+        let cursor_cond = if self.is_desc_query() {
             format!("id <= {}", self.get_cursor())
         } else {
             format!("id >= {}", self.get_cursor())
         };
 
-        let user_resource_cond = match (self.user_id.is_empty(), self.resource_id.is_empty()) {
-            (true, true) => "TRUE".into(),
-            (true, false) => format!("resource_id = '{}'", self.resource_id),
-            (false, true) => format!("user_id = '{}'", self.user_id),
-            (false, false) => format!(
-                "user_id = '{}' AND resource_id = '{}'",
-                self.user_id, self.resource_id
-            ),
-        };
+        let direction = if self.is_desc_query() { "DESC" } else { "ASC" };
 
-        let direction = if self.desc { "DESC" } else { "ASC" };
+        query.push("SELECT * FROM rsvp.reservations WHERE ");
+        push_status_cond(query, &self.get_statuses());
+        query.push(format!("{} AND ", cursor_cond));
+        push_user_resource_cond(query, &self.user_id, &self.resource_id);
+        query.push(format!(" ORDER BY id {} LIMIT {}", direction, limit));
+    }
+}
+
+impl ToSql for ReservationFilter {
+    fn to_query(&self) -> QueryBuilder<'static, Postgres> {
+        if !self.with_total {
+            let mut query = QueryBuilder::new("");
+            self.push_page_query(&mut query);
+            return query;
+        }
 
-        format!("SELECT * FROM rsvp.reservations WHERE status = '{}'::rsvp.reservation_status AND {} AND {} ORDER BY id {} LIMIT {}",status,cursor_cond,user_resource_cond,direction,limit)
+        // count in the same statement, a row with null reservation columns is returned if the page is empty
+        let mut query = QueryBuilder::new(
+            "WITH total AS (SELECT COUNT(*) AS total FROM rsvp.reservations WHERE ",
+        );
+        push_status_cond(&mut query, &self.get_statuses());
+        push_user_resource_cond(&mut query, &self.user_id, &self.resource_id);
+        query.push(") SELECT page.*, total.total FROM total LEFT JOIN LATERAL (");
+        self.push_page_query(&mut query);
+        let direction = if self.is_desc_query() { "DESC" } else { "ASC" };
+        query.push(format!(") page ON TRUE ORDER BY page.id {}", direction));
+        query
     }
 }
 
@@ -134,7 +204,88 @@ mod tests {
     use crate::{pager::pager_test_utils::generate_test_ids, ReservationFilterBuilder};
 
     #[test]
-    fn filter_should_generate_correct_pager() {}
+    fn filter_should_generate_correct_pager() {
+        let filter = ReservationFilterBuilder::default()
+            .user_id("chalanzi")
+            .build()
+            .unwrap();
+
+        let mut data = generate_test_ids(1, 11);
+        let pager = filter.get_pager(&mut data);
+        assert_eq!(pager.prev, None);
+        assert_eq!(pager.next, Some(10));
+        assert!(filter.prev_page(&pager).is_none());
+
+        let filter = filter.next_page(&pager).unwrap();
+        let mut data = generate_test_ids(10, 21);
+        let pager = filter.get_pager(&mut data);
+        assert_eq!(pager.prev, Some(11));
+        assert_eq!(pager.next, Some(20));
+
+        // back to the first page, the rows are fetched in the reversed direction
+        let filter = filter.prev_page(&pager).unwrap();
+        assert_eq!(filter.cursor, Some(11));
+        assert!(filter.backward);
+        let mut data = generate_test_ids(1, 11).into_iter().rev().collect();
+        let pager = filter.get_pager(&mut data);
+        assert_eq!(pager.prev, None);
+        assert_eq!(pager.next, Some(10));
+        assert_eq!(data.front().unwrap().id(), 1);
+        assert_eq!(data.len(), 10);
+
+        // forward again from a backward page
+        let filter = filter.next_page(&pager).unwrap();
+        assert_eq!(filter.cursor, Some(10));
+        assert!(!filter.backward);
+    }
+
+    #[test]
+    fn filter_prev_page_should_generate_correct_sql() {
+        let filter = ReservationFilterBuilder::default()
+            .user_id("chalanzi")
+            .cursor(11)
+            .backward(true)
+            .build()
+            .unwrap();
+        let query = filter.to_query();
+        assert_eq!(query.sql(), "SELECT * FROM rsvp.reservations WHERE id <= 11 AND user_id = $1 ORDER BY id DESC LIMIT 12");
+
+        let filter = ReservationFilterBuilder::default()
+            .cursor(11)
+            .desc(true)
+            .backward(true)
+            .build()
+            .unwrap();
+        let query = filter.to_query();
+        assert_eq!(
+            query.sql(),
+            "SELECT * FROM rsvp.reservations WHERE id >= 11 AND TRUE ORDER BY id ASC LIMIT 12"
+        );
+    }
+
+    #[test]
+    fn filter_with_total_should_generate_correct_sql() {
+        let filter = ReservationFilterBuilder::default()
+            .user_id("chalanzi")
+            .with_total(true)
+            .build()
+            .unwrap();
+        let query = filter.to_query();
+        assert_eq!(query.sql(), "WITH total AS (SELECT COUNT(*) AS total FROM rsvp.reservations WHERE user_id = $1) SELECT page.*, total.total FROM total LEFT JOIN LATERAL (SELECT * FROM rsvp.reservations WHERE id >= 0 AND user_id = $2 ORDER BY id ASC LIMIT 11) page ON TRUE ORDER BY page.id ASC");
+    }
+
+    #[test]
+    fn pager_should_round_trip_metadata() {
+        let pager = FilterPager {
+            prev: None,
+            next: Some(3),
+            total: Some(25),
+        };
+        let mut metadata = MetadataMap::new();
+        pager.to_metadata(&mut metadata);
+        assert!(metadata.get(PAGER_PREV_KEY).is_none());
+        assert_eq!(FilterPager::from_metadata(&metadata), pager);
+    }
 
     #[test]
     fn filter_should_generate_correct_sql() {
@@ -143,32 +294,36 @@ mod tests {
             .build()
             .unwrap();
 
-        let sql = filter.to_sql();
+        let query = filter.to_query();
+        let sql = query.sql();
 
-        assert_eq!(sql,"SELECT * FROM rsvp.reservations WHERE status = 'pending'::rsvp.reservation_status AND id >= 0 AND user_id = 'chalanzi' ORDER BY id ASC LIMIT 11");
+        assert_eq!(sql,"SELECT * FROM rsvp.reservations WHERE id >= 0 AND user_id = $1 ORDER BY id ASC LIMIT 11");
 
         let filter = ReservationFilterBuilder::default()
             .user_id("chalanzi")
             .resource_id("test")
             .build()
             .unwrap();
-        let sql = filter.to_sql();
-        assert_eq!(sql,"SELECT * FROM rsvp.reservations WHERE status = 'pending'::rsvp.reservation_status AND id >= 0 AND user_id = 'chalanzi' AND resource_id = 'test' ORDER BY id ASC LIMIT 11");
+        let query = filter.to_query();
+        let sql = query.sql();
+        assert_eq!(sql,"SELECT * FROM rsvp.reservations WHERE id >= 0 AND user_id = $1 AND resource_id = $2 ORDER BY id ASC LIMIT 11");
 
         let filter = ReservationFilterBuilder::default()
             .desc(true)
             .build()
             .unwrap();
-        let sql = filter.to_sql();
-        assert_eq!(sql,"SELECT * FROM rsvp.reservations WHERE status = 'pending'::rsvp.reservation_status AND id <= 9223372036854775807 AND TRUE ORDER BY id DESC LIMIT 11");
+        let query = filter.to_query();
+        let sql = query.sql();
+        assert_eq!(sql,"SELECT * FROM rsvp.reservations WHERE id <= 9223372036854775807 AND TRUE ORDER BY id DESC LIMIT 11");
 
         let filter = ReservationFilterBuilder::default()
             .user_id("chalanzi")
             .cursor(100)
             .build()
             .unwrap();
-        let sql = filter.to_sql();
-        assert_eq!(sql,"SELECT * FROM rsvp.reservations WHERE status = 'pending'::rsvp.reservation_status AND id >= 100 AND user_id = 'chalanzi' ORDER BY id ASC LIMIT 12");
+        let query = filter.to_query();
+        let sql = query.sql();
+        assert_eq!(sql,"SELECT * FROM rsvp.reservations WHERE id >= 100 AND user_id = $1 ORDER BY id ASC LIMIT 12");
 
         let filter = ReservationFilterBuilder::default()
             .user_id("chalanzi")
@@ -176,8 +331,29 @@ mod tests {
             .desc(true)
             .build()
             .unwrap();
-        let sql = filter.to_sql();
-        assert_eq!(sql,"SELECT * FROM rsvp.reservations WHERE status = 'pending'::rsvp.reservation_status AND id <= 10 AND user_id = 'chalanzi' ORDER BY id DESC LIMIT 12");
+        let query = filter.to_query();
+        let sql = query.sql();
+        assert_eq!(sql,"SELECT * FROM rsvp.reservations WHERE id <= 10 AND user_id = $1 ORDER BY id DESC LIMIT 12");
+    }
+
+    #[test]
+    fn filter_with_statuses_should_generate_correct_sql() {
+        let filter = ReservationFilterBuilder::default()
+            .status(ReservationStatus::Confirmed as i32)
+            .build()
+            .unwrap();
+        let query = filter.to_query();
+        assert_eq!(query.sql(), "SELECT * FROM rsvp.reservations WHERE status = 'confirmed'::rsvp.reservation_status AND id >= 0 AND TRUE ORDER BY id ASC LIMIT 11");
+
+        let filter = ReservationFilterBuilder::default()
+            .statuses(vec![
+                ReservationStatus::Confirmed as i32,
+                ReservationStatus::Pending as i32,
+            ])
+            .build()
+            .unwrap();
+        let query = filter.to_query();
+        assert_eq!(query.sql(), "SELECT * FROM rsvp.reservations WHERE status IN ('pending'::rsvp.reservation_status, 'confirmed'::rsvp.reservation_status) AND id >= 0 AND TRUE ORDER BY id ASC LIMIT 11");
     }
 
     #[test]
@@ -195,12 +371,13 @@ mod tests {
         assert_eq!(pager.next, Some(10));
 
         let filter = filter.next_page(&pager).unwrap();
-        let sql = filter.to_sql();
+        let query = filter.to_query();
+        let sql = query.sql();
 
         println!("sql1:{}", sql);
         assert_eq!(
             sql,
-            "SELECT * FROM rsvp.reservations WHERE status = 'pending'::rsvp.reservation_status AND id >= 10 AND resource_id = 'router-1' ORDER BY id ASC LIMIT 12"
+            "SELECT * FROM rsvp.reservations WHERE id >= 10 AND resource_id = $1 ORDER BY id ASC LIMIT 12"
         );
 
         let mut data = generate_test_ids(10, 20);
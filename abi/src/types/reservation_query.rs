@@ -1,9 +1,11 @@
+use std::ops::Bound;
+
 use chrono::{DateTime, Utc};
-use prost_types::Timestamp;
-use sqlx::postgres::types::PgRange;
+use sqlx::{postgres::types::PgRange, Postgres, QueryBuilder};
 
+use super::{get_statuses, push_status_cond, push_user_resource_cond, validate_statuses};
 use crate::{
-    convert_to_utc_time, get_timestamp, Error, Normalizer, ReservationQuery,
+    convert_to_utc_time, Error, FilterPager, Normalizer, ReservationMatchMode, ReservationQuery,
     ReservationQueryBuilder, ReservationStatus, ToSql, Validator,
 };
 
@@ -18,18 +20,90 @@ impl ReservationQueryBuilder {
 }
 
 impl ReservationQuery {
-    pub fn get_status(&self) -> ReservationStatus {
-        ReservationStatus::from_i32(self.status).unwrap()
+    /// the statuses to match, an empty list matches all statuses
+    pub fn get_statuses(&self) -> Vec<ReservationStatus> {
+        get_statuses(self.status, &self.statuses)
     }
 
+    /// the time window of the query, the start or end is unbounded if it is not set
     pub fn get_timespan(&self) -> PgRange<DateTime<Utc>> {
-        get_timestamp(self.start.as_ref(), self.end.as_ref())
+        let f = |ts| match ts {
+            Some(ts) => Bound::Included(convert_to_utc_time(ts)),
+            None => Bound::Unbounded,
+        };
+        let start = f(self.start.as_ref());
+        let end = match f(self.end.as_ref()) {
+            Bound::Included(end) => Bound::Excluded(end),
+            end => end,
+        };
+        PgRange { start, end }
+    }
+
+    /// how the reservations match the time window of the query
+    pub fn get_match_mode(&self) -> ReservationMatchMode {
+        ReservationMatchMode::from_i32(self.match_mode).unwrap_or(ReservationMatchMode::Contained)
+    }
+
+    /// page_size 0 means all the reservations are returned in one page
+    pub fn is_paged(&self) -> bool {
+        self.page_size > 0
+    }
+
+    /// pager of a paged query, prev/next are the page numbers
+    pub fn get_pager(&self, total: i64) -> FilterPager {
+        let prev = if self.page > 1 {
+            Some(self.page - 1)
+        } else {
+            None
+        };
+        let next = if self.page * self.page_size < total {
+            Some(self.page + 1)
+        } else {
+            None
+        };
+
+        FilterPager {
+            prev,
+            next,
+            total: Some(total),
+        }
+    }
+
+    /// count all the reservations matching the query, regardless of the page
+    pub fn to_count_query(&self) -> QueryBuilder<'static, Postgres> {
+        let mut query = QueryBuilder::new("SELECT COUNT(*) FROM rsvp.reservations WHERE ");
+        self.push_conditions(&mut query);
+        query
+    }
+
+    fn push_conditions(&self, query: &mut QueryBuilder<'static, Postgres>) {
+        let cond = match self.get_match_mode() {
+            ReservationMatchMode::Contained => " @> timespan AND ",
+            ReservationMatchMode::Overlapping => " && timespan AND ",
+            ReservationMatchMode::StartingWithin => " @> lower(timespan) AND ",
+        };
+        query.push_bind(self.get_timespan()).push(cond);
+        push_status_cond(query, &self.get_statuses());
+        push_user_resource_cond(query, &self.user_id, &self.resource_id);
     }
 }
 
 impl Validator for ReservationQuery {
     fn validate(&self) -> Result<(), crate::Error> {
-        ReservationStatus::from_i32(self.status).ok_or(Error::InvalidStatus(self.status))?;
+        validate_statuses(self.status, &self.statuses)?;
+
+        if ReservationMatchMode::from_i32(self.match_mode).is_none() {
+            return Err(Error::InvalidMatchMode(self.match_mode));
+        }
+
+        // the offset of the page must fit in an i64
+        if self.page < 0 || self.page.checked_mul(self.page_size).is_none() {
+            return Err(Error::InvalidPage(self.page));
+        }
+
+        if self.page_size != 0 && (self.page_size < 10 || self.page_size > 100) {
+            return Err(Error::InvalidPageSize(self.page_size));
+        }
 
         if let (Some(start), Some(end)) = (self.start.as_ref(), self.end.as_ref()) {
             if start.seconds >= end.seconds {
@@ -43,41 +117,191 @@ impl Validator for ReservationQuery {
 
 impl Normalizer for ReservationQuery {
     fn do_normalize(&mut self) {
-        if self.status == ReservationStatus::Unknown as i32 {
-            self.status = ReservationStatus::Pending as i32;
+        self.statuses = self.get_statuses().into_iter().map(|s| s as i32).collect();
+
+        if self.page < 1 {
+            self.page = 1;
         }
     }
 }
 
 impl ToSql for ReservationQuery {
-    fn to_sql(&self) -> String {
-        let status = self.get_status();
+    fn to_query(&self) -> QueryBuilder<'static, Postgres> {
+        let direction = if self.desc { "DESC" } else { "ASC" };
+
+        let mut query = QueryBuilder::new("SELECT * FROM rsvp.reservations WHERE ");
+        self.push_conditions(&mut query);
+        // order by id as well to make the pages stable
+        query.push(format!(
+            " ORDER BY lower(timespan) {}, id {}",
+            direction, direction
+        ));
 
-        let timespan = format!(
-            "tstzrange('{}','{}')",
-            get_time_string(self.start.as_ref(), true),
-            get_time_string(self.end.as_ref(), false)
+        if self.is_paged() {
+            query.push(format!(
+                " LIMIT {} OFFSET {}",
+                self.page_size,
+                (self.page - 1) * self.page_size
+            ));
+        }
+        query
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prost_types::Timestamp;
+
+    use super::*;
+
+    #[test]
+    fn query_should_generate_correct_sql() {
+        let query = ReservationQueryBuilder::default()
+            .user_id("chalanzi")
+            .start("2022-12-01T15:00:00-0700".parse::<Timestamp>().unwrap())
+            .end("2022-12-28T12:00:00-0700".parse::<Timestamp>().unwrap())
+            .build()
+            .unwrap();
+        let query = query.to_query();
+        assert_eq!(query.sql(), "SELECT * FROM rsvp.reservations WHERE $1 @> timespan AND user_id = $2 ORDER BY lower(timespan) ASC, id ASC LIMIT 10 OFFSET 0");
+
+        let query = ReservationQueryBuilder::default()
+            .user_id("it's me")
+            .resource_id("room-1")
+            .desc(true)
+            .page(3)
+            .page_size(20)
+            .build()
+            .unwrap();
+        let query = query.to_query();
+        assert_eq!(query.sql(), "SELECT * FROM rsvp.reservations WHERE $1 @> timespan AND user_id = $2 AND resource_id = $3 ORDER BY lower(timespan) DESC, id DESC LIMIT 20 OFFSET 40");
+
+        let query = ReservationQueryBuilder::default()
+            .page_size(0)
+            .build()
+            .unwrap();
+        let query = query.to_query();
+        assert_eq!(query.sql(), "SELECT * FROM rsvp.reservations WHERE $1 @> timespan AND TRUE ORDER BY lower(timespan) ASC, id ASC");
+    }
+
+    #[test]
+    fn query_should_generate_correct_status_sql() {
+        let query = ReservationQueryBuilder::default()
+            .status(ReservationStatus::Pending as i32)
+            .build()
+            .unwrap();
+        let query = query.to_query();
+        assert_eq!(query.sql(), "SELECT * FROM rsvp.reservations WHERE $1 @> timespan AND status = 'pending'::rsvp.reservation_status AND TRUE ORDER BY lower(timespan) ASC, id ASC LIMIT 10 OFFSET 0");
+
+        let query = ReservationQueryBuilder::default()
+            .status(ReservationStatus::Pending as i32)
+            .statuses(vec![
+                ReservationStatus::Confirmed as i32,
+                ReservationStatus::Pending as i32,
+                ReservationStatus::Unknown as i32,
+            ])
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.get_statuses(),
+            vec![ReservationStatus::Pending, ReservationStatus::Confirmed]
         );
+        let query = query.to_query();
+        assert_eq!(query.sql(), "SELECT * FROM rsvp.reservations WHERE $1 @> timespan AND status IN ('pending'::rsvp.reservation_status, 'confirmed'::rsvp.reservation_status) AND TRUE ORDER BY lower(timespan) ASC, id ASC LIMIT 10 OFFSET 0");
 
-        let condition = match (self.user_id.is_empty(), self.resource_id.is_empty()) {
-            (true, true) => "TRUE".into(),
-            (true, false) => format!("resource_id = '{}'", self.resource_id),
-            (false, true) => format!("user_id = '{}'", self.user_id),
-            (false, false) => format!(
-                "user_id = '{}' AND resource_id = '{}'",
-                self.user_id, self.resource_id
-            ),
+        let err = ReservationQueryBuilder::default()
+            .statuses(vec![10])
+            .build()
+            .unwrap_err();
+        assert_eq!(err, Error::InvalidStatus(10));
+    }
+
+    #[test]
+    fn query_should_generate_correct_match_mode_sql() {
+        let sql = |mode: ReservationMatchMode| {
+            let query = ReservationQueryBuilder::default()
+                .resource_id("room-1")
+                .match_mode(mode as i32)
+                .build()
+                .unwrap();
+            query.to_count_query().sql().to_string()
         };
 
-        let direction = if self.desc { "DESC" } else { "ASC" };
+        assert_eq!(
+            sql(ReservationMatchMode::Contained),
+            "SELECT COUNT(*) FROM rsvp.reservations WHERE $1 @> timespan AND resource_id = $2"
+        );
+        assert_eq!(
+            sql(ReservationMatchMode::Overlapping),
+            "SELECT COUNT(*) FROM rsvp.reservations WHERE $1 && timespan AND resource_id = $2"
+        );
+        assert_eq!(
+            sql(ReservationMatchMode::StartingWithin),
+            "SELECT COUNT(*) FROM rsvp.reservations WHERE $1 @> lower(timespan) AND resource_id = $2"
+        );
 
-        format!("SELECT * FROM rsvp.reservations WHERE {} @>timespan AND status = '{}'::rsvp.reservation_status AND {} ORDER BY lower(timespan) {} ",timespan,status,condition,direction )
+        let err = ReservationQueryBuilder::default()
+            .match_mode(3)
+            .build()
+            .unwrap_err();
+        assert_eq!(err, Error::InvalidMatchMode(3));
+    }
+
+    #[test]
+    fn query_should_generate_correct_count_sql() {
+        let query = ReservationQueryBuilder::default()
+            .resource_id("room-1")
+            .page(2)
+            .build()
+            .unwrap();
+        let query = query.to_count_query();
+        assert_eq!(
+            query.sql(),
+            "SELECT COUNT(*) FROM rsvp.reservations WHERE $1 @> timespan AND resource_id = $2"
+        );
+    }
+
+    #[test]
+    fn query_should_generate_correct_pager() {
+        let query = ReservationQueryBuilder::default().build().unwrap();
+        assert_eq!(query.page, 1);
+        let pager = query.get_pager(25);
+        assert_eq!(pager.prev, None);
+        assert_eq!(pager.next, Some(2));
+        assert_eq!(pager.total, Some(25));
+
+        let query = ReservationQueryBuilder::default().page(3).build().unwrap();
+        let pager = query.get_pager(25);
+        assert_eq!(pager.prev, Some(2));
+        assert_eq!(pager.next, None);
+    }
+
+    #[test]
+    fn query_should_reject_invalid_page() {
+        let err = ReservationQueryBuilder::default()
+            .page(-1)
+            .build()
+            .unwrap_err();
+        assert_eq!(err, Error::InvalidPage(-1));
+
+        let err = ReservationQueryBuilder::default()
+            .page(i64::MAX / 5)
+            .build()
+            .unwrap_err();
+        assert_eq!(err, Error::InvalidPage(i64::MAX / 5));
+
+        let err = ReservationQueryBuilder::default()
+            .page_size(5)
+            .build()
+            .unwrap_err();
+        assert_eq!(err, Error::InvalidPageSize(5));
     }
-}
 
-fn get_time_string(ts: Option<&Timestamp>, start: bool) -> String {
-    match ts {
-        Some(ts) => convert_to_utc_time(ts).to_rfc3339(),
-        None => (if start { "-infinity" } else { "infinity" }).into(),
+    #[test]
+    fn query_without_time_should_be_unbounded() {
+        let query = ReservationQueryBuilder::default().build().unwrap();
+        let timespan = query.get_timespan();
+        assert_eq!(timespan.start, Bound::Unbounded);
+        assert_eq!(timespan.end, Bound::Unbounded);
     }
 }
@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+
+use sqlx::{postgres::PgRow, types::Json, FromRow, Postgres, QueryBuilder, Row};
+
+use crate::{
+    AllocationStrategy, Error, ListResourcesRequest, Normalizer, Resource, ResourcePool, ToSql,
+    UpdateResourceRequest, Validator,
+};
+
+/// the max length of a resource id, as the column of the reservations
+const MAX_RESOURCE_ID_LEN: usize = 64;
+const DEFAULT_TIMEZONE: &str = "UTC";
+
+impl Resource {
+    pub fn new(id: impl Into<String>, r#type: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            r#type: r#type.into(),
+            active: true,
+            capacity: 1,
+            ..Default::default()
+        }
+    }
+
+    /// apply the changes of the request, the attributes are merged
+    pub fn update(&mut self, request: UpdateResourceRequest) {
+        if let Some(name) = request.name {
+            self.name = name;
+        }
+        if let Some(r#type) = request.r#type {
+            self.r#type = r#type;
+        }
+        if let Some(timezone) = request.timezone {
+            self.timezone = timezone;
+        }
+        if let Some(capacity) = request.capacity {
+            self.capacity = capacity;
+        }
+        if let Some(parent_id) = request.parent_id {
+            self.parent_id = parent_id;
+        }
+        for (key, value) in request.attributes {
+            if value.is_empty() {
+                self.attributes.remove(&key);
+            } else {
+                self.attributes.insert(key, value);
+            }
+        }
+    }
+}
+
+impl Validator for Resource {
+    fn validate(&self) -> Result<(), Error> {
+        if self.id.is_empty() || self.id.len() > MAX_RESOURCE_ID_LEN {
+            return Err(Error::InvalidResourceId(self.id.clone()));
+        }
+        if self.capacity < 0 {
+            return Err(Error::InvalidCapacity(self.capacity));
+        }
+        if self.parent_id == self.id {
+            return Err(Error::InvalidParent(self.parent_id.clone()));
+        }
+        Ok(())
+    }
+}
+
+impl Normalizer for Resource {
+    fn do_normalize(&mut self) {
+        if self.name.is_empty() {
+            self.name = self.id.clone();
+        }
+        if self.timezone.is_empty() {
+            self.timezone = DEFAULT_TIMEZONE.to_string();
+        }
+        if self.capacity == 0 {
+            self.capacity = 1;
+        }
+    }
+}
+
+impl FromRow<'_, PgRow> for Resource {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let attributes: Json<HashMap<String, String>> = row.try_get("attributes")?;
+        Ok(Self {
+            id: row.get("id"),
+            name: row.get("name"),
+            r#type: row.get("type"),
+            attributes: attributes.0,
+            active: row.get("active"),
+            timezone: row.get("timezone"),
+            capacity: row.get("capacity"),
+            parent_id: row
+                .get::<Option<String>, _>("parent_id")
+                .unwrap_or_default(),
+        })
+    }
+}
+
+impl Validator for UpdateResourceRequest {
+    fn validate(&self) -> Result<(), Error> {
+        if self.id.is_empty() {
+            return Err(Error::InvalidResourceId(self.id.clone()));
+        }
+        match self.capacity {
+            Some(capacity) if capacity <= 0 => return Err(Error::InvalidCapacity(capacity)),
+            _ => {}
+        }
+        match &self.parent_id {
+            // a resource couldn't be the parent of itself
+            Some(parent_id) if *parent_id == self.id => {
+                Err(Error::InvalidParent(parent_id.clone()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Validator for ResourcePool {
+    fn validate(&self) -> Result<(), Error> {
+        if self.r#type.is_empty() {
+            return Err(Error::InvalidPool("type is required".into()));
+        }
+        if AllocationStrategy::from_i32(self.strategy).is_none() {
+            return Err(Error::InvalidPool(format!(
+                "unknown strategy {}",
+                self.strategy
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl ToSql for ListResourcesRequest {
+    fn to_query(&self) -> QueryBuilder<'static, Postgres> {
+        let mut query = QueryBuilder::new("SELECT * FROM rsvp.resources WHERE TRUE");
+        if !self.r#type.is_empty() {
+            query.push(" AND type = ");
+            query.push_bind(self.r#type.clone());
+        }
+        if !self.include_inactive {
+            query.push(" AND active");
+        }
+        query.push(" ORDER BY id");
+        query
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resource_should_be_normalized() {
+        let mut resource = Resource::new("room-1", "room");
+        resource.normalize().unwrap();
+        assert_eq!(resource.name, "room-1");
+        assert_eq!(resource.timezone, "UTC");
+        assert_eq!(resource.capacity, 1);
+
+        resource.capacity = -1;
+        assert_eq!(resource.normalize(), Err(Error::InvalidCapacity(-1)));
+
+        let mut resource = Resource::new("", "room");
+        assert_eq!(
+            resource.normalize(),
+            Err(Error::InvalidResourceId("".into()))
+        );
+        resource.id = "r".repeat(65);
+        assert!(resource.normalize().is_err());
+
+        let mut resource = Resource {
+            parent_id: "room-1".into(),
+            ..Resource::new("room-1", "room")
+        };
+        assert_eq!(
+            resource.normalize(),
+            Err(Error::InvalidParent("room-1".into()))
+        );
+    }
+
+    #[test]
+    fn resource_update_should_merge_attributes() {
+        let mut resource = Resource {
+            name: "Room 1".into(),
+            attributes: HashMap::from([
+                ("seats".to_string(), "8".to_string()),
+                ("floor".to_string(), "3".to_string()),
+            ]),
+            ..Resource::new("room-1", "room")
+        };
+        resource.update(UpdateResourceRequest {
+            id: "room-1".into(),
+            timezone: Some("Asia/Shanghai".into()),
+            attributes: HashMap::from([
+                ("seats".to_string(), "10".to_string()),
+                ("floor".to_string(), "".to_string()),
+                ("tv".to_string(), "yes".to_string()),
+            ]),
+            parent_id: Some("floor-1".into()),
+            ..Default::default()
+        });
+        assert_eq!(resource.name, "Room 1");
+        assert_eq!(resource.parent_id, "floor-1");
+        assert_eq!(resource.r#type, "room");
+        assert_eq!(resource.timezone, "Asia/Shanghai");
+        assert_eq!(
+            resource.attributes,
+            HashMap::from([
+                ("seats".to_string(), "10".to_string()),
+                ("tv".to_string(), "yes".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn resource_pool_should_be_validated() {
+        let pool = ResourcePool {
+            r#type: "room".into(),
+            strategy: AllocationStrategy::BestFit as i32,
+        };
+        assert!(pool.validate().is_ok());
+
+        let err = ResourcePool::default().validate().unwrap_err();
+        assert_eq!(err, Error::InvalidPool("type is required".into()));
+        let err = ResourcePool {
+            strategy: 5,
+            ..pool
+        }
+        .validate()
+        .unwrap_err();
+        assert_eq!(err, Error::InvalidPool("unknown strategy 5".into()));
+    }
+
+    #[test]
+    fn list_resources_should_generate_correct_sql() {
+        let query = ListResourcesRequest::default().to_query();
+        assert_eq!(
+            query.sql(),
+            "SELECT * FROM rsvp.resources WHERE TRUE AND active ORDER BY id"
+        );
+
+        let query = ListResourcesRequest {
+            r#type: "room".into(),
+            include_inactive: true,
+        }
+        .to_query();
+        assert_eq!(
+            query.sql(),
+            "SELECT * FROM rsvp.resources WHERE TRUE AND type = $1 ORDER BY id"
+        );
+    }
+}
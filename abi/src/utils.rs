@@ -1,12 +1,9 @@
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, Utc};
 use prost_types::Timestamp;
 
 pub fn convert_to_utc_time(ts: &Timestamp) -> DateTime<Utc> {
-    DateTime::<Utc>::from_utc(
-        NaiveDateTime::from_timestamp_opt(ts.seconds, ts.nanos as _)
-            .expect("invalid or out-of-range datetime"),
-        Utc,
-    )
+    DateTime::<Utc>::from_timestamp(ts.seconds, ts.nanos as _)
+        .expect("invalid or out-of-range datetime")
 }
 
 pub fn convert_to_timestamp(dt: &DateTime<Utc>) -> Timestamp {
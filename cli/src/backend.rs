@@ -0,0 +1,134 @@
+use abi::{
+    AckRequest, Error, ListenRequest, ListenResponse, Reservation, ReservationFilter,
+    ReservationId, ReservationQuery,
+};
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
+use reservation::{ReservationManager, Rsvp};
+use reservation_client::ReservationClient;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// where the commands are sent to, the server or the database
+#[derive(Debug)]
+pub enum Backend {
+    Grpc(Box<ReservationClient>),
+    Db(ReservationManager),
+}
+
+impl Backend {
+    pub async fn reserve(&self, rsvp: Reservation) -> Result<Reservation, Error> {
+        match self {
+            Backend::Grpc(client) => client.reserve(rsvp).await,
+            Backend::Db(manager) => manager.reserve(rsvp).await,
+        }
+    }
+
+    /// test the reservation against the existing ones without making it
+    pub async fn check(&self, rsvp: Reservation) -> Result<Reservation, Error> {
+        match self {
+            Backend::Grpc(client) => client.check(rsvp).await,
+            Backend::Db(manager) => manager.check(rsvp).await,
+        }
+    }
+
+    pub async fn confirm(&self, id: ReservationId) -> Result<Reservation, Error> {
+        match self {
+            Backend::Grpc(client) => client.confirm(id).await,
+            Backend::Db(manager) => manager.change_status(id).await,
+        }
+    }
+
+    pub async fn cancel(&self, id: ReservationId) -> Result<Reservation, Error> {
+        match self {
+            Backend::Grpc(client) => client.cancel(id).await,
+            Backend::Db(manager) => manager.delete(id).await,
+        }
+    }
+
+    pub async fn get(&self, id: ReservationId) -> Result<Reservation, Error> {
+        match self {
+            Backend::Grpc(client) => client.get(id).await,
+            Backend::Db(manager) => manager.get(id).await,
+        }
+    }
+
+    pub async fn query(&self, query: ReservationQuery) -> Result<Vec<Reservation>, Error> {
+        match self {
+            Backend::Grpc(client) => {
+                let (_, stream) = client.query(query).await?;
+                stream.try_collect().await
+            }
+            Backend::Db(manager) => manager.query(query).await,
+        }
+    }
+
+    /// all the reservations matching the filter, the pages are followed till the last one
+    pub async fn filter(&self, filter: ReservationFilter) -> Result<Vec<Reservation>, Error> {
+        match self {
+            Backend::Grpc(client) => client.filter(filter).try_collect().await,
+            Backend::Db(manager) => {
+                let mut filter = Some(filter);
+                let mut reservations = vec![];
+                while let Some(current) = filter {
+                    let (pager, page) = manager.filter(current.clone()).await?;
+                    reservations.extend(page);
+                    filter = current.next_page(&pager);
+                }
+                Ok(reservations)
+            }
+        }
+    }
+
+    pub async fn listen(
+        &self,
+        request: ListenRequest,
+    ) -> Result<BoxStream<'static, Result<ListenResponse, Error>>, Error> {
+        match self {
+            Backend::Grpc(client) => Ok(client.listen(request).await?.boxed()),
+            Backend::Db(manager) => Ok(ReceiverStream::new(manager.listen(request).await?).boxed()),
+        }
+    }
+
+    pub async fn ack(&self, consumer: String, id: abi::ChangeId) -> Result<(), Error> {
+        match self {
+            Backend::Grpc(client) => client.ack(consumer, id).await,
+            Backend::Db(manager) => manager.ack(AckRequest { consumer, id }).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use abi::{ReservationFilterBuilder, ReservationStatus};
+    use reservation_service::test_utils::seed_resources;
+
+    use super::*;
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn db_backend_should_manage_reservations() {
+        seed_resources(&migrate_pool).await;
+        let backend = Backend::Db(ReservationManager::new(migrate_pool.clone()));
+        for i in 0..15 {
+            let rsvp = Reservation::new_pending(
+                "chalanziId",
+                format!("room-{}", i),
+                "2022-12-25T15:00:00-0700".parse().unwrap(),
+                "2022-12-28T12:00:00-0700".parse().unwrap(),
+                "",
+            );
+            backend.reserve(rsvp).await.unwrap();
+        }
+
+        let rsvp = backend.confirm(1).await.unwrap();
+        assert_eq!(rsvp.status, ReservationStatus::Confirmed as i32);
+        assert_eq!(backend.get(1).await.unwrap(), rsvp);
+        assert_eq!(backend.cancel(1).await.unwrap(), rsvp);
+
+        // the filter is followed to the second page
+        let filter = ReservationFilterBuilder::default()
+            .user_id("chalanziId")
+            .build()
+            .unwrap();
+        let rsvps = backend.filter(filter).await.unwrap();
+        assert_eq!(rsvps.len(), 14);
+    }
+}
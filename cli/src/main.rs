@@ -0,0 +1,336 @@
+mod backend;
+mod output;
+
+use std::path::PathBuf;
+
+use abi::{
+    Config, ListenRequest, Reservation, ReservationFilterBuilder, ReservationMatchMode,
+    ReservationQueryBuilder, ReservationStatus,
+};
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use futures::StreamExt;
+use reservation::ReservationManager;
+use reservation_client::{ClientConfig, ReservationClient};
+
+use backend::Backend;
+use output::{ChangeRow, Format, Printer, ReservationRow};
+
+const DEFAULT_SERVER: &str = "http://localhost:50051";
+
+/// manage the reservations through the reservation service, or the database directly
+#[derive(Debug, Parser)]
+#[command(name = "rsvp", version)]
+struct Cli {
+    /// config file of the service, to find the server or the database
+    #[arg(short, long, env = "RESERVATION_CONFIG")]
+    config: Option<PathBuf>,
+    /// url of the server, e.g. http://localhost:50051. Default to the server of the config
+    #[arg(short, long)]
+    server: Option<String>,
+    /// connect to the database of the config instead of the server
+    #[arg(long)]
+    db: bool,
+    #[arg(short, long, value_enum, default_value_t = Format::Table)]
+    format: Format,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// make a pending reservation
+    Reserve {
+        #[arg(long)]
+        user: String,
+        #[arg(long)]
+        resource: String,
+        /// start time in rfc3339, e.g. 2022-12-25T15:00:00-07:00
+        #[arg(long)]
+        start: DateTime<FixedOffset>,
+        /// end time in rfc3339
+        #[arg(long)]
+        end: DateTime<FixedOffset>,
+        #[arg(long, default_value = "")]
+        note: String,
+        /// units of a shared resource, e.g. the seats of a class
+        #[arg(long, default_value_t = 1)]
+        quantity: i32,
+        /// only check the reservation against the existing ones, it's not made
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// confirm a pending reservation
+    Confirm { id: i64 },
+    /// cancel a reservation
+    Cancel { id: i64 },
+    /// get a reservation by id
+    Get { id: i64 },
+    /// query the reservations in a time window
+    Query(QueryArgs),
+    /// filter the reservations order by id, all the pages are printed
+    Filter(FilterArgs),
+    /// print the changes of reservations as they happen
+    Watch {
+        /// replay the changes after this change id
+        #[arg(long)]
+        since: Option<i64>,
+        /// durable consumer name, resume from its last acknowledged change
+        #[arg(long, default_value = "")]
+        consumer: String,
+        /// acknowledge each change of the consumer once it is printed
+        #[arg(long, requires = "consumer")]
+        ack: bool,
+    },
+}
+
+#[derive(Debug, Args)]
+struct QueryArgs {
+    #[arg(long, default_value = "")]
+    user: String,
+    #[arg(long, default_value = "")]
+    resource: String,
+    /// start of the window in rfc3339, unbounded if not set
+    #[arg(long)]
+    start: Option<DateTime<FixedOffset>>,
+    /// end of the window in rfc3339, unbounded if not set
+    #[arg(long)]
+    end: Option<DateTime<FixedOffset>>,
+    /// match any of the statuses, all statuses if not set
+    #[arg(long, value_enum)]
+    status: Vec<StatusArg>,
+    #[arg(long, value_enum, default_value_t = MatchModeArg::Contained)]
+    match_mode: MatchModeArg,
+    #[arg(long, default_value_t = 1)]
+    page: i64,
+    /// 0 means all the reservations in one page
+    #[arg(long, default_value_t = 0)]
+    page_size: i64,
+    #[arg(long)]
+    desc: bool,
+}
+
+#[derive(Debug, Args)]
+struct FilterArgs {
+    #[arg(long, default_value = "")]
+    user: String,
+    #[arg(long, default_value = "")]
+    resource: String,
+    /// match any of the statuses, all statuses if not set
+    #[arg(long, value_enum)]
+    status: Vec<StatusArg>,
+    /// reservations fetched per request
+    #[arg(long, default_value_t = 100)]
+    page_size: i64,
+    #[arg(long)]
+    desc: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum StatusArg {
+    Pending,
+    Confirmed,
+    Blocked,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum MatchModeArg {
+    Contained,
+    Overlapping,
+    StartingWithin,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let backend = connect(&cli).await?;
+    let mut printer = Printer::new(cli.format, std::io::stdout());
+
+    match cli.command {
+        Command::Reserve {
+            user,
+            resource,
+            start,
+            end,
+            note,
+            quantity,
+            dry_run,
+        } => {
+            let rsvp = Reservation {
+                quantity,
+                ..Reservation::new_pending(user, resource, start, end, note)
+            };
+            let rsvp = if dry_run {
+                backend.check(rsvp).await?
+            } else {
+                backend.reserve(rsvp).await?
+            };
+            printer.print(&[ReservationRow::from(&rsvp)])?;
+        }
+        Command::Confirm { id } => {
+            let rsvp = backend.confirm(id).await?;
+            printer.print(&[ReservationRow::from(&rsvp)])?;
+        }
+        Command::Cancel { id } => {
+            let rsvp = backend.cancel(id).await?;
+            printer.print(&[ReservationRow::from(&rsvp)])?;
+        }
+        Command::Get { id } => {
+            let rsvp = backend.get(id).await?;
+            printer.print(&[ReservationRow::from(&rsvp)])?;
+        }
+        Command::Query(args) => {
+            let mut builder = ReservationQueryBuilder::default();
+            builder
+                .user_id(args.user)
+                .resource_id(args.resource)
+                .statuses(statuses(&args.status))
+                .match_mode(ReservationMatchMode::from(args.match_mode) as i32)
+                .page(args.page)
+                .page_size(args.page_size)
+                .desc(args.desc);
+            if let Some(start) = args.start {
+                builder.start(to_timestamp(start));
+            }
+            if let Some(end) = args.end {
+                builder.end(to_timestamp(end));
+            }
+            let rsvps = backend.query(builder.build()?).await?;
+            printer.print(&rsvps.iter().map(ReservationRow::from).collect::<Vec<_>>())?;
+        }
+        Command::Filter(args) => {
+            let filter = ReservationFilterBuilder::default()
+                .user_id(args.user)
+                .resource_id(args.resource)
+                .statuses(statuses(&args.status))
+                .page_size(args.page_size)
+                .desc(args.desc)
+                .build()?;
+            let rsvps = backend.filter(filter).await?;
+            printer.print(&rsvps.iter().map(ReservationRow::from).collect::<Vec<_>>())?;
+        }
+        Command::Watch {
+            since,
+            consumer,
+            ack,
+        } => {
+            let request = ListenRequest {
+                since,
+                consumer: consumer.clone(),
+            };
+            let mut changes = backend.listen(request).await?;
+            let mut printer = Printer::streaming(cli.format, std::io::stdout());
+            while let Some(change) = changes.next().await {
+                let change = change?;
+                printer.print(&[ChangeRow::from(&change)])?;
+                if ack {
+                    backend.ack(consumer.clone(), change.id).await?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// connect to the database with --db, otherwise to the server
+async fn connect(cli: &Cli) -> Result<Backend> {
+    let config = cli.config.as_ref().map(Config::load).transpose()?;
+    if cli.db {
+        let config = config.context("--db needs the config of the database, set it by --config")?;
+        let manager = ReservationManager::from_config(&config.db).await?;
+        return Ok(Backend::Db(manager));
+    }
+
+    let url = match (&cli.server, &config) {
+        (Some(url), _) => url.clone(),
+        (None, Some(config)) => config.server.url(false),
+        (None, None) => DEFAULT_SERVER.to_string(),
+    };
+    let client = ReservationClient::connect(ClientConfig::new(url.clone()))
+        .await
+        .with_context(|| format!("failed to connect to {}", url))?;
+    Ok(Backend::Grpc(Box::new(client)))
+}
+
+fn statuses(status: &[StatusArg]) -> Vec<i32> {
+    status
+        .iter()
+        .map(|s| ReservationStatus::from(*s) as i32)
+        .collect()
+}
+
+fn to_timestamp(dt: DateTime<FixedOffset>) -> prost_types::Timestamp {
+    abi::convert_to_timestamp(&dt.with_timezone(&Utc))
+}
+
+impl From<StatusArg> for ReservationStatus {
+    fn from(status: StatusArg) -> Self {
+        match status {
+            StatusArg::Pending => ReservationStatus::Pending,
+            StatusArg::Confirmed => ReservationStatus::Confirmed,
+            StatusArg::Blocked => ReservationStatus::Blocked,
+        }
+    }
+}
+
+impl From<MatchModeArg> for ReservationMatchMode {
+    fn from(mode: MatchModeArg) -> Self {
+        match mode {
+            MatchModeArg::Contained => ReservationMatchMode::Contained,
+            MatchModeArg::Overlapping => ReservationMatchMode::Overlapping,
+            MatchModeArg::StartingWithin => ReservationMatchMode::StartingWithin,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn cli_should_be_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn cli_should_parse_query() {
+        let cli = Cli::try_parse_from([
+            "rsvp",
+            "--format",
+            "csv",
+            "query",
+            "--user",
+            "chalanziId",
+            "--start",
+            "2022-12-25T15:00:00-07:00",
+            "--status",
+            "pending",
+            "--status",
+            "confirmed",
+            "--match-mode",
+            "overlapping",
+        ])
+        .unwrap();
+        assert_eq!(cli.format, Format::Csv);
+        match cli.command {
+            Command::Query(args) => {
+                assert_eq!(args.user, "chalanziId");
+                assert_eq!(
+                    args.start.unwrap().to_rfc3339(),
+                    "2022-12-25T15:00:00-07:00"
+                );
+                assert_eq!(args.end, None);
+                assert_eq!(statuses(&args.status), vec![1, 2]);
+                assert_eq!(args.match_mode, MatchModeArg::Overlapping);
+            }
+            command => panic!("unexpected command: {:?}", command),
+        }
+
+        // ack needs a durable consumer
+        assert!(Cli::try_parse_from(["rsvp", "watch", "--ack"]).is_err());
+    }
+}
@@ -0,0 +1,298 @@
+use std::io::Write;
+
+use abi::{convert_to_utc_time, ListenResponse, Reservation, ReservationStatus};
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// aligned columns
+    Table,
+    /// one json object per line
+    Json,
+    Csv,
+}
+
+/// a row of the output
+pub trait Row: Serialize {
+    fn header() -> Vec<&'static str>;
+    fn values(&self) -> Vec<String>;
+    /// the column widths of a stream of rows, which couldn't be measured ahead. A longer value
+    /// pushes the following columns of its line
+    fn widths() -> Vec<usize>;
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReservationRow {
+    id: i64,
+    user_id: String,
+    resource_id: String,
+    status: String,
+    start: String,
+    end: String,
+    note: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangeRow {
+    change_id: i64,
+    op: String,
+    reservation: Option<ReservationRow>,
+}
+
+/// print the rows in the format, the header of table and csv is only printed once so that the
+/// rows of a stream could be printed one by one
+pub struct Printer<W: Write> {
+    format: Format,
+    writer: W,
+    header_printed: bool,
+    /// the table columns are of the fixed widths of the row, so that the lines printed one by one
+    /// are aligned
+    streaming: bool,
+}
+
+impl<W: Write> Printer<W> {
+    pub fn new(format: Format, writer: W) -> Self {
+        Self {
+            format,
+            writer,
+            header_printed: false,
+            streaming: false,
+        }
+    }
+
+    pub fn streaming(format: Format, writer: W) -> Self {
+        Self {
+            streaming: true,
+            ..Self::new(format, writer)
+        }
+    }
+
+    pub fn print<R: Row>(&mut self, rows: &[R]) -> Result<()> {
+        match self.format {
+            Format::Table => self.print_table(rows)?,
+            Format::Json => {
+                for row in rows {
+                    serde_json::to_writer(&mut self.writer, row)?;
+                    writeln!(self.writer)?;
+                }
+            }
+            Format::Csv => {
+                let mut writer = csv::WriterBuilder::new()
+                    .has_headers(false)
+                    .from_writer(&mut self.writer);
+                if !self.header_printed {
+                    writer.write_record(R::header())?;
+                }
+                for row in rows {
+                    writer.write_record(row.values())?;
+                }
+                writer.flush()?;
+            }
+        }
+        self.header_printed = true;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn print_table<R: Row>(&mut self, rows: &[R]) -> Result<()> {
+        let header: Vec<String> = R::header().into_iter().map(|h| h.to_uppercase()).collect();
+        let rows: Vec<Vec<String>> = rows.iter().map(|row| row.values()).collect();
+        let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+        if self.streaming {
+            for (width, fixed) in widths.iter_mut().zip(R::widths()) {
+                *width = (*width).max(fixed);
+            }
+        } else {
+            for row in &rows {
+                for (width, value) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(value.chars().count());
+                }
+            }
+        }
+
+        if !self.header_printed {
+            self.print_line(&header, &widths)?;
+        }
+        for row in &rows {
+            self.print_line(row, &widths)?;
+        }
+        Ok(())
+    }
+
+    fn print_line(&mut self, values: &[String], widths: &[usize]) -> Result<()> {
+        let line: Vec<String> = values
+            .iter()
+            .zip(widths)
+            .map(|(value, width)| format!("{:width$}", value, width = width))
+            .collect();
+        writeln!(self.writer, "{}", line.join("  ").trim_end())?;
+        Ok(())
+    }
+}
+
+impl From<&Reservation> for ReservationRow {
+    fn from(rsvp: &Reservation) -> Self {
+        let time = |ts: Option<&prost_types::Timestamp>| {
+            ts.map(|ts| convert_to_utc_time(ts).to_rfc3339())
+                .unwrap_or_default()
+        };
+        let status = ReservationStatus::from_i32(rsvp.status).unwrap_or(ReservationStatus::Unknown);
+        Self {
+            id: rsvp.id,
+            user_id: rsvp.user_id.clone(),
+            resource_id: rsvp.resource_id.clone(),
+            status: status.to_string(),
+            start: time(rsvp.start.as_ref()),
+            end: time(rsvp.end.as_ref()),
+            note: rsvp.note.clone(),
+        }
+    }
+}
+
+impl Row for ReservationRow {
+    fn header() -> Vec<&'static str> {
+        vec![
+            "id",
+            "user_id",
+            "resource_id",
+            "status",
+            "start",
+            "end",
+            "note",
+        ]
+    }
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.user_id.clone(),
+            self.resource_id.clone(),
+            self.status.clone(),
+            self.start.clone(),
+            self.end.clone(),
+            self.note.clone(),
+        ]
+    }
+
+    fn widths() -> Vec<usize> {
+        // the time is printed in rfc3339 in utc, e.g. 2022-12-25T22:00:00+00:00
+        vec![8, 12, 20, 9, 25, 25, 0]
+    }
+}
+
+impl From<&ListenResponse> for ChangeRow {
+    fn from(change: &ListenResponse) -> Self {
+        let op = abi::ReservationUpdateType::from_i32(change.op)
+            .unwrap_or(abi::ReservationUpdateType::Unknown);
+        Self {
+            change_id: change.id,
+            op: format!("{:?}", op).to_lowercase(),
+            reservation: change.reservation.as_ref().map(ReservationRow::from),
+        }
+    }
+}
+
+impl Row for ChangeRow {
+    fn header() -> Vec<&'static str> {
+        let mut header = vec!["change_id", "op"];
+        header.extend(ReservationRow::header());
+        header
+    }
+
+    fn values(&self) -> Vec<String> {
+        let mut values = vec![self.change_id.to_string(), self.op.clone()];
+        match &self.reservation {
+            Some(rsvp) => values.extend(rsvp.values()),
+            None => values.extend(ReservationRow::header().iter().map(|_| String::new())),
+        }
+        values
+    }
+
+    fn widths() -> Vec<usize> {
+        let mut widths = vec![9, 6];
+        widths.extend(ReservationRow::widths());
+        widths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<ReservationRow> {
+        let mut rsvp = Reservation::new_pending(
+            "chalanziId",
+            "ocean-view-room-713",
+            "2022-12-25T15:00:00-0700".parse().unwrap(),
+            "2022-12-28T12:00:00-0700".parse().unwrap(),
+            "hello, world",
+        );
+        rsvp.id = 1;
+        vec![ReservationRow::from(&rsvp)]
+    }
+
+    fn print(format: Format) -> String {
+        let mut buf = vec![];
+        let mut printer = Printer::new(format, &mut buf);
+        printer.print(&rows()).unwrap();
+        // the header is printed only once
+        printer.print(&rows()).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn table_should_be_aligned() {
+        assert_eq!(
+            print(Format::Table),
+            "ID  USER_ID     RESOURCE_ID          STATUS   START                      END                        NOTE\n\
+             1   chalanziId  ocean-view-room-713  pending  2022-12-25T22:00:00+00:00  2022-12-28T19:00:00+00:00  hello, world\n\
+             1   chalanziId  ocean-view-room-713  pending  2022-12-25T22:00:00+00:00  2022-12-28T19:00:00+00:00  hello, world\n"
+        );
+    }
+
+    #[test]
+    fn streamed_table_should_be_of_fixed_widths() {
+        let mut buf = vec![];
+        let mut printer = Printer::streaming(Format::Table, &mut buf);
+        let mut rows = rows();
+        printer.print(&rows).unwrap();
+        rows[0].user_id = "u".into();
+        printer.print(&rows).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "ID        USER_ID       RESOURCE_ID           STATUS     START                      END                        NOTE\n\
+             1         chalanziId    ocean-view-room-713   pending    2022-12-25T22:00:00+00:00  2022-12-28T19:00:00+00:00  hello, world\n\
+             1         u             ocean-view-room-713   pending    2022-12-25T22:00:00+00:00  2022-12-28T19:00:00+00:00  hello, world\n"
+        );
+    }
+
+    #[test]
+    fn csv_should_quote_values() {
+        assert_eq!(
+            print(Format::Csv),
+            "id,user_id,resource_id,status,start,end,note\n\
+             1,chalanziId,ocean-view-room-713,pending,2022-12-25T22:00:00+00:00,2022-12-28T19:00:00+00:00,\"hello, world\"\n\
+             1,chalanziId,ocean-view-room-713,pending,2022-12-25T22:00:00+00:00,2022-12-28T19:00:00+00:00,\"hello, world\"\n"
+        );
+    }
+
+    #[test]
+    fn json_should_be_printed_per_line() {
+        let line = r#"{"id":1,"user_id":"chalanziId","resource_id":"ocean-view-room-713","status":"pending","start":"2022-12-25T22:00:00+00:00","end":"2022-12-28T19:00:00+00:00","note":"hello, world"}"#;
+        assert_eq!(print(Format::Json), format!("{}\n{}\n", line, line));
+    }
+
+    #[test]
+    fn change_row_should_include_reservation() {
+        let change = ListenResponse {
+            op: abi::ReservationUpdateType::Delete as i32,
+            reservation: None,
+            id: 3,
+        };
+        let row = ChangeRow::from(&change);
+        assert_eq!(row.values()[..2], ["3", "delete"]);
+        assert_eq!(row.values().len(), ChangeRow::header().len());
+    }
+}
@@ -0,0 +1,721 @@
+use std::future::Future;
+
+use abi::{
+    reservation_service_client::ReservationServiceClient,
+    resource_service_client::ResourceServiceClient, AckRequest, AvailabilityQuery,
+    AvailabilityRequest, CancelRequest, CancelSeriesRequest, ConfirmRequest, CreateResourceRequest,
+    DeactivateResourceRequest, Error, FilterPager, FilterRequest, FilterResponse, FreeSlot,
+    GetRequest, ListResourcesRequest, ListenRequest, ListenResponse, QueryRequest, RecurrenceRule,
+    Reservation, ReservationFilter, ReservationQuery, ReservationRequest, ReserveBatchRequest,
+    ReserveSeriesRequest, Resource, ResourcePool, UpdateRequest, UpdateResourceRequest,
+    UpdateSeriesRequest,
+};
+use futures::{stream, Stream, StreamExt, TryStreamExt};
+use tonic::{
+    transport::{Channel, Endpoint},
+    Code, Request, Response, Status,
+};
+
+use crate::{ClientConfig, ReservationClient};
+
+impl ReservationClient {
+    pub async fn connect(config: ClientConfig) -> Result<Self, tonic::transport::Error> {
+        let mut endpoint = Endpoint::from_shared(config.url.clone())?;
+        if let Some(timeout) = config.timeout {
+            endpoint = endpoint.connect_timeout(timeout);
+        }
+        let channel = endpoint.connect().await?;
+        Ok(Self {
+            inner: ReservationServiceClient::new(channel.clone()),
+            resources: ResourceServiceClient::new(channel),
+            config,
+        })
+    }
+
+    /// make a reservation
+    pub async fn reserve(&self, reservation: Reservation) -> Result<Reservation, Error> {
+        self.send_reservation(ReservationRequest {
+            reservation: Some(reservation),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// make a reservation, if it conflicts the free windows of the same length on its resource and
+    /// the free alternative resources are suggested in the conflict
+    pub async fn reserve_with_suggestions(
+        &self,
+        reservation: Reservation,
+        alternatives: Vec<String>,
+    ) -> Result<Reservation, Error> {
+        self.send_reservation(ReservationRequest {
+            reservation: Some(reservation),
+            suggest: true,
+            alternative_resource_ids: alternatives,
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// reserve a free resource of the pool chosen by the server, the resource_id of the reservation
+    /// must be empty. The returned reservation carries the chosen resource
+    pub async fn reserve_in_pool(
+        &self,
+        reservation: Reservation,
+        pool: ResourcePool,
+    ) -> Result<Reservation, Error> {
+        self.send_reservation(ReservationRequest {
+            reservation: Some(reservation),
+            pool: Some(pool),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// make the reservations in one transaction, either all of them or none
+    pub async fn reserve_batch(
+        &self,
+        reservations: Vec<Reservation>,
+    ) -> Result<Vec<Reservation>, Error> {
+        let response = self
+            .call(false, |mut client| {
+                let request = self.request(ReserveBatchRequest {
+                    reservations: reservations.clone(),
+                });
+                async move { client.reserve_batch(request).await }
+            })
+            .await?;
+        Ok(response.into_inner().reservations)
+    }
+
+    /// make the occurrences of a recurring reservation, either all of them or none
+    pub async fn reserve_series(
+        &self,
+        reservation: Reservation,
+        rule: RecurrenceRule,
+    ) -> Result<Vec<Reservation>, Error> {
+        let response = self
+            .call(false, |mut client| {
+                let request = self.request(ReserveSeriesRequest {
+                    reservation: Some(reservation.clone()),
+                    rule: Some(rule.clone()),
+                });
+                async move { client.reserve_series(request).await }
+            })
+            .await?;
+        Ok(response.into_inner().reservations)
+    }
+
+    /// change the note and/or move the occurrences of a series in the scope
+    pub async fn update_series(
+        &self,
+        request: UpdateSeriesRequest,
+    ) -> Result<Vec<Reservation>, Error> {
+        // the new times are absolute, so it could be retried
+        let response = self
+            .call(true, |mut client| {
+                let request = self.request(request.clone());
+                async move { client.update_series(request).await }
+            })
+            .await?;
+        Ok(response.into_inner().reservations)
+    }
+
+    /// cancel the occurrences of a series in the scope, return the canceled ones
+    pub async fn cancel_series(
+        &self,
+        id: abi::ReservationId,
+        scope: abi::SeriesScope,
+    ) -> Result<Vec<Reservation>, Error> {
+        let response = self
+            .call(false, |mut client| {
+                let request = self.request(CancelSeriesRequest {
+                    id,
+                    scope: scope as i32,
+                });
+                async move { client.cancel_series(request).await }
+            })
+            .await?;
+        Ok(response.into_inner().reservations)
+    }
+
+    /// validate the reservation and test it against the existing ones without making it
+    pub async fn check(&self, reservation: Reservation) -> Result<Reservation, Error> {
+        self.send_reservation(ReservationRequest {
+            reservation: Some(reservation),
+            dry_run: true,
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// confirm a pending reservation
+    pub async fn confirm(&self, id: abi::ReservationId) -> Result<Reservation, Error> {
+        let response = self
+            .call(false, |mut client| {
+                let request = self.request(ConfirmRequest { id });
+                async move { client.confirm(request).await }
+            })
+            .await?;
+        response.into_inner().reservation.ok_or(Error::Unknown)
+    }
+
+    /// update the note of a reservation
+    pub async fn update(
+        &self,
+        id: abi::ReservationId,
+        note: impl Into<String>,
+    ) -> Result<Reservation, Error> {
+        let note = note.into();
+        let response = self
+            .call(true, |mut client| {
+                let request = self.request(UpdateRequest {
+                    id,
+                    note: note.clone(),
+                });
+                async move { client.update(request).await }
+            })
+            .await?;
+        response.into_inner().reservation.ok_or(Error::Unknown)
+    }
+
+    /// cancel a reservation, return the canceled one
+    pub async fn cancel(&self, id: abi::ReservationId) -> Result<Reservation, Error> {
+        let response = self
+            .call(false, |mut client| {
+                let request = self.request(CancelRequest { id });
+                async move { client.cancel(request).await }
+            })
+            .await?;
+        response.into_inner().reservation.ok_or(Error::Unknown)
+    }
+
+    /// get a reservation by id
+    pub async fn get(&self, id: abi::ReservationId) -> Result<Reservation, Error> {
+        let response = self
+            .call(true, |mut client| {
+                let request = self.request(GetRequest { id });
+                async move { client.get(request).await }
+            })
+            .await?;
+        response.into_inner().reservation.ok_or(Error::Unknown)
+    }
+
+    /// stream the reservations matching the query, the pager is only filled for a paged query
+    pub async fn query(
+        &self,
+        query: ReservationQuery,
+    ) -> Result<(FilterPager, impl Stream<Item = Result<Reservation, Error>>), Error> {
+        let response = self
+            .call(true, |mut client| {
+                let request = self.request(QueryRequest {
+                    query: Some(query.clone()),
+                });
+                async move { client.query(request).await }
+            })
+            .await?;
+        let pager = FilterPager::from_metadata(response.metadata());
+        let stream = response.into_inner().map(|item| item.map_err(Error::from));
+        Ok((pager, stream))
+    }
+
+    /// get a page of the filter. page_token is the prev or next page token of the last page,
+    /// empty for the first page
+    pub async fn filter_page(
+        &self,
+        filter: ReservationFilter,
+        page_token: impl Into<String>,
+    ) -> Result<FilterResponse, Error> {
+        let page_token = page_token.into();
+        let response = self
+            .call(true, |mut client| {
+                let request = self.request(FilterRequest {
+                    filter: Some(filter.clone()),
+                    page_token: page_token.clone(),
+                });
+                async move { client.filter(request).await }
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// stream all the reservations matching the filter, the next pages are fetched as the
+    /// stream is consumed
+    pub fn filter(
+        &self,
+        filter: ReservationFilter,
+    ) -> impl Stream<Item = Result<Reservation, Error>> {
+        let client = self.clone();
+        // None means there is no more page
+        stream::try_unfold(Some(String::new()), move |page_token| {
+            let client = client.clone();
+            let filter = filter.clone();
+            async move {
+                let page_token = match page_token {
+                    Some(page_token) => page_token,
+                    None => return Ok::<_, Error>(None),
+                };
+                let page = client.filter_page(filter, page_token).await?;
+                let next = if page.next_page_token.is_empty() {
+                    None
+                } else {
+                    Some(page.next_page_token)
+                };
+                let reservations = stream::iter(page.reservations.into_iter().map(Ok));
+                Ok(Some((reservations, next)))
+            }
+        })
+        .try_flatten()
+    }
+
+    /// stream the changes of reservations
+    pub async fn listen(
+        &self,
+        request: ListenRequest,
+    ) -> Result<impl Stream<Item = Result<ListenResponse, Error>>, Error> {
+        let response = self
+            .call(true, |mut client| {
+                // the stream lives until the client drops it, no deadline for it
+                let request = Request::new(request.clone());
+                async move { client.listen(request).await }
+            })
+            .await?;
+        Ok(response.into_inner().map(|item| item.map_err(Error::from)))
+    }
+
+    /// acknowledge the changes processed by a durable consumer
+    pub async fn ack(&self, consumer: impl Into<String>, id: abi::ChangeId) -> Result<(), Error> {
+        let consumer = consumer.into();
+        self.call(true, |mut client| {
+            let request = self.request(AckRequest {
+                consumer: consumer.clone(),
+                id,
+            });
+            async move { client.ack(request).await }
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// the free slots of the resources in the window
+    pub async fn availability(&self, query: AvailabilityQuery) -> Result<Vec<FreeSlot>, Error> {
+        let response = self
+            .call(true, |mut client| {
+                let request = self.request(AvailabilityRequest {
+                    query: Some(query.clone()),
+                });
+                async move { client.availability(request).await }
+            })
+            .await?;
+        Ok(response.into_inner().slots)
+    }
+
+    /// add a resource to the catalog
+    pub async fn create_resource(&self, resource: Resource) -> Result<Resource, Error> {
+        let response = self
+            .call_resources(false, |mut client| {
+                let request = self.request(CreateResourceRequest {
+                    resource: Some(resource.clone()),
+                });
+                async move { client.create(request).await }
+            })
+            .await?;
+        response.into_inner().resource.ok_or(Error::Unknown)
+    }
+
+    /// change a resource, the attributes are merged
+    pub async fn update_resource(&self, request: UpdateResourceRequest) -> Result<Resource, Error> {
+        let response = self
+            .call_resources(true, |mut client| {
+                let request = self.request(request.clone());
+                async move { client.update(request).await }
+            })
+            .await?;
+        response.into_inner().resource.ok_or(Error::Unknown)
+    }
+
+    /// stop the resource from being reserved, its reservations are kept
+    pub async fn deactivate_resource(&self, id: impl Into<String>) -> Result<Resource, Error> {
+        let id = id.into();
+        let response = self
+            .call_resources(true, |mut client| {
+                let request = self.request(DeactivateResourceRequest { id: id.clone() });
+                async move { client.deactivate(request).await }
+            })
+            .await?;
+        response.into_inner().resource.ok_or(Error::Unknown)
+    }
+
+    /// list the resources order by id
+    pub async fn list_resources(
+        &self,
+        request: ListResourcesRequest,
+    ) -> Result<Vec<Resource>, Error> {
+        let response = self
+            .call_resources(true, |mut client| {
+                let request = self.request(request.clone());
+                async move { client.list(request).await }
+            })
+            .await?;
+        Ok(response.into_inner().resources)
+    }
+
+    async fn send_reservation(&self, request: ReservationRequest) -> Result<Reservation, Error> {
+        // a dry run changes nothing, so it could be retried
+        let response = self
+            .call(request.dry_run, |mut client| {
+                let request = self.request(request.clone());
+                async move { client.reserve(request).await }
+            })
+            .await?;
+        response.into_inner().reservation.ok_or(Error::Unknown)
+    }
+
+    fn request<T>(&self, message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        if let Some(timeout) = self.config.timeout {
+            request.set_timeout(timeout);
+        }
+        request
+    }
+
+    /// call the server, retry if it failed and could be retried. A call not idempotent is only
+    /// retried when the server is unavailable
+    async fn call<T, F, Fut>(&self, idempotent: bool, mut f: F) -> Result<Response<T>, Error>
+    where
+        F: FnMut(ReservationServiceClient<Channel>) -> Fut,
+        Fut: Future<Output = Result<Response<T>, Status>>,
+    {
+        self.retry(idempotent, || f(self.inner.clone())).await
+    }
+
+    /// call the resource service, retry as `call`
+    async fn call_resources<T, F, Fut>(
+        &self,
+        idempotent: bool,
+        mut f: F,
+    ) -> Result<Response<T>, Error>
+    where
+        F: FnMut(ResourceServiceClient<Channel>) -> Fut,
+        Fut: Future<Output = Result<Response<T>, Status>>,
+    {
+        self.retry(idempotent, || f(self.resources.clone())).await
+    }
+
+    async fn retry<T, F, Fut>(&self, idempotent: bool, mut f: F) -> Result<Response<T>, Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<Response<T>, Status>>,
+    {
+        let mut backoff = self.config.retry_backoff;
+        let mut retries = 0;
+        loop {
+            match f().await {
+                Ok(response) => return Ok(response),
+                Err(status)
+                    if retries < self.config.retries && should_retry(&status, idempotent) =>
+                {
+                    retries += 1;
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Err(status) => return Err(status.into()),
+            }
+        }
+    }
+}
+
+fn should_retry(status: &Status, idempotent: bool) -> bool {
+    match status.code() {
+        Code::Unavailable => true,
+        Code::DeadlineExceeded | Code::Aborted => idempotent,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use abi::{
+        AvailabilityQueryBuilder, ReservationConflictInfo, ReservationFilterBuilder,
+        ReservationQueryBuilder, ReservationStatus,
+    };
+    use reservation_service::test_utils::{new_reservation, seed_resources, serve};
+    use sqlx::PgPool;
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn client_should_manage_reservation() {
+        let client = start_server(migrate_pool.clone()).await;
+        let checked = client.check(new_reservation("room-1")).await.unwrap();
+        assert_eq!(checked.id, 0);
+        let rsvp = client.reserve(new_reservation("room-1")).await.unwrap();
+        assert!(rsvp.id != 0);
+        let err = client.check(new_reservation("room-1")).await.unwrap_err();
+        assert!(matches!(err, Error::ConflictReservation(_)));
+
+        // the reserved window is not available any more
+        let query = AvailabilityQueryBuilder::default()
+            .resource_ids(vec![rsvp.resource_id.clone()])
+            .start(rsvp.start.clone().unwrap())
+            .end(rsvp.end.clone().unwrap())
+            .build()
+            .unwrap();
+        assert!(client.availability(query).await.unwrap().is_empty());
+
+        let confirmed = client.confirm(rsvp.id).await.unwrap();
+        assert_eq!(confirmed.status, ReservationStatus::Confirmed as i32);
+
+        let updated = client.update(rsvp.id, "hello world").await.unwrap();
+        assert_eq!(updated.note, "hello world");
+        assert_eq!(client.get(rsvp.id).await.unwrap(), updated);
+
+        let canceled = client.cancel(rsvp.id).await.unwrap();
+        assert_eq!(canceled, updated);
+        let err = client.get(rsvp.id).await.unwrap_err();
+        assert_eq!(err, Error::RowNotFound);
+        let err = client.get(0).await.unwrap_err();
+        assert_eq!(err, Error::InvalidReservationId(0));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn client_should_parse_conflict_reservation() {
+        let client = start_server(migrate_pool.clone()).await;
+        let rsvp = client.reserve(new_reservation("room-1")).await.unwrap();
+        let err = client.reserve(new_reservation("room-1")).await.unwrap_err();
+        match err {
+            Error::ConflictReservation(ReservationConflictInfo::Parsed(conflict)) => {
+                assert_eq!(conflict.new.rid, "room-1");
+                assert_eq!(conflict.old.rid, "room-1");
+                assert_eq!(conflict.old.id, rsvp.id);
+                assert_eq!(conflict.conflicts, vec![rsvp]);
+                assert_eq!(
+                    conflict.old.start.unwrap().to_rfc3339(),
+                    "2022-12-25T22:00:00+00:00"
+                );
+                assert!(conflict.suggestions.is_empty());
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        let err = client
+            .reserve_with_suggestions(new_reservation("room-1"), vec!["room-2".to_string()])
+            .await
+            .unwrap_err();
+        match err {
+            Error::ConflictReservation(ReservationConflictInfo::Parsed(conflict)) => {
+                let rids: Vec<_> = conflict
+                    .suggestions
+                    .iter()
+                    .map(|slot| slot.resource_id.as_str())
+                    .collect();
+                assert_eq!(rids, vec!["room-1", "room-1", "room-2"]);
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn client_should_reserve_batch() {
+        let client = start_server(migrate_pool.clone()).await;
+        let rsvps = client
+            .reserve_batch(vec![new_reservation("room-1"), new_reservation("room-2")])
+            .await
+            .unwrap();
+        assert_eq!(rsvps.len(), 2);
+        assert_eq!(client.get(rsvps[1].id).await.unwrap(), rsvps[1]);
+
+        let err = client
+            .reserve_batch(vec![new_reservation("room-3"), new_reservation("room-1")])
+            .await
+            .unwrap_err();
+        match err {
+            Error::BatchConflict(conflicts) => {
+                assert_eq!(conflicts.len(), 1);
+                assert_eq!(conflicts[0].0, 1);
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn client_should_reserve_in_pool() {
+        let client = start_server(migrate_pool.clone()).await;
+        for id in ["meeting-a", "meeting-b"] {
+            let resource = Resource::new(id, "meeting-room");
+            client.create_resource(resource).await.unwrap();
+        }
+        let pool = ResourcePool {
+            r#type: "meeting-room".into(),
+            ..Default::default()
+        };
+
+        let rsvp = client
+            .reserve_in_pool(new_reservation(""), pool.clone())
+            .await
+            .unwrap();
+        assert_eq!(rsvp.resource_id, "meeting-a");
+        let rsvp = client
+            .reserve_in_pool(new_reservation(""), pool.clone())
+            .await
+            .unwrap();
+        assert_eq!(rsvp.resource_id, "meeting-b");
+        let err = client
+            .reserve_in_pool(new_reservation(""), pool)
+            .await
+            .unwrap_err();
+        assert_eq!(err, Error::NoAvailableResource("meeting-room".into()));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn client_should_manage_resources() {
+        let client = start_server(migrate_pool.clone()).await;
+        let resource = client
+            .create_resource(Resource::new("desk-1", "desk"))
+            .await
+            .unwrap();
+        assert!(resource.active);
+        let err = client
+            .create_resource(Resource::new("desk-1", "desk"))
+            .await
+            .unwrap_err();
+        assert_eq!(err, Error::ResourceExists("desk-1".into()));
+
+        let resource = client
+            .update_resource(UpdateResourceRequest {
+                id: "desk-1".into(),
+                name: Some("Desk 1".into()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(resource.name, "Desk 1");
+
+        client.deactivate_resource("desk-1").await.unwrap();
+        let err = client.reserve(new_reservation("desk-1")).await.unwrap_err();
+        assert_eq!(err, Error::InactiveResource("desk-1".into()));
+        let err = client
+            .reserve(new_reservation("desk-404"))
+            .await
+            .unwrap_err();
+        assert_eq!(err, Error::ResourceNotFound("desk-404".into()));
+
+        let request = ListResourcesRequest {
+            r#type: "desk".into(),
+            ..Default::default()
+        };
+        assert!(client.list_resources(request).await.unwrap().is_empty());
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn client_should_manage_series() {
+        let client = start_server(migrate_pool.clone()).await;
+        let rule = RecurrenceRule {
+            frequency: abi::RecurrenceFrequency::Weekly as i32,
+            count: 2,
+            ..Default::default()
+        };
+        let rsvps = client
+            .reserve_series(new_reservation("room-1"), rule)
+            .await
+            .unwrap();
+        assert_eq!(rsvps.len(), 2);
+        assert_eq!(client.get(rsvps[1].id).await.unwrap(), rsvps[1]);
+
+        // a week later, the last one is moved to the third week
+        let updated = client
+            .update_series(UpdateSeriesRequest {
+                id: rsvps[1].id,
+                scope: abi::SeriesScope::One as i32,
+                start: rsvps[1].start.clone().map(|mut ts| {
+                    ts.seconds += 7 * 24 * 3600;
+                    ts
+                }),
+                end: rsvps[1].end.clone().map(|mut ts| {
+                    ts.seconds += 7 * 24 * 3600;
+                    ts
+                }),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].id, rsvps[1].id);
+
+        let canceled = client
+            .cancel_series(rsvps[0].id, abi::SeriesScope::All)
+            .await
+            .unwrap();
+        assert_eq!(canceled.len(), 2);
+        assert_eq!(canceled[1], updated[0]);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn client_should_query_and_follow_filter_pages() {
+        let client = start_server(migrate_pool.clone()).await;
+        for i in 0..25 {
+            client
+                .reserve(new_reservation(format!("room-{}", i)))
+                .await
+                .unwrap();
+        }
+
+        let query = ReservationQueryBuilder::default()
+            .user_id("chalanziId")
+            .build()
+            .unwrap();
+        let (pager, stream) = client.query(query).await.unwrap();
+        assert_eq!(pager.total, Some(25));
+        assert_eq!(pager.next, Some(2));
+        let rsvps: Vec<_> = stream.try_collect().await.unwrap();
+        assert_eq!(rsvps.len(), 10);
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("chalanziId")
+            .build()
+            .unwrap();
+        let rsvps: Vec<_> = client.filter(filter).try_collect().await.unwrap();
+        assert_eq!(rsvps.len(), 25);
+        let ids: Vec<_> = rsvps.iter().map(|rsvp| rsvp.id).collect();
+        assert_eq!(ids, (1..=25).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn client_should_retry_unavailable_server() {
+        // the port is closed once the listener is dropped
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let endpoint = Endpoint::from_shared(format!("http://{}", addr)).unwrap();
+        let channel = endpoint.connect_lazy();
+        let client = ReservationClient {
+            inner: ReservationServiceClient::new(channel.clone()),
+            resources: ResourceServiceClient::new(channel),
+            config: ClientConfig::new(format!("http://{}", addr))
+                .with_retries(2, Duration::from_millis(100)),
+        };
+
+        let start = tokio::time::Instant::now();
+        let err = client.get(1).await.unwrap_err();
+        match err {
+            Error::RpcError(code, _) => assert_eq!(code, Code::Unavailable),
+            err => panic!("unexpected error: {:?}", err),
+        }
+        // retried twice, waited 100ms and 200ms
+        assert!(start.elapsed() >= Duration::from_millis(300));
+    }
+
+    /// a client of the service served on a random local port, with the resources reserved in
+    /// the tests
+    async fn start_server(pool: PgPool) -> ReservationClient {
+        seed_resources(&pool).await;
+        ReservationClient::connect(ClientConfig::new(serve(pool).await))
+            .await
+            .unwrap()
+    }
+}
@@ -0,0 +1,58 @@
+mod client;
+
+use std::time::Duration;
+
+use abi::{
+    reservation_service_client::ReservationServiceClient,
+    resource_service_client::ResourceServiceClient,
+};
+use tonic::transport::Channel;
+
+/// async client of the reservation service, the errors returned by the server are converted
+/// back to abi::Error
+#[derive(Debug, Clone)]
+pub struct ReservationClient {
+    inner: ReservationServiceClient<Channel>,
+    resources: ResourceServiceClient<Channel>,
+    config: ClientConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientConfig {
+    /// url of the server, e.g. http://localhost:50051
+    pub url: String,
+    /// deadline of each call. The listen stream is not limited
+    pub timeout: Option<Duration>,
+    /// times to retry a call failed as the server is unavailable
+    pub retries: u32,
+    /// delay before the first retry, it is doubled for the next one
+    pub retry_backoff: Duration,
+}
+
+impl ClientConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            timeout: Some(Duration::from_secs(10)),
+            retries: 3,
+            retry_backoff: Duration::from_millis(100),
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_retries(mut self, retries: u32, backoff: Duration) -> Self {
+        self.retries = retries;
+        self.retry_backoff = backoff;
+        self
+    }
+}
+
+impl From<&abi::ServerConfig> for ClientConfig {
+    fn from(config: &abi::ServerConfig) -> Self {
+        Self::new(config.url(false))
+    }
+}
@@ -0,0 +1,8 @@
+-- the resources reserved in the tests, it could be run more than once
+INSERT INTO rsvp.resources (id, name)
+SELECT id, id
+FROM (
+  SELECT 'room-' || i FROM generate_series(0, 24) i
+  UNION ALL
+  SELECT unnest(ARRAY['ocean-view-room-713', 'ixia-test-1', 'projector-1', 'parking-1', 'room ''a''']::varchar[])) AS r (id)
+ON CONFLICT (id) DO NOTHING;
@@ -0,0 +1,33 @@
+CREATE OR REPLACE FUNCTION rsvp.reservations_trigger ()
+  RETURNS TRIGGER
+  AS $$
+BEGIN
+  IF TG_OP = 'INSERT' THEN
+    -- update reservation_changes
+    INSERT INTO rsvp.reservation_changes (reservation_id, op)
+      VALUES (NEW.id, 'create');
+  ELSIF TG_OP = 'update' THEN
+    -- if status changed,update reservation_changes
+    IF OLD.status <> NEW.status THEN
+      INSERT INTO rsvp.reservation_changes (reservation_id, op)
+        VALUES (OLD.id, 'update');
+    END IF;
+  ELSIF TG_OP = 'DELETE' THEN
+    -- update reservation_changes
+    INSERT INTO rsvp.reservation_changes (reservation_id, op)
+      VALUES (OLD.id, 'delete');
+  END IF;
+  -- notify a channel called reservation_udate
+  NOTIFY reservation_update;
+  RETURN NULL;
+END;
+$$
+LANGUAGE plpgsql;
+
+ALTER TABLE rsvp.reservation_changes
+  DROP CONSTRAINT reservation_changes_pkey,
+  DROP COLUMN user_id,
+  DROP COLUMN status,
+  DROP COLUMN resource_id,
+  DROP COLUMN timespan,
+  DROP COLUMN note;
@@ -0,0 +1,59 @@
+-- keep a snapshot of the reservation in the change queue, so that a listener
+-- could still get the full reservation after it has been deleted
+ALTER TABLE rsvp.reservation_changes
+  ADD COLUMN user_id varchar(64),
+  ADD COLUMN status rsvp.reservation_status,
+  ADD COLUMN resource_id varchar(64),
+  ADD COLUMN timespan tstzrange,
+  ADD COLUMN note text;
+
+UPDATE
+  rsvp.reservation_changes c
+SET
+  user_id = r.user_id,
+  status = r.status,
+  resource_id = r.resource_id,
+  timespan = r.timespan,
+  note = r.note
+FROM
+  rsvp.reservations r
+WHERE
+  c.reservation_id = r.id;
+
+-- changes of the reservations deleted before this migration could not be recovered
+DELETE FROM rsvp.reservation_changes
+WHERE user_id IS NULL;
+
+ALTER TABLE rsvp.reservation_changes
+  ALTER COLUMN user_id SET NOT NULL,
+  ALTER COLUMN status SET NOT NULL,
+  ALTER COLUMN resource_id SET NOT NULL,
+  ALTER COLUMN timespan SET NOT NULL,
+  ADD CONSTRAINT reservation_changes_pkey PRIMARY KEY (id);
+
+-- trigger for add/update/delete a reservation
+CREATE OR REPLACE FUNCTION rsvp.reservations_trigger ()
+  RETURNS TRIGGER
+  AS $$
+BEGIN
+  IF TG_OP = 'INSERT' THEN
+    -- update reservation_changes
+    INSERT INTO rsvp.reservation_changes (reservation_id, op, user_id, status, resource_id, timespan, note)
+      VALUES (NEW.id, 'create', NEW.user_id, NEW.status, NEW.resource_id, NEW.timespan, NEW.note);
+  ELSIF TG_OP = 'UPDATE' THEN
+    -- if status changed,update reservation_changes
+    IF OLD.status <> NEW.status THEN
+      INSERT INTO rsvp.reservation_changes (reservation_id, op, user_id, status, resource_id, timespan, note)
+        VALUES (NEW.id, 'update', NEW.user_id, NEW.status, NEW.resource_id, NEW.timespan, NEW.note);
+    END IF;
+  ELSIF TG_OP = 'DELETE' THEN
+    -- update reservation_changes
+    INSERT INTO rsvp.reservation_changes (reservation_id, op, user_id, status, resource_id, timespan, note)
+      VALUES (OLD.id, 'delete', OLD.user_id, OLD.status, OLD.resource_id, OLD.timespan, OLD.note);
+  END IF;
+  -- notify a channel called reservation_update
+  NOTIFY reservation_update;
+  RETURN NULL;
+END;
+$$
+LANGUAGE plpgsql;
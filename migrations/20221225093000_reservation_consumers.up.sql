@@ -0,0 +1,7 @@
+-- the last acknowledged change of each durable consumer
+CREATE TABLE rsvp.reservation_consumers (
+  name varchar(64) NOT NULL,
+  last_change_id bigint NOT NULL,
+  updated_at timestamptz NOT NULL DEFAULT now(),
+  CONSTRAINT reservation_consumers_pkey PRIMARY KEY (name)
+);
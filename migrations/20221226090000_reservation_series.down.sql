@@ -0,0 +1,36 @@
+CREATE OR REPLACE FUNCTION rsvp.reservations_trigger ()
+  RETURNS TRIGGER
+  AS $$
+BEGIN
+  PERFORM pg_advisory_xact_lock('rsvp.reservation_changes'::regclass::oid::bigint);
+  IF TG_OP = 'INSERT' THEN
+    -- update reservation_changes
+    INSERT INTO rsvp.reservation_changes (reservation_id, op, user_id, status, resource_id, timespan, note)
+      VALUES (NEW.id, 'create', NEW.user_id, NEW.status, NEW.resource_id, NEW.timespan, NEW.note);
+  ELSIF TG_OP = 'UPDATE' THEN
+    -- if status changed,update reservation_changes
+    IF OLD.status <> NEW.status THEN
+      INSERT INTO rsvp.reservation_changes (reservation_id, op, user_id, status, resource_id, timespan, note)
+        VALUES (NEW.id, 'update', NEW.user_id, NEW.status, NEW.resource_id, NEW.timespan, NEW.note);
+    END IF;
+  ELSIF TG_OP = 'DELETE' THEN
+    -- update reservation_changes
+    INSERT INTO rsvp.reservation_changes (reservation_id, op, user_id, status, resource_id, timespan, note)
+      VALUES (OLD.id, 'delete', OLD.user_id, OLD.status, OLD.resource_id, OLD.timespan, OLD.note);
+  END IF;
+  -- notify a channel called reservation_update
+  NOTIFY reservation_update;
+  RETURN NULL;
+END;
+$$
+LANGUAGE plpgsql;
+
+ALTER TABLE rsvp.reservation_changes
+  DROP COLUMN series_id;
+
+DROP INDEX rsvp.reservations_series_id_idx;
+
+ALTER TABLE rsvp.reservations
+  DROP COLUMN series_id;
+
+DROP SEQUENCE rsvp.reservation_series_id_seq;
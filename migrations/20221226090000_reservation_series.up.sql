@@ -0,0 +1,40 @@
+-- the occurrences of a recurring reservation share the same series id
+CREATE SEQUENCE rsvp.reservation_series_id_seq AS bigint;
+
+ALTER TABLE rsvp.reservations
+  ADD COLUMN series_id bigint;
+
+CREATE INDEX reservations_series_id_idx ON rsvp.reservations (series_id, lower(timespan))
+WHERE
+  series_id IS NOT NULL;
+
+ALTER TABLE rsvp.reservation_changes
+  ADD COLUMN series_id bigint;
+
+-- trigger for add/update/delete a reservation
+CREATE OR REPLACE FUNCTION rsvp.reservations_trigger ()
+  RETURNS TRIGGER
+  AS $$
+BEGIN
+  PERFORM pg_advisory_xact_lock('rsvp.reservation_changes'::regclass::oid::bigint);
+  IF TG_OP = 'INSERT' THEN
+    -- update reservation_changes
+    INSERT INTO rsvp.reservation_changes (reservation_id, op, user_id, status, resource_id, timespan, note, series_id)
+      VALUES (NEW.id, 'create', NEW.user_id, NEW.status, NEW.resource_id, NEW.timespan, NEW.note, NEW.series_id);
+  ELSIF TG_OP = 'UPDATE' THEN
+    -- if status or timespan changed,update reservation_changes
+    IF OLD.status <> NEW.status OR OLD.timespan <> NEW.timespan THEN
+      INSERT INTO rsvp.reservation_changes (reservation_id, op, user_id, status, resource_id, timespan, note, series_id)
+        VALUES (NEW.id, 'update', NEW.user_id, NEW.status, NEW.resource_id, NEW.timespan, NEW.note, NEW.series_id);
+    END IF;
+  ELSIF TG_OP = 'DELETE' THEN
+    -- update reservation_changes
+    INSERT INTO rsvp.reservation_changes (reservation_id, op, user_id, status, resource_id, timespan, note, series_id)
+      VALUES (OLD.id, 'delete', OLD.user_id, OLD.status, OLD.resource_id, OLD.timespan, OLD.note, OLD.series_id);
+  END IF;
+  -- notify a channel called reservation_update
+  NOTIFY reservation_update;
+  RETURN NULL;
+END;
+$$
+LANGUAGE plpgsql;
@@ -0,0 +1,8 @@
+DROP TRIGGER reservations_resource_active ON rsvp.reservations;
+
+DROP FUNCTION rsvp.reservations_resource_active ();
+
+ALTER TABLE rsvp.reservations
+  DROP CONSTRAINT reservations_resource_id_fkey;
+
+DROP TABLE rsvp.resources;
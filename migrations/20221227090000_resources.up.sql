@@ -0,0 +1,52 @@
+-- the resources which could be reserved
+CREATE TABLE rsvp.resources (
+  id varchar(64) NOT NULL,
+  name varchar(128) NOT NULL,
+  type varchar(64) NOT NULL DEFAULT '',
+  attributes jsonb NOT NULL DEFAULT '{}',
+  active boolean NOT NULL DEFAULT TRUE,
+  -- IANA name of the timezone, e.g. Asia/Shanghai
+  timezone varchar(64) NOT NULL DEFAULT 'UTC',
+  created_at timestamptz NOT NULL DEFAULT now(),
+  updated_at timestamptz NOT NULL DEFAULT now(),
+  CONSTRAINT resources_pkey PRIMARY KEY (id)
+);
+
+CREATE INDEX resources_type_idx ON rsvp.resources (type);
+
+-- the resources reserved before are kept as they are
+INSERT INTO rsvp.resources (id, name)
+SELECT DISTINCT
+  resource_id,
+  resource_id
+FROM
+  rsvp.reservations;
+
+ALTER TABLE rsvp.reservations
+  ADD CONSTRAINT reservations_resource_id_fkey FOREIGN KEY (resource_id) REFERENCES rsvp.resources (id);
+
+-- a reservation could only be made on an active resource. The row is locked so that the resource
+-- isn't deactivated before the reservation is committed
+CREATE OR REPLACE FUNCTION rsvp.reservations_resource_active ()
+  RETURNS TRIGGER
+  AS $$
+DECLARE
+  is_active boolean;
+BEGIN
+  SELECT active INTO is_active FROM rsvp.resources WHERE id = NEW.resource_id FOR SHARE;
+  -- an unknown resource is rejected by the foreign key
+  IF NOT is_active THEN
+    RAISE EXCEPTION 'resource % is not active', NEW.resource_id
+      USING ERRCODE = 'check_violation', SCHEMA = TG_TABLE_SCHEMA, TABLE = TG_TABLE_NAME,
+        CONSTRAINT = 'reservations_resource_active',
+        DETAIL = format('Key (resource_id)=(%s) is not active.', NEW.resource_id);
+  END IF;
+  RETURN NEW;
+END;
+$$
+LANGUAGE plpgsql;
+
+CREATE TRIGGER reservations_resource_active
+  BEFORE INSERT ON rsvp.reservations
+  FOR EACH ROW
+  EXECUTE PROCEDURE rsvp.reservations_resource_active ();
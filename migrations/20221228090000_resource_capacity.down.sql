@@ -0,0 +1,51 @@
+CREATE OR REPLACE FUNCTION rsvp.reservations_trigger ()
+  RETURNS TRIGGER
+  AS $$
+BEGIN
+  PERFORM pg_advisory_xact_lock('rsvp.reservation_changes'::regclass::oid::bigint);
+  IF TG_OP = 'INSERT' THEN
+    -- update reservation_changes
+    INSERT INTO rsvp.reservation_changes (reservation_id, op, user_id, status, resource_id, timespan, note, series_id)
+      VALUES (NEW.id, 'create', NEW.user_id, NEW.status, NEW.resource_id, NEW.timespan, NEW.note, NEW.series_id);
+  ELSIF TG_OP = 'UPDATE' THEN
+    -- if status or timespan changed,update reservation_changes
+    IF OLD.status <> NEW.status OR OLD.timespan <> NEW.timespan THEN
+      INSERT INTO rsvp.reservation_changes (reservation_id, op, user_id, status, resource_id, timespan, note, series_id)
+        VALUES (NEW.id, 'update', NEW.user_id, NEW.status, NEW.resource_id, NEW.timespan, NEW.note, NEW.series_id);
+    END IF;
+  ELSIF TG_OP = 'DELETE' THEN
+    -- update reservation_changes
+    INSERT INTO rsvp.reservation_changes (reservation_id, op, user_id, status, resource_id, timespan, note, series_id)
+      VALUES (OLD.id, 'delete', OLD.user_id, OLD.status, OLD.resource_id, OLD.timespan, OLD.note, OLD.series_id);
+  END IF;
+  -- notify a channel called reservation_update
+  NOTIFY reservation_update;
+  RETURN NULL;
+END;
+$$
+LANGUAGE plpgsql;
+
+DROP TRIGGER resources_capacity ON rsvp.resources;
+
+DROP FUNCTION rsvp.resources_capacity ();
+
+DROP TRIGGER reservations_capacity ON rsvp.reservations;
+
+DROP FUNCTION rsvp.reservations_capacity ();
+
+ALTER TABLE rsvp.reservation_changes
+  DROP COLUMN quantity;
+
+-- the shared reservations may overlap, the constraint is only restored if they are removed
+ALTER TABLE rsvp.reservations
+  DROP CONSTRAINT reservations_conflict;
+
+ALTER TABLE rsvp.reservations
+  ADD CONSTRAINT reservations_conflict EXCLUDE USING gist (resource_id WITH =, timespan WITH &&);
+
+ALTER TABLE rsvp.reservations
+  DROP COLUMN shared,
+  DROP COLUMN quantity;
+
+ALTER TABLE rsvp.resources
+  DROP COLUMN capacity;
@@ -0,0 +1,116 @@
+-- a resource of capacity N could be reserved by up to N units at the same time
+ALTER TABLE rsvp.resources
+  ADD COLUMN capacity integer NOT NULL DEFAULT 1 CHECK (capacity > 0);
+
+-- shared is kept by the trigger, the reservations of a resource of capacity 1 are exclusive
+ALTER TABLE rsvp.reservations
+  ADD COLUMN quantity integer NOT NULL DEFAULT 1 CHECK (quantity > 0),
+  ADD COLUMN shared boolean NOT NULL DEFAULT FALSE;
+
+ALTER TABLE rsvp.reservations
+  DROP CONSTRAINT reservations_conflict;
+
+ALTER TABLE rsvp.reservations
+  ADD CONSTRAINT reservations_conflict EXCLUDE USING gist (resource_id WITH =, timespan WITH &&)
+  WHERE (NOT shared);
+
+ALTER TABLE rsvp.reservation_changes
+  ADD COLUMN quantity integer NOT NULL DEFAULT 1;
+
+-- check the units reserved at any point of the timespan never exceed the capacity
+CREATE OR REPLACE FUNCTION rsvp.reservations_capacity ()
+  RETURNS TRIGGER
+  AS $$
+DECLARE
+  cap integer;
+  reserved integer := 0;
+BEGIN
+  SELECT capacity INTO cap FROM rsvp.resources WHERE id = NEW.resource_id;
+  -- an unknown resource is rejected by the foreign key
+  IF cap IS NULL THEN
+    RETURN NEW;
+  END IF;
+  IF cap > 1 THEN
+    -- the concurrent reservations of a shared resource are serialized by the lock. It's taken before
+    -- the weaker one of reservations_resource_active, which fires after this trigger by name
+    SELECT capacity INTO cap FROM rsvp.resources WHERE id = NEW.resource_id FOR NO KEY UPDATE;
+  END IF;
+  NEW.shared := cap > 1;
+
+  IF NEW.shared THEN
+    -- the units reserved only change at the start of a reservation
+    SELECT COALESCE(MAX(load), 0) INTO reserved
+    FROM (
+      SELECT (
+          SELECT COALESCE(SUM(r.quantity), 0)
+          FROM rsvp.reservations r
+          WHERE r.resource_id = NEW.resource_id AND r.id <> NEW.id AND r.timespan @> p.at) AS load
+      FROM (
+        SELECT COALESCE(lower(NEW.timespan), '-infinity') AS at
+        UNION
+        SELECT lower(timespan)
+        FROM rsvp.reservations
+        WHERE resource_id = NEW.resource_id AND id <> NEW.id AND NEW.timespan @> lower(timespan)) p) loads;
+  END IF;
+
+  IF reserved + NEW.quantity > cap THEN
+    RAISE EXCEPTION 'reservation exceeds the capacity of resource %', NEW.resource_id
+      USING ERRCODE = 'check_violation', SCHEMA = TG_TABLE_SCHEMA, TABLE = TG_TABLE_NAME,
+        CONSTRAINT = 'reservations_capacity',
+        DETAIL = format('Key (resource_id, timespan)=(%s, %s) requests %s of capacity %s, %s reserved.',
+          NEW.resource_id, NEW.timespan, NEW.quantity, cap, reserved);
+  END IF;
+  RETURN NEW;
+END;
+$$
+LANGUAGE plpgsql;
+
+CREATE TRIGGER reservations_capacity
+  BEFORE INSERT OR UPDATE OF resource_id, timespan, quantity, shared ON rsvp.reservations
+  FOR EACH ROW
+  EXECUTE PROCEDURE rsvp.reservations_capacity ();
+
+-- the reservations of a resource are checked again against its new capacity
+CREATE OR REPLACE FUNCTION rsvp.resources_capacity ()
+  RETURNS TRIGGER
+  AS $$
+BEGIN
+  UPDATE rsvp.reservations SET shared = NEW.capacity > 1 WHERE resource_id = NEW.id;
+  RETURN NULL;
+END;
+$$
+LANGUAGE plpgsql;
+
+CREATE TRIGGER resources_capacity
+  AFTER UPDATE OF capacity ON rsvp.resources
+  FOR EACH ROW
+  WHEN (OLD.capacity <> NEW.capacity)
+  EXECUTE PROCEDURE rsvp.resources_capacity ();
+
+-- trigger for add/update/delete a reservation
+CREATE OR REPLACE FUNCTION rsvp.reservations_trigger ()
+  RETURNS TRIGGER
+  AS $$
+BEGIN
+  PERFORM pg_advisory_xact_lock('rsvp.reservation_changes'::regclass::oid::bigint);
+  IF TG_OP = 'INSERT' THEN
+    -- update reservation_changes
+    INSERT INTO rsvp.reservation_changes (reservation_id, op, user_id, status, resource_id, timespan, note, series_id, quantity)
+      VALUES (NEW.id, 'create', NEW.user_id, NEW.status, NEW.resource_id, NEW.timespan, NEW.note, NEW.series_id, NEW.quantity);
+  ELSIF TG_OP = 'UPDATE' THEN
+    -- if status or timespan changed,update reservation_changes
+    IF OLD.status <> NEW.status OR OLD.timespan <> NEW.timespan THEN
+      INSERT INTO rsvp.reservation_changes (reservation_id, op, user_id, status, resource_id, timespan, note, series_id, quantity)
+        VALUES (NEW.id, 'update', NEW.user_id, NEW.status, NEW.resource_id, NEW.timespan, NEW.note, NEW.series_id, NEW.quantity);
+    END IF;
+  ELSIF TG_OP = 'DELETE' THEN
+    -- update reservation_changes
+    INSERT INTO rsvp.reservation_changes (reservation_id, op, user_id, status, resource_id, timespan, note, series_id, quantity)
+      VALUES (OLD.id, 'delete', OLD.user_id, OLD.status, OLD.resource_id, OLD.timespan, OLD.note, OLD.series_id, OLD.quantity);
+  END IF;
+  -- notify a channel called reservation_update
+  NOTIFY reservation_update;
+  RETURN NULL;
+END;
+$$
+LANGUAGE plpgsql;
@@ -0,0 +1,13 @@
+DROP TRIGGER reservations_hierarchy ON rsvp.reservations;
+
+DROP FUNCTION rsvp.reservations_hierarchy ();
+
+DROP FUNCTION rsvp.resource_root (varchar);
+
+DROP FUNCTION rsvp.resource_family (varchar);
+
+DROP INDEX rsvp.resources_parent_id_idx;
+
+ALTER TABLE rsvp.resources
+  DROP CONSTRAINT resources_parent_check,
+  DROP COLUMN parent_id;
@@ -0,0 +1,83 @@
+-- the resources form a tree, e.g. building -> floor -> room. The root resources have no parent
+ALTER TABLE rsvp.resources
+  ADD COLUMN parent_id varchar(64) REFERENCES rsvp.resources (id),
+  ADD CONSTRAINT resources_parent_check CHECK (parent_id <> id);
+
+CREATE INDEX resources_parent_id_idx ON rsvp.resources (parent_id);
+
+-- the ancestors and the descendants of a resource, not including itself
+CREATE OR REPLACE FUNCTION rsvp.resource_family (rid varchar(64))
+  RETURNS TABLE (
+    id varchar(64),
+    relation text)
+  AS $$
+  WITH RECURSIVE ancestors (id) AS (
+    SELECT r.parent_id FROM rsvp.resources r WHERE r.id = rid AND r.parent_id IS NOT NULL
+    UNION
+    SELECT r.parent_id FROM rsvp.resources r JOIN ancestors a ON r.id = a.id WHERE r.parent_id IS NOT NULL
+  ),
+  descendants (id) AS (
+    SELECT r.id FROM rsvp.resources r WHERE r.parent_id = rid
+    UNION
+    SELECT r.id FROM rsvp.resources r JOIN descendants d ON r.parent_id = d.id
+  )
+  SELECT a.id, 'ancestor' FROM ancestors a WHERE a.id <> rid
+  UNION ALL
+  SELECT d.id, 'descendant' FROM descendants d WHERE d.id <> rid
+$$
+LANGUAGE sql
+STABLE;
+
+-- the root of the tree of a resource, the resource itself if it has no parent
+CREATE OR REPLACE FUNCTION rsvp.resource_root (rid varchar(64))
+  RETURNS varchar(64)
+  AS $$
+  WITH RECURSIVE ancestors (id, parent_id, path) AS (
+    SELECT r.id, r.parent_id, ARRAY[r.id]::varchar[] FROM rsvp.resources r WHERE r.id = rid
+    UNION ALL
+    SELECT r.id, r.parent_id, a.path || r.id
+    FROM rsvp.resources r
+    JOIN ancestors a ON r.id = a.parent_id
+    WHERE NOT r.id = ANY (a.path)
+  )
+  SELECT id FROM ancestors ORDER BY cardinality(path) DESC LIMIT 1
+$$
+LANGUAGE sql
+STABLE;
+
+-- a reservation conflicts with the overlapping ones on the ancestors and the descendants of its
+-- resource, the ones on the resource itself are checked by reservations_conflict
+CREATE OR REPLACE FUNCTION rsvp.reservations_hierarchy ()
+  RETURNS TRIGGER
+  AS $$
+DECLARE
+  existing rsvp.reservations;
+BEGIN
+  -- the concurrent reservations in the same tree are serialized by the lock of its root. It's
+  -- taken even without a family, as a resource could get one concurrently
+  PERFORM 1 FROM rsvp.resources WHERE id = rsvp.resource_root(NEW.resource_id) FOR NO KEY UPDATE;
+
+  SELECT r.* INTO existing
+  FROM rsvp.reservations r
+  JOIN rsvp.resource_family(NEW.resource_id) f ON f.id = r.resource_id
+  WHERE r.timespan && NEW.timespan AND r.id <> NEW.id
+  ORDER BY lower(r.timespan)
+  LIMIT 1;
+
+  -- the same detail as the exclusion constraint, so that it's parsed the same way
+  IF FOUND THEN
+    RAISE EXCEPTION 'reservation conflicts with the reservation % of resource %', existing.id, existing.resource_id
+      USING ERRCODE = 'exclusion_violation', SCHEMA = TG_TABLE_SCHEMA, TABLE = TG_TABLE_NAME,
+        CONSTRAINT = 'reservations_hierarchy',
+        DETAIL = format('Key (resource_id, timespan)=(%s, %s) conflicts with existing key (resource_id, timespan)=(%s, %s).',
+          NEW.resource_id, NEW.timespan, existing.resource_id, existing.timespan);
+  END IF;
+  RETURN NEW;
+END;
+$$
+LANGUAGE plpgsql;
+
+CREATE TRIGGER reservations_hierarchy
+  BEFORE INSERT OR UPDATE OF resource_id, timespan ON rsvp.reservations
+  FOR EACH ROW
+  EXECUTE PROCEDURE rsvp.reservations_hierarchy ();
@@ -1,18 +1,81 @@
 mod manager;
+mod pool;
+mod resource;
+
+pub use pool::{BestFit, Candidate, FirstFit, LeastUsed, Strategy};
 
 use async_trait::async_trait;
 use sqlx::PgPool;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 
 #[derive(Debug)]
 pub struct ReservationManager {
     pool: PgPool,
 }
 
+/// resource catalog trait, a reservation could only be made on an active resource in it
+#[async_trait]
+pub trait Catalog {
+    /// add a resource to the catalog, a new resource is always active
+    async fn create_resource(&self, resource: abi::Resource) -> Result<abi::Resource, abi::Error>;
+    /// change the resource by the request, the attributes are merged. A smaller capacity is
+    /// rejected if the existing reservations exceed it
+    async fn update_resource(
+        &self,
+        request: abi::UpdateResourceRequest,
+    ) -> Result<abi::Resource, abi::Error>;
+    /// the resource could not be reserved any more, its reservations are kept
+    async fn deactivate_resource(&self, id: abi::ResourceId) -> Result<abi::Resource, abi::Error>;
+    /// the resources order by id
+    async fn list_resources(
+        &self,
+        request: abi::ListResourcesRequest,
+    ) -> Result<Vec<abi::Resource>, abi::Error>;
+}
+
 /// reservation trait
 #[async_trait]
 pub trait Rsvp {
     /// make a reservation
     async fn reserve(&self, rsvp: abi::Reservation) -> Result<abi::Reservation, abi::Error>;
+    /// make the reservations, possibly on different resources, in one transaction. If any of them
+    /// conflicts, none is made and all the conflicts are returned with their index in the batch
+    async fn reserve_many(
+        &self,
+        rsvps: Vec<abi::Reservation>,
+    ) -> Result<Vec<abi::Reservation>, abi::Error>;
+    /// make the occurrences of the recurring reservation in one transaction, linked by a new series
+    /// id. If any of them conflicts, none is made and all the conflicts are returned with their
+    /// index in the occurrences
+    async fn reserve_series(
+        &self,
+        rsvp: abi::Reservation,
+        rule: abi::RecurrenceRule,
+    ) -> Result<Vec<abi::Reservation>, abi::Error>;
+    /// change the note and/or move the occurrences in the scope of the given one by its offsets,
+    /// either all or none of them. The conflicts are indexed by the start time of the occurrences
+    async fn update_series(
+        &self,
+        request: abi::UpdateSeriesRequest,
+    ) -> Result<Vec<abi::Reservation>, abi::Error>;
+    /// delete the occurrences in the scope of the given one, return the deleted ones
+    async fn cancel_series(
+        &self,
+        request: abi::CancelSeriesRequest,
+    ) -> Result<Vec<abi::Reservation>, abi::Error>;
+    /// reserve a free resource of the pool chosen by its strategy, atomically. The resource_id of
+    /// the reservation must be empty, the returned one carries the chosen resource. Nothing is
+    /// made for a dry run
+    async fn reserve_in_pool(
+        &self,
+        rsvp: abi::Reservation,
+        pool: abi::ResourcePool,
+        dry_run: bool,
+    ) -> Result<abi::Reservation, abi::Error>;
+    /// validate the reservation and test it against the existing ones without making it, the
+    /// same conflict as `reserve` is returned. The id of the returned reservation is 0
+    async fn check(&self, rsvp: abi::Reservation) -> Result<abi::Reservation, abi::Error>;
     /// change reservation status (if current status is pending, change it to confirmed)
     async fn change_status(&self, id: abi::ReservationId) -> Result<abi::Reservation, abi::Error>;
     /// update note
@@ -21,8 +84,14 @@ pub trait Rsvp {
         id: abi::ReservationId,
         note: String,
     ) -> Result<abi::Reservation, abi::Error>;
-    /// delete reservation
-    async fn delete(&self, id: abi::ReservationId) -> Result<(), abi::Error>;
+    /// confirm the pending reservation and change its note in one write
+    async fn confirm_with_note(
+        &self,
+        id: abi::ReservationId,
+        note: String,
+    ) -> Result<abi::Reservation, abi::Error>;
+    /// delete reservation, return the deleted one
+    async fn delete(&self, id: abi::ReservationId) -> Result<abi::Reservation, abi::Error>;
     /// get reservation by id
     async fn get(&self, id: abi::ReservationId) -> Result<abi::Reservation, abi::Error>;
     /// query reservations
@@ -30,9 +99,44 @@ pub trait Rsvp {
         &self,
         query: abi::ReservationQuery,
     ) -> Result<Vec<abi::Reservation>, abi::Error>;
+    /// query reservations, the rows are streamed from the database as the stream is consumed.
+    /// the pager is only filled for a paged query
+    async fn query_stream(
+        &self,
+        query: abi::ReservationQuery,
+    ) -> Result<
+        (
+            abi::FilterPager,
+            ReceiverStream<Result<abi::Reservation, abi::Error>>,
+        ),
+        abi::Error,
+    >;
     /// query reservations order by id
     async fn filter(
         &self,
         query: abi::ReservationFilter,
     ) -> Result<(abi::FilterPager, Vec<abi::Reservation>), abi::Error>;
+    /// listen to reservation changes (create/update/delete). The changes after `since`, or after
+    /// the last acknowledged change of the consumer, are replayed before the live ones
+    async fn listen(
+        &self,
+        request: abi::ListenRequest,
+    ) -> Result<mpsc::Receiver<Result<abi::ListenResponse, abi::Error>>, abi::Error>;
+    /// record the last change processed by a durable consumer
+    async fn ack(&self, request: abi::AckRequest) -> Result<(), abi::Error>;
+    /// free slots of the resources in the window, order by the resources of the query, then by
+    /// the start time. the gaps between all the reservations are free, whatever their status is.
+    /// a shared resource is only free where none of its units is reserved, an inactive one has no
+    /// free slot and an unknown one is rejected
+    async fn availability(
+        &self,
+        query: abi::AvailabilityQuery,
+    ) -> Result<Vec<abi::FreeSlot>, abi::Error>;
+    /// free windows of the same length as the reservation: the nearest ones before and after it
+    /// on its resource, then the same window on the alternative resources which are free
+    async fn suggest(
+        &self,
+        rsvp: &abi::Reservation,
+        alternatives: &[String],
+    ) -> Result<Vec<abi::FreeSlot>, abi::Error>;
 }
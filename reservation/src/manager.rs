@@ -1,39 +1,199 @@
-use abi::{Normalizer, ToSql, Validator};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use abi::{convert_to_timestamp, convert_to_utc_time, Normalizer, ToSql, Validator};
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+use futures::StreamExt;
 use sqlx::{
-    postgres::{types::PgRange, PgPoolOptions},
-    PgPool, Row,
+    postgres::{types::PgRange, PgListener, PgPoolOptions},
+    Acquire, Executor, FromRow, PgPool, Postgres, QueryBuilder, Row,
 };
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::{pool::strategy, ReservationManager, Rsvp};
 
-use crate::{ReservationManager, Rsvp};
+/// the channel notified by "rsvp.reservations_trigger"
+const RESERVATION_UPDATE_CHANNEL: &str = "reservation_update";
+const CHANNEL_SIZE: usize = 128;
+const CHANGES_BATCH_SIZE: i64 = 100;
+/// how far the suggestions are searched before and after the reservation
+const SUGGESTION_HORIZON_DAYS: i64 = 30;
 
 #[async_trait]
 impl Rsvp for ReservationManager {
     async fn reserve(&self, mut rsvp: abi::Reservation) -> Result<abi::Reservation, abi::Error> {
-        rsvp.validate()?;
-
-        let timespan: PgRange<DateTime<Utc>> = rsvp.get_timespan();
-
-        let status = abi::ReservationStatus::from_i32(rsvp.status)
-            .unwrap_or(abi::ReservationStatus::Pending);
-        // generate a insert sql for the reservation
-        let id= sqlx::query(
-          "INSERT INTO rsvp.reservations (user_id,resource_id,timespan,note,status) VALUES ($1,$2,$3,$4,$5::rsvp.reservation_status) RETURNING id")
-        .bind(rsvp.user_id.clone())
-        .bind(rsvp.resource_id.clone())
-        .bind(timespan)
-        .bind(rsvp.note.clone())
-        .bind(status.to_string())
-        .fetch_one(&self.pool)
-        .await?
-        .get(0);
+        validate_new(&mut rsvp)?;
 
-        rsvp.id = id;
+        rsvp.id = match insert(&self.pool, &rsvp).await {
+            Ok(id) => id,
+            Err(e) => return Err(Self::with_conflicts(&self.pool, e, &rsvp).await),
+        };
 
         Ok(rsvp)
     }
 
+    async fn reserve_many(
+        &self,
+        mut rsvps: Vec<abi::Reservation>,
+    ) -> Result<Vec<abi::Reservation>, abi::Error> {
+        for rsvp in rsvps.iter_mut() {
+            validate_new(rsvp)?;
+        }
+
+        self.insert_batch(&mut rsvps).await?;
+        Ok(rsvps)
+    }
+
+    async fn reserve_series(
+        &self,
+        mut rsvp: abi::Reservation,
+        rule: abi::RecurrenceRule,
+    ) -> Result<Vec<abi::Reservation>, abi::Error> {
+        validate_new(&mut rsvp)?;
+        let mut rsvps = rule.expand(&rsvp)?;
+
+        let series_id: abi::SeriesId =
+            sqlx::query_scalar("SELECT nextval('rsvp.reservation_series_id_seq')")
+                .fetch_one(&self.pool)
+                .await?;
+        for rsvp in rsvps.iter_mut() {
+            rsvp.series_id = series_id;
+        }
+
+        self.insert_batch(&mut rsvps).await?;
+        Ok(rsvps)
+    }
+
+    async fn update_series(
+        &self,
+        request: abi::UpdateSeriesRequest,
+    ) -> Result<Vec<abi::Reservation>, abi::Error> {
+        request.validate()?;
+        let scope = abi::SeriesScope::from_i32(request.scope).unwrap();
+        let rsvp = self.get(request.id).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let mut query = QueryBuilder::new("SELECT * FROM rsvp.reservations WHERE ");
+        push_scope(&mut query, &rsvp, scope);
+        query.push(" ORDER BY lower(timespan) FOR UPDATE");
+        let mut rsvps: Vec<abi::Reservation> = query.build_query_as().fetch_all(&mut tx).await?;
+        if rsvps.is_empty() {
+            return Err(abi::Error::RowNotFound);
+        }
+
+        // the occurrences are moved by the offsets of the given one
+        let start = convert_to_utc_time(rsvp.start.as_ref().unwrap());
+        let end = convert_to_utc_time(rsvp.end.as_ref().unwrap());
+        let start_delta = request
+            .start
+            .as_ref()
+            .map_or_else(Duration::zero, |t| convert_to_utc_time(t) - start);
+        let end_delta = request
+            .end
+            .as_ref()
+            .map_or_else(Duration::zero, |t| convert_to_utc_time(t) - end);
+        for r in rsvps.iter_mut() {
+            let start = convert_to_utc_time(r.start.as_ref().unwrap()) + start_delta;
+            let end = convert_to_utc_time(r.end.as_ref().unwrap()) + end_delta;
+            if start >= end {
+                return Err(abi::Error::InvalidTime);
+            }
+            r.start = Some(convert_to_timestamp(&start));
+            r.end = Some(convert_to_timestamp(&end));
+        }
+
+        // moved later from the last one, so that an occurrence never runs into the next one
+        // which is not moved yet
+        let mut order: Vec<_> = (0..rsvps.len()).collect();
+        if start_delta > Duration::zero() {
+            order.reverse();
+        }
+        let mut conflicts = vec![];
+        for i in order {
+            let mut savepoint = tx.begin().await?;
+            let ret = sqlx::query_as(
+                "UPDATE rsvp.reservations SET timespan = $1, note = COALESCE($2, note) WHERE id = $3 RETURNING *",
+            )
+            .bind(rsvps[i].get_timespan())
+            .bind(&request.note)
+            .bind(rsvps[i].id)
+            .fetch_one(&mut savepoint)
+            .await;
+            match ret.map_err(abi::Error::from) {
+                Ok(updated) => {
+                    savepoint.commit().await?;
+                    rsvps[i] = updated;
+                }
+                Err(e @ abi::Error::ConflictReservation(_)) => {
+                    savepoint.rollback().await?;
+                    match Self::with_conflicts(&mut tx, e, &rsvps[i]).await {
+                        abi::Error::ConflictReservation(info) => conflicts.push((i, info.into())),
+                        e => return Err(e),
+                    }
+                }
+                Err(abi::Error::CapacityExceeded(conflict)) => {
+                    savepoint.rollback().await?;
+                    conflicts.push((i, conflict.into()));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        if conflicts.is_empty() {
+            tx.commit().await?;
+            return Ok(rsvps);
+        }
+        tx.rollback().await?;
+        conflicts.sort_by_key(|(i, _)| *i);
+        Err(abi::Error::BatchConflict(conflicts))
+    }
+
+    async fn cancel_series(
+        &self,
+        request: abi::CancelSeriesRequest,
+    ) -> Result<Vec<abi::Reservation>, abi::Error> {
+        request.validate()?;
+        let scope = abi::SeriesScope::from_i32(request.scope).unwrap();
+        let rsvp = self.get(request.id).await?;
+
+        let mut query = QueryBuilder::new("DELETE FROM rsvp.reservations WHERE ");
+        push_scope(&mut query, &rsvp, scope);
+        query.push(" RETURNING *");
+        let mut rsvps: Vec<abi::Reservation> = query.build_query_as().fetch_all(&self.pool).await?;
+        if rsvps.is_empty() {
+            return Err(abi::Error::RowNotFound);
+        }
+        rsvps.sort_by_key(|r| convert_to_utc_time(r.start.as_ref().unwrap()));
+        Ok(rsvps)
+    }
+
+    async fn reserve_in_pool(
+        &self,
+        rsvp: abi::Reservation,
+        pool: abi::ResourcePool,
+        dry_run: bool,
+    ) -> Result<abi::Reservation, abi::Error> {
+        pool.validate()?;
+        let kind = abi::AllocationStrategy::from_i32(pool.strategy).unwrap();
+        self.reserve_with(rsvp, &pool.r#type, strategy(kind), dry_run)
+            .await
+    }
+
+    async fn check(&self, mut rsvp: abi::Reservation) -> Result<abi::Reservation, abi::Error> {
+        validate_new(&mut rsvp)?;
+
+        // the insert is tested against the exclusion constraint and the capacity, then rolled back
+        let mut tx = self.pool.begin().await?;
+        let ret = insert(&mut tx, &rsvp).await;
+        tx.rollback().await?;
+
+        match ret {
+            Ok(_) => Ok(rsvp),
+            Err(e) => Err(Self::with_conflicts(&self.pool, e, &rsvp).await),
+        }
+    }
+
     /// change reservation status (if current status is pending, change it to confirmed)
     async fn change_status(&self, id: abi::ReservationId) -> Result<abi::Reservation, abi::Error> {
         id.validate()?;
@@ -61,15 +221,32 @@ impl Rsvp for ReservationManager {
 
         Ok(rsvp)
     }
-    /// delete reservation
-    async fn delete(&self, id: abi::ReservationId) -> Result<(), abi::Error> {
+    /// confirm the pending reservation and change its note in one write
+    async fn confirm_with_note(
+        &self,
+        id: abi::ReservationId,
+        note: String,
+    ) -> Result<abi::Reservation, abi::Error> {
+        id.validate()?;
+        let rsvp = sqlx::query_as(
+            "UPDATE rsvp.reservations SET status = 'confirmed', note = $1 WHERE id = $2 AND status = 'pending' RETURNING *",
+        )
+        .bind(note)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(rsvp)
+    }
+    /// delete reservation, return the deleted one
+    async fn delete(&self, id: abi::ReservationId) -> Result<abi::Reservation, abi::Error> {
         id.validate()?;
-        sqlx::query("DELETE FROM rsvp.reservations WHERE id= $1")
+        let rsvp = sqlx::query_as("DELETE FROM rsvp.reservations WHERE id= $1 RETURNING *")
             .bind(id)
-            .execute(&self.pool)
+            .fetch_one(&self.pool)
             .await?;
 
-        Ok(())
+        Ok(rsvp)
     }
     /// get reservation by id
     async fn get(&self, id: abi::ReservationId) -> Result<abi::Reservation, abi::Error> {
@@ -81,29 +258,403 @@ impl Rsvp for ReservationManager {
 
         Ok(rsvp)
     }
-    /// query reservations
     async fn query(
         &self,
         query: abi::ReservationQuery,
     ) -> Result<Vec<abi::Reservation>, abi::Error> {
-        let sql = query.to_sql();
-        let rsvps = sqlx::query_as(&sql).fetch_all(&self.pool).await?;
+        let (_, mut stream) = self.query_stream(query).await?;
+        let mut rsvps = vec![];
+        while let Some(rsvp) = stream.next().await {
+            rsvps.push(rsvp?);
+        }
         Ok(rsvps)
     }
 
+    async fn query_stream(
+        &self,
+        mut query: abi::ReservationQuery,
+    ) -> Result<
+        (
+            abi::FilterPager,
+            ReceiverStream<Result<abi::Reservation, abi::Error>>,
+        ),
+        abi::Error,
+    > {
+        query.normalize()?;
+        let pager = if query.is_paged() {
+            let total: i64 = query
+                .to_count_query()
+                .build()
+                .fetch_one(&self.pool)
+                .await?
+                .get(0);
+            query.get_pager(total)
+        } else {
+            abi::FilterPager::default()
+        };
+
+        let mut query = query.to_query();
+        let pool = self.pool.clone();
+        let (tx, rx) = mpsc::channel(CHANNEL_SIZE);
+
+        tokio::spawn(async move {
+            let mut rsvps = query.build_query_as().fetch(&pool);
+            while let Some(ret) = rsvps.next().await {
+                // stop fetching if the receiver is dropped
+                if tx.send(ret.map_err(abi::Error::from)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok((pager, ReceiverStream::new(rx)))
+    }
+
     async fn filter(
         &self,
         mut filter: abi::ReservationFilter,
     ) -> Result<(abi::FilterPager, Vec<abi::Reservation>), abi::Error> {
         // filter reservations by user_id,resource_id,status,and order by id
         filter.normalize()?;
-        let sql = filter.to_sql();
+        let mut query = filter.to_query();
 
-        let rsvps: Vec<abi::Reservation> = sqlx::query_as(&sql).fetch_all(&self.pool).await?;
-        let mut rsvps = rsvps.into_iter().collect();
-        let pager = filter.get_pager(&mut rsvps);
+        let rows = query.build().fetch_all(&self.pool).await?;
+        let mut total = None;
+        let mut rsvps = VecDeque::with_capacity(rows.len());
+        for row in rows {
+            if filter.with_total {
+                total = Some(row.get("total"));
+            }
+            // the reservation columns are null if the page is empty
+            let id: Option<abi::ReservationId> = row.get("id");
+            if id.is_some() {
+                rsvps.push_back(abi::Reservation::from_row(&row)?);
+            }
+        }
+
+        let mut pager = filter.get_pager(&mut rsvps);
+        pager.total = total;
         Ok((pager, rsvps.into_iter().collect()))
     }
+
+    async fn listen(
+        &self,
+        request: abi::ListenRequest,
+    ) -> Result<mpsc::Receiver<Result<abi::ListenResponse, abi::Error>>, abi::Error> {
+        request.validate()?;
+
+        // subscribe first, so that no change is missed between reading the last id and listening
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener.listen(RESERVATION_UPDATE_CHANNEL).await?;
+
+        let mut last_id = match request.since {
+            Some(since) => since,
+            None => self.get_last_change_id(&request.consumer).await?,
+        };
+
+        if !request.consumer.is_empty() {
+            // register the consumer, so that it could resume from here even if nothing is acknowledged
+            sqlx::query(
+                "INSERT INTO rsvp.reservation_consumers (name, last_change_id) VALUES ($1, $2) ON CONFLICT (name) DO NOTHING",
+            )
+            .bind(&request.consumer)
+            .bind(last_id)
+            .execute(&self.pool)
+            .await?;
+        }
+
+        let pool = self.pool.clone();
+        let (tx, rx) = mpsc::channel(CHANNEL_SIZE);
+
+        tokio::spawn(async move {
+            loop {
+                // replay all the changes after last_id
+                loop {
+                    match send_changes(&pool, &tx, last_id).await {
+                        Ok(Some(id)) => last_id = id,
+                        Ok(None) => break,
+                        // receiver is dropped or the error has been sent
+                        Err(_) => return,
+                    }
+                }
+
+                // wait for the next notification, stop if the receiver is dropped
+                tokio::select! {
+                    _ = tx.closed() => return,
+                    ret = listener.recv() => {
+                        if let Err(e) = ret {
+                            let _ = tx.send(Err(e.into())).await;
+                            return;
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+
+    async fn ack(&self, request: abi::AckRequest) -> Result<(), abi::Error> {
+        request.validate()?;
+        // a consumer could only acknowledge the changes it could have been sent
+        if request.id > self.get_latest_change_id().await? {
+            return Err(abi::Error::InvalidCursor(request.id));
+        }
+
+        // the offset of a consumer never goes backwards
+        sqlx::query(
+            "INSERT INTO rsvp.reservation_consumers (name, last_change_id) VALUES ($1, $2) ON CONFLICT (name) DO UPDATE SET last_change_id = GREATEST(rsvp.reservation_consumers.last_change_id, EXCLUDED.last_change_id), updated_at = now()",
+        )
+        .bind(request.consumer)
+        .bind(request.id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn availability(
+        &self,
+        mut query: abi::AvailabilityQuery,
+    ) -> Result<Vec<abi::FreeSlot>, abi::Error> {
+        query.normalize()?;
+
+        // the unknown resources are rejected, the inactive ones have no free slot
+        let active: HashMap<String, bool> =
+            sqlx::query_as("SELECT id, active FROM rsvp.resources WHERE id = ANY($1)")
+                .bind(&query.resource_ids)
+                .fetch_all(&self.pool)
+                .await?
+                .into_iter()
+                .collect();
+        if let Some(rid) = query
+            .resource_ids
+            .iter()
+            .find(|rid| !active.contains_key(*rid))
+        {
+            return Err(abi::Error::ResourceNotFound(rid.clone()));
+        }
+
+        let rows = query.to_query().build().fetch_all(&self.pool).await?;
+        let mut busy: HashMap<String, Vec<PgRange<DateTime<Utc>>>> = HashMap::new();
+        // the reservations of the resource itself, with its capacity
+        let mut own: HashMap<String, (i32, Vec<_>)> = HashMap::new();
+        for row in rows {
+            let rid: String = row.get("resource_id");
+            if row.get("own") {
+                let (_, rsvps) = own
+                    .entry(rid)
+                    .or_insert_with(|| (row.get("capacity"), vec![]));
+                rsvps.push((row.get("timespan"), row.get("quantity")));
+            } else {
+                busy.entry(rid).or_default().push(row.get("timespan"));
+            }
+        }
+        for (rid, (capacity, rsvps)) in own {
+            let full = query.full_spans(capacity, &rsvps);
+            busy.entry(rid).or_default().extend(full);
+        }
+
+        let slots = query
+            .resource_ids
+            .iter()
+            .filter(|rid| active[*rid])
+            .flat_map(|rid| query.free_slots(rid, busy.get(rid).map_or(&[], Vec::as_slice)))
+            .collect();
+        Ok(slots)
+    }
+
+    async fn suggest(
+        &self,
+        rsvp: &abi::Reservation,
+        alternatives: &[String],
+    ) -> Result<Vec<abi::FreeSlot>, abi::Error> {
+        rsvp.validate()?;
+
+        let start = convert_to_utc_time(rsvp.start.as_ref().unwrap());
+        let end = convert_to_utc_time(rsvp.end.as_ref().unwrap());
+        let duration = end - start;
+        let horizon = Duration::days(SUGGESTION_HORIZON_DAYS);
+
+        let query = abi::AvailabilityQueryBuilder::default()
+            .resource_ids(vec![rsvp.resource_id.clone()])
+            .start(convert_to_timestamp(&(start - horizon)))
+            .end(convert_to_timestamp(&(end + horizon)))
+            .min_duration(duration.num_seconds())
+            .quantity(rsvp.quantity)
+            .build()?;
+        let slots = self.availability(query).await?;
+        let mut suggestions = nearest_windows(&slots, start, duration);
+
+        let alternatives: Vec<String> = alternatives
+            .iter()
+            .filter(|rid| **rid != rsvp.resource_id)
+            .cloned()
+            .collect();
+        if !alternatives.is_empty() {
+            let query = abi::AvailabilityQueryBuilder::default()
+                .resource_ids(alternatives)
+                .start(convert_to_timestamp(&start))
+                .end(convert_to_timestamp(&end))
+                .quantity(rsvp.quantity)
+                .build()?;
+            // only the resources free for the whole window are suggested
+            let window = (
+                Some(convert_to_timestamp(&start)),
+                Some(convert_to_timestamp(&end)),
+            );
+            suggestions.extend(
+                self.availability(query)
+                    .await?
+                    .into_iter()
+                    .filter(|slot| (slot.start.clone(), slot.end.clone()) == window),
+            );
+        }
+
+        Ok(suggestions)
+    }
+}
+
+/// a new reservation made alone is out of any series, the series id is given by `reserve_series`
+pub(crate) fn validate_new(rsvp: &mut abi::Reservation) -> Result<(), abi::Error> {
+    rsvp.normalize()?;
+    if rsvp.series_id != 0 {
+        return Err(abi::Error::InvalidSeriesId(rsvp.series_id));
+    }
+    Ok(())
+}
+
+/// the occurrences in the scope of the given one, a reservation out of any series is a series
+/// of its own
+fn push_scope(
+    query: &mut QueryBuilder<'_, Postgres>,
+    rsvp: &abi::Reservation,
+    scope: abi::SeriesScope,
+) {
+    match scope {
+        abi::SeriesScope::One => {
+            query.push("id = ").push_bind(rsvp.id);
+        }
+        _ if rsvp.series_id == 0 => {
+            query.push("id = ").push_bind(rsvp.id);
+        }
+        abi::SeriesScope::Following => {
+            query
+                .push("series_id = ")
+                .push_bind(rsvp.series_id)
+                .push(" AND lower(timespan) >= ")
+                .push_bind(convert_to_utc_time(rsvp.start.as_ref().unwrap()));
+        }
+        abi::SeriesScope::All => {
+            query.push("series_id = ").push_bind(rsvp.series_id);
+        }
+    }
+}
+
+/// insert the reservation, return its id. The resource must be an active one in the catalog,
+/// and the reservations of a shared one must fit in its capacity
+pub(crate) async fn insert<'e, E>(
+    executor: E,
+    rsvp: &abi::Reservation,
+) -> Result<abi::ReservationId, abi::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let timespan: PgRange<DateTime<Utc>> = rsvp.get_timespan();
+
+    let status =
+        abi::ReservationStatus::from_i32(rsvp.status).unwrap_or(abi::ReservationStatus::Pending);
+    // generate a insert sql for the reservation. An unknown resource is rejected by the foreign key,
+    // an inactive one by the trigger
+    let id = sqlx::query(
+      "INSERT INTO rsvp.reservations (user_id,resource_id,timespan,note,status,series_id,quantity) VALUES ($1,$2,$3,$4,$5::rsvp.reservation_status,NULLIF($6,0),$7) RETURNING id")
+    .bind(rsvp.user_id.clone())
+    .bind(rsvp.resource_id.clone())
+    .bind(timespan)
+    .bind(rsvp.note.clone())
+    .bind(status.to_string())
+    .bind(rsvp.series_id)
+    .bind(rsvp.quantity)
+    .fetch_one(executor)
+    .await?
+    .get(0);
+
+    Ok(id)
+}
+
+/// the relation given by "rsvp.resource_family", or the same resource
+fn resource_relation(relation: &str) -> abi::ResourceRelation {
+    match relation {
+        "ancestor" => abi::ResourceRelation::Ancestor,
+        "descendant" => abi::ResourceRelation::Descendant,
+        _ => abi::ResourceRelation::Same,
+    }
+}
+
+/// the latest window of the duration starting before `start` and the earliest one starting
+/// after it, in the free slots of a resource order by start time
+fn nearest_windows(
+    slots: &[abi::FreeSlot],
+    start: DateTime<Utc>,
+    duration: Duration,
+) -> Vec<abi::FreeSlot> {
+    let mut before = None;
+    let mut after = None;
+    for slot in slots {
+        let slot_start = convert_to_utc_time(slot.start.as_ref().unwrap());
+        let slot_end = convert_to_utc_time(slot.end.as_ref().unwrap());
+
+        let latest = (slot_end - duration).min(start);
+        if latest >= slot_start && latest < start {
+            before = Some((&slot.resource_id, latest));
+        }
+        let earliest = slot_start.max(start);
+        if earliest > start && earliest + duration <= slot_end && after.is_none() {
+            after = Some((&slot.resource_id, earliest));
+        }
+    }
+
+    [before, after]
+        .into_iter()
+        .flatten()
+        .map(|(rid, start)| abi::FreeSlot {
+            resource_id: rid.clone(),
+            start: Some(convert_to_timestamp(&start)),
+            end: Some(convert_to_timestamp(&(start + duration))),
+        })
+        .collect()
+}
+
+/// send a batch of changes after `last_id` to the receiver, return the id of the last change sent
+async fn send_changes(
+    pool: &PgPool,
+    tx: &mpsc::Sender<Result<abi::ListenResponse, abi::Error>>,
+    last_id: abi::ChangeId,
+) -> Result<Option<abi::ChangeId>, ()> {
+    let rows = sqlx::query(
+        "SELECT id::bigint AS change_id, op, reservation_id AS id, user_id, status, resource_id, timespan, note, series_id, quantity FROM rsvp.reservation_changes WHERE id > $1 ORDER BY id LIMIT $2",
+    )
+    .bind(last_id)
+    .bind(CHANGES_BATCH_SIZE)
+    .fetch_all(pool)
+    .await;
+
+    let rows = match rows {
+        Ok(rows) => rows,
+        Err(e) => {
+            let _ = tx.send(Err(e.into())).await;
+            return Err(());
+        }
+    };
+
+    let mut last_id = None;
+    for row in rows {
+        let change = abi::ListenResponse::from_row(&row).map_err(abi::Error::from);
+        last_id = Some(row.get("change_id"));
+        tx.send(change).await.map_err(|_| ())?;
+    }
+
+    Ok(last_id)
 }
 
 impl ReservationManager {
@@ -111,6 +662,147 @@ impl ReservationManager {
         Self { pool }
     }
 
+    /// insert the reservations in one transaction. If any of them conflicts or exceeds the capacity,
+    /// none is inserted and all the conflicts are returned with their index in the slice
+    async fn insert_batch(&self, rsvps: &mut [abi::Reservation]) -> Result<(), abi::Error> {
+        let mut tx = self.pool.begin().await?;
+        let mut conflicts = vec![];
+        for (i, rsvp) in rsvps.iter_mut().enumerate() {
+            // each one is inserted in a savepoint, so that the rest are still tried after a conflict
+            let mut savepoint = tx.begin().await?;
+            match insert(&mut savepoint, rsvp).await {
+                Ok(id) => {
+                    savepoint.commit().await?;
+                    rsvp.id = id;
+                }
+                Err(e @ abi::Error::ConflictReservation(_)) => {
+                    savepoint.rollback().await?;
+                    match Self::with_conflicts(&mut tx, e, rsvp).await {
+                        abi::Error::ConflictReservation(info) => conflicts.push((i, info.into())),
+                        e => return Err(e),
+                    }
+                }
+                Err(abi::Error::CapacityExceeded(conflict)) => {
+                    savepoint.rollback().await?;
+                    conflicts.push((i, conflict.into()));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        if conflicts.is_empty() {
+            tx.commit().await?;
+            return Ok(());
+        }
+        tx.rollback().await?;
+
+        // the reservations of the batch are rolled back, their ids are unknown
+        let batch: HashSet<_> = rsvps.iter().map(|r| r.id).filter(|id| *id != 0).collect();
+        for (_, info) in conflicts.iter_mut() {
+            if let abi::BatchConflictInfo::Reservation(abi::ReservationConflictInfo::Parsed(
+                conflict,
+            )) = info
+            {
+                if batch.contains(&conflict.old.id) {
+                    conflict.old.id = 0;
+                }
+                for r in conflict
+                    .conflicts
+                    .iter_mut()
+                    .filter(|r| batch.contains(&r.id))
+                {
+                    r.id = 0;
+                }
+            }
+        }
+        Err(abi::Error::BatchConflict(conflicts))
+    }
+
+    /// fill all the existing reservations which the new one conflicts with, on its resource or on
+    /// an ancestor or a descendant of it. postgres only names the first one. the notes of the
+    /// reservations of other users are hidden
+    async fn with_conflicts<'e, E>(
+        executor: E,
+        err: abi::Error,
+        rsvp: &abi::Reservation,
+    ) -> abi::Error
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        let info = match err {
+            abi::Error::ConflictReservation(info) => info,
+            err => return err,
+        };
+
+        // the existing ones may have been changed since then, it's the best effort
+        let rows = sqlx::query(
+            "SELECT r.*, COALESCE(f.relation, 'same') AS relation FROM rsvp.reservations r LEFT JOIN rsvp.resource_family($1) f ON f.id = r.resource_id WHERE (r.resource_id = $1 OR f.id IS NOT NULL) AND r.timespan && $2 AND r.id <> $3 ORDER BY lower(r.timespan)",
+        )
+        .bind(&rsvp.resource_id)
+        .bind(rsvp.get_timespan())
+        // a moved reservation doesn't conflict with itself
+        .bind(rsvp.id)
+        .fetch_all(executor)
+        .await;
+
+        // the conflict is still reported if they couldn't be read, only without them
+        let rows = match rows {
+            Ok(rows) => rows,
+            Err(_) => return abi::Error::ConflictReservation(info),
+        };
+        let mut relations = HashMap::new();
+        let conflicts: Vec<abi::Reservation> = rows
+            .iter()
+            .filter_map(|row| {
+                let rsvp = abi::Reservation::from_row(row).ok()?;
+                let relation = resource_relation(row.try_get("relation").ok()?);
+                relations.insert(rsvp.resource_id.clone(), relation);
+                Some(rsvp)
+            })
+            .collect();
+
+        let mut conflict = match info {
+            abi::ReservationConflictInfo::Parsed(conflict) => conflict,
+            // the detail couldn't be parsed so the one postgres named is unknown, report the
+            // earliest of the overlapping reservations instead
+            info => match conflicts.first() {
+                Some(old) => Box::new(abi::ReservationConflict {
+                    new: rsvp.into(),
+                    old: old.into(),
+                    relation: abi::ResourceRelation::Unknown,
+                    conflicts: vec![],
+                    suggestions: vec![],
+                }),
+                None => return abi::Error::ConflictReservation(info),
+            },
+        };
+
+        if let Some(relation) = relations.get(&conflict.old.rid) {
+            conflict.relation = *relation;
+        }
+
+        conflict.old.id = conflicts
+            .iter()
+            .map(abi::ReservationWindow::from)
+            .find(|w| {
+                (&w.rid, w.start, w.end)
+                    == (&conflict.old.rid, conflict.old.start, conflict.old.end)
+            })
+            .map(|w| w.id)
+            .unwrap_or_default();
+        conflict.conflicts = conflicts
+            .into_iter()
+            .map(|mut r| {
+                if r.user_id != rsvp.user_id {
+                    r.note.clear();
+                }
+                r
+            })
+            .collect();
+
+        abi::Error::ConflictReservation(abi::ReservationConflictInfo::Parsed(conflict))
+    }
+
     pub async fn from_config(config: &abi::DBConfig) -> Result<Self, abi::Error> {
         let url = config.url();
         let pool = PgPoolOptions::default()
@@ -119,17 +811,48 @@ impl ReservationManager {
             .await?;
         Ok(Self::new(pool))
     }
+
+    /// the last acknowledged change of the consumer, or the latest change if the consumer is unknown
+    async fn get_last_change_id(&self, consumer: &str) -> Result<abi::ChangeId, abi::Error> {
+        if !consumer.is_empty() {
+            let id = sqlx::query_scalar(
+                "SELECT last_change_id FROM rsvp.reservation_consumers WHERE name = $1",
+            )
+            .bind(consumer)
+            .fetch_optional(&self.pool)
+            .await?;
+            if let Some(id) = id {
+                return Ok(id);
+            }
+        }
+
+        self.get_latest_change_id().await
+    }
+
+    /// the id of the latest change, 0 if there's none
+    async fn get_latest_change_id(&self) -> Result<abi::ChangeId, abi::Error> {
+        let id =
+            sqlx::query_scalar("SELECT COALESCE(MAX(id), 0)::bigint FROM rsvp.reservation_changes")
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(id)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use abi::{
-        Reservation, ReservationConflict, ReservationConflictInfo, ReservationFilterBuilder,
-        ReservationQueryBuilder, ReservationWindow,
+        AvailabilityQueryBuilder, BatchConflictInfo, Reservation, ReservationConflict,
+        ReservationConflictInfo, ReservationFilterBuilder, ReservationQueryBuilder,
+        ReservationWindow,
     };
     use prost_types::Timestamp;
+    use sqlx::{Postgres, Transaction};
+    use std::time::Duration;
+    use tokio::time::timeout;
 
     use super::*;
+    use crate::Catalog;
 
     #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
     async fn reserve_should_work_for_valid_window() {
@@ -139,7 +862,7 @@ mod tests {
 
     #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
     async fn reserve_conflict_reservation_should_reject() {
-        let (_rsvp, manager) = make_chalanzi_reservation(migrate_pool.clone()).await;
+        let (rsvp, manager) = make_chalanzi_reservation(migrate_pool.clone()).await;
         let rsvp2 = abi::Reservation::new_pending(
             "wanerId",
             "ocean-view-room-713",
@@ -150,25 +873,511 @@ mod tests {
 
         let err = manager.reserve(rsvp2).await.unwrap_err();
 
-        let info = ReservationConflictInfo::Parsed(ReservationConflict {
+        let info = ReservationConflictInfo::Parsed(Box::new(ReservationConflict {
             new: ReservationWindow {
+                id: 0,
                 rid: "ocean-view-room-713".to_string(),
-                start: "2022-12-26T15:00:00-0700".parse().unwrap(),
-                end: "2022-12-30T12:00:00-0700".parse().unwrap(),
+                start: Some("2022-12-26T15:00:00-0700".parse().unwrap()),
+                end: Some("2022-12-30T12:00:00-0700".parse().unwrap()),
             },
             old: ReservationWindow {
+                id: rsvp.id,
                 rid: "ocean-view-room-713".to_string(),
-                start: "2022-12-25T15:00:00-0700".parse().unwrap(),
-                end: "2022-12-28T12:00:00-0700".parse().unwrap(),
+                start: Some("2022-12-25T15:00:00-0700".parse().unwrap()),
+                end: Some("2022-12-28T12:00:00-0700".parse().unwrap()),
             },
-        });
+            relation: abi::ResourceRelation::Same,
+            // the note of other user's reservation is hidden
+            conflicts: vec![abi::Reservation {
+                note: "".to_string(),
+                ..rsvp
+            }],
+            suggestions: vec![],
+        }));
 
         assert_eq!(err, abi::Error::ConflictReservation(info));
     }
 
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn reserve_conflict_should_report_all_overlapping_reservations() {
+        let manager = new_manager(migrate_pool.clone()).await;
+        let mut ids = vec![];
+        for (uid, start, end) in [
+            (
+                "wanerId",
+                "2022-12-21T15:00:00-0700",
+                "2022-12-22T12:00:00-0700",
+            ),
+            (
+                "chalanziId",
+                "2022-12-23T15:00:00-0700",
+                "2022-12-24T12:00:00-0700",
+            ),
+            (
+                "tyrId",
+                "2022-12-25T15:00:00-0700",
+                "2022-12-26T12:00:00-0700",
+            ),
+        ] {
+            let rsvp = abi::Reservation::new_pending(
+                uid,
+                "ocean-view-room-713",
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "hello.",
+            );
+            ids.push(manager.reserve(rsvp).await.unwrap().id);
+        }
+        manager.change_status(ids[2]).await.unwrap();
+
+        let rsvp = abi::Reservation::new_pending(
+            "chalanziId",
+            "ocean-view-room-713",
+            "2022-12-20T15:00:00-0700".parse().unwrap(),
+            "2022-12-30T12:00:00-0700".parse().unwrap(),
+            "hello.",
+        );
+        let err = manager.reserve(rsvp).await.unwrap_err();
+        let conflict = match err {
+            abi::Error::ConflictReservation(ReservationConflictInfo::Parsed(conflict)) => conflict,
+            err => panic!("expect conflict reservation error, got {:?}", err),
+        };
+
+        assert!(ids.contains(&conflict.old.id));
+        let conflicts = conflict.conflicts;
+        assert_eq!(conflicts.iter().map(|r| r.id).collect::<Vec<_>>(), ids);
+        let users: Vec<_> = conflicts.iter().map(|r| r.user_id.as_str()).collect();
+        assert_eq!(users, ["wanerId", "chalanziId", "tyrId"]);
+        assert_eq!(conflicts[0].status, abi::ReservationStatus::Pending as i32);
+        assert_eq!(
+            conflicts[2].status,
+            abi::ReservationStatus::Confirmed as i32
+        );
+        // only the note of the caller's own reservation is visible
+        let notes: Vec<_> = conflicts.iter().map(|r| r.note.as_str()).collect();
+        assert_eq!(notes, ["", "hello.", ""]);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn reserve_conflict_should_query_rows_if_detail_is_unparsed() {
+        let (rsvp, _manager) = make_chalanzi_reservation(migrate_pool.clone()).await;
+        // postgres formats the detail with the abbreviation of the time zone, it can't be parsed
+        let pool = PgPoolOptions::new()
+            .after_connect(|conn, _| {
+                Box::pin(async move {
+                    sqlx::query("SET DateStyle = 'German'")
+                        .execute(conn)
+                        .await?;
+                    Ok(())
+                })
+            })
+            .connect_with(migrate_pool.connect_options().clone())
+            .await
+            .unwrap();
+        let manager = ReservationManager::new(pool);
+        let rsvp2 = abi::Reservation::new_pending(
+            "wanerId",
+            "ocean-view-room-713",
+            "2022-12-26T15:00:00-0700".parse().unwrap(),
+            "2022-12-30T12:00:00-0700".parse().unwrap(),
+            "hello.",
+        );
+
+        let err = manager.reserve(rsvp2.clone()).await.unwrap_err();
+
+        let info = ReservationConflictInfo::Parsed(Box::new(ReservationConflict {
+            new: (&rsvp2).into(),
+            old: (&rsvp).into(),
+            relation: abi::ResourceRelation::Same,
+            conflicts: vec![abi::Reservation {
+                note: "".to_string(),
+                ..rsvp
+            }],
+            suggestions: vec![],
+        }));
+        assert_eq!(err, abi::Error::ConflictReservation(info));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn reserve_many_should_make_all_reservations() {
+        let manager = new_manager(migrate_pool.clone()).await;
+        let rsvps = ["room-1", "projector-1", "parking-1"].map(|rid| {
+            abi::Reservation::new_pending(
+                "chalanziId",
+                rid,
+                "2022-12-25T15:00:00-0700".parse().unwrap(),
+                "2022-12-25T18:00:00-0700".parse().unwrap(),
+                "",
+            )
+        });
+        let made = manager.reserve_many(rsvps.to_vec()).await.unwrap();
+        assert_eq!(made.len(), 3);
+        for (rsvp, made) in rsvps.iter().zip(made) {
+            assert!(made.id != 0);
+            assert_eq!(manager.get(made.id).await.unwrap(), made);
+            assert_eq!(abi::Reservation { id: 0, ..made }, *rsvp);
+        }
+
+        let err = manager
+            .reserve_many(vec![rsvps[0].clone(), Reservation::default()])
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidUserId("".into()));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn reserve_many_should_report_all_conflicts() {
+        let (rsvp, manager) = make_chalanzi_reservation(migrate_pool.clone()).await;
+        let new = |rid: &str| {
+            abi::Reservation::new_pending(
+                "wanerId",
+                rid,
+                "2022-12-26T15:00:00-0700".parse().unwrap(),
+                "2022-12-27T12:00:00-0700".parse().unwrap(),
+                "",
+            )
+        };
+        let rsvps = vec![
+            new("projector-1"),
+            new("ocean-view-room-713"),
+            new("parking-1"),
+            new("parking-1"),
+        ];
+        let err = manager.reserve_many(rsvps).await.unwrap_err();
+        let conflicts = match err {
+            abi::Error::BatchConflict(conflicts) => conflicts,
+            err => panic!("unexpected error: {:?}", err),
+        };
+        let conflicts: Vec<_> = conflicts
+            .into_iter()
+            .map(|(i, info)| match info {
+                BatchConflictInfo::Reservation(ReservationConflictInfo::Parsed(conflict)) => {
+                    (i, conflict)
+                }
+                info => panic!("unparsed conflict: {}", info),
+            })
+            .collect();
+        assert_eq!(conflicts.len(), 2);
+
+        // with the existing reservation
+        assert_eq!(conflicts[0].0, 1);
+        assert_eq!(conflicts[0].1.old.id, rsvp.id);
+        assert_eq!(conflicts[0].1.conflicts.len(), 1);
+        // with an earlier one of the batch, which is rolled back
+        assert_eq!(conflicts[1].0, 3);
+        assert_eq!(conflicts[1].1.old.id, 0);
+        assert_eq!(conflicts[1].1.old.rid, "parking-1");
+        assert_eq!(conflicts[1].1.conflicts[0].id, 0);
+
+        // none of the batch is made
+        let count: i64 = sqlx::query_scalar("SELECT count(*) FROM rsvp.reservations")
+            .fetch_one(&migrate_pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+
+        // the capacity is checked for every reservation of the batch too
+        let mut over = new("projector-1");
+        over.quantity = 2;
+        let rsvps = vec![over, new("ocean-view-room-713")];
+        let err = manager.reserve_many(rsvps).await.unwrap_err();
+        let conflicts = match err {
+            abi::Error::BatchConflict(conflicts) => conflicts,
+            err => panic!("unexpected error: {:?}", err),
+        };
+        assert_eq!(conflicts.len(), 2);
+        match &conflicts[0] {
+            (0, BatchConflictInfo::Capacity(conflict)) => {
+                assert_eq!((conflict.quantity, conflict.capacity), (2, 1))
+            }
+            conflict => panic!("unexpected conflict: {:?}", conflict),
+        }
+        assert_eq!(conflicts[1].0, 1);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn reserve_series_should_make_all_occurrences_or_none() {
+        let manager = new_manager(migrate_pool.clone()).await;
+        let rsvps = manager
+            .reserve_series(standup("room-1"), daily_rule(3))
+            .await
+            .unwrap();
+        assert_eq!(rsvps.len(), 3);
+        let series_id = rsvps[0].series_id;
+        assert!(series_id != 0);
+        for rsvp in &rsvps {
+            assert_eq!(rsvp.series_id, series_id);
+            assert_eq!(manager.get(rsvp.id).await.unwrap(), *rsvp);
+        }
+        assert_eq!(
+            rsvps[2].start.clone().unwrap().to_string(),
+            "2022-12-27T10:00:00Z"
+        );
+
+        // a new series on another resource, the third occurrence conflicts with a reservation
+        let mut rsvp = standup("room-2");
+        rsvp.start = Some("2022-12-27T10:30:00Z".parse().unwrap());
+        rsvp.end = Some("2022-12-27T12:00:00Z".parse().unwrap());
+        let existing = manager.reserve(rsvp).await.unwrap();
+        let err = manager
+            .reserve_series(standup("room-2"), daily_rule(4))
+            .await
+            .unwrap_err();
+        match err {
+            abi::Error::BatchConflict(conflicts) => {
+                assert_eq!(conflicts.len(), 1);
+                assert_eq!(conflicts[0].0, 2);
+                match &conflicts[0].1 {
+                    BatchConflictInfo::Reservation(ReservationConflictInfo::Parsed(conflict)) => {
+                        assert_eq!(conflict.old.id, existing.id)
+                    }
+                    info => panic!("unparsed conflict: {}", info),
+                }
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+        let count: i64 = sqlx::query_scalar("SELECT count(*) FROM rsvp.reservations")
+            .fetch_one(&migrate_pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 4);
+
+        // the series id is only given by reserve_series
+        let err = manager
+            .reserve(abi::Reservation {
+                series_id,
+                ..standup("room-3")
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidSeriesId(series_id));
+        let err = manager
+            .reserve_series(standup("room-3"), abi::RecurrenceRule::default())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::InvalidRecurrence("unknown frequency".into())
+        );
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn update_series_should_move_occurrences_in_scope() {
+        let manager = new_manager(migrate_pool.clone()).await;
+        let rsvps = manager
+            .reserve_series(standup("room-1"), daily_rule(4))
+            .await
+            .unwrap();
+
+        // the second one and the following ones are 30 minutes later
+        let updated = manager
+            .update_series(abi::UpdateSeriesRequest {
+                id: rsvps[1].id,
+                scope: abi::SeriesScope::Following as i32,
+                note: Some("later".into()),
+                start: Some("2022-12-26T10:30:00Z".parse().unwrap()),
+                end: Some("2022-12-26T11:30:00Z".parse().unwrap()),
+            })
+            .await
+            .unwrap();
+        let ids: Vec<_> = updated.iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![rsvps[1].id, rsvps[2].id, rsvps[3].id]);
+        assert!(updated.iter().all(|r| r.note == "later"));
+        assert_eq!(
+            updated[2].start.clone().unwrap().to_string(),
+            "2022-12-28T10:30:00Z"
+        );
+        assert_eq!(manager.get(rsvps[0].id).await.unwrap(), rsvps[0]);
+
+        // all of them are a day later, each one takes the place of the next one
+        let updated = manager
+            .update_series(abi::UpdateSeriesRequest {
+                id: rsvps[0].id,
+                scope: abi::SeriesScope::All as i32,
+                start: Some("2022-12-26T10:00:00Z".parse().unwrap()),
+                end: Some("2022-12-26T11:00:00Z".parse().unwrap()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(updated.len(), 4);
+        assert_eq!(updated[0].note, "standup");
+        assert_eq!(
+            updated[3].end.clone().unwrap().to_string(),
+            "2022-12-29T11:30:00Z"
+        );
+
+        // the last one conflicts, none is moved
+        let mut rsvp = standup("room-1");
+        rsvp.start = Some("2022-12-29T12:00:00Z".parse().unwrap());
+        rsvp.end = Some("2022-12-29T13:00:00Z".parse().unwrap());
+        let existing = manager.reserve(rsvp).await.unwrap();
+        let err = manager
+            .update_series(abi::UpdateSeriesRequest {
+                id: rsvps[0].id,
+                scope: abi::SeriesScope::All as i32,
+                end: Some("2022-12-26T13:00:00Z".parse().unwrap()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        match err {
+            abi::Error::BatchConflict(conflicts) => {
+                assert_eq!(conflicts.len(), 1);
+                assert_eq!(conflicts[0].0, 3);
+                match &conflicts[0].1 {
+                    BatchConflictInfo::Reservation(ReservationConflictInfo::Parsed(conflict)) => {
+                        assert_eq!(conflict.old.id, existing.id);
+                        assert_eq!(conflict.conflicts.len(), 1);
+                    }
+                    info => panic!("unparsed conflict: {}", info),
+                }
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+        assert_eq!(manager.get(rsvps[3].id).await.unwrap(), updated[3]);
+
+        let err = manager
+            .update_series(abi::UpdateSeriesRequest {
+                id: rsvps[0].id,
+                scope: abi::SeriesScope::One as i32,
+                start: Some("2022-12-26T12:00:00Z".parse().unwrap()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidTime);
+        let err = manager
+            .update_series(abi::UpdateSeriesRequest {
+                id: rsvps[0].id,
+                scope: 3,
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidScope(3));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn update_series_should_report_capacity_conflicts() {
+        let manager = new_manager(migrate_pool.clone()).await;
+        let resource = abi::Resource {
+            capacity: 2,
+            ..abi::Resource::new("desk-zone-a", "desk")
+        };
+        manager.create_resource(resource).await.unwrap();
+        let mut rsvp = standup("desk-zone-a");
+        rsvp.quantity = 2;
+        let rsvps = manager.reserve_series(rsvp, daily_rule(3)).await.unwrap();
+        let mut rsvp = standup("desk-zone-a");
+        rsvp.start = Some("2022-12-27T12:00:00Z".parse().unwrap());
+        rsvp.end = Some("2022-12-27T13:00:00Z".parse().unwrap());
+        manager.reserve(rsvp).await.unwrap();
+
+        // the last one runs into the desk taken in the afternoon
+        let err = manager
+            .update_series(abi::UpdateSeriesRequest {
+                id: rsvps[0].id,
+                scope: abi::SeriesScope::All as i32,
+                end: Some("2022-12-25T13:00:00Z".parse().unwrap()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        match err {
+            abi::Error::BatchConflict(conflicts) => match &conflicts[..] {
+                [(2, BatchConflictInfo::Capacity(conflict))] => {
+                    assert_eq!(conflict.reserved, 1);
+                }
+                conflicts => panic!("unexpected conflicts: {:?}", conflicts),
+            },
+            err => panic!("unexpected error: {:?}", err),
+        }
+        assert_eq!(manager.get(rsvps[2].id).await.unwrap(), rsvps[2]);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn cancel_series_should_delete_occurrences_in_scope() {
+        let manager = new_manager(migrate_pool.clone()).await;
+        let rsvps = manager
+            .reserve_series(standup("room-1"), daily_rule(4))
+            .await
+            .unwrap();
+        let cancel = |id, scope: abi::SeriesScope| {
+            manager.cancel_series(abi::CancelSeriesRequest {
+                id,
+                scope: scope as i32,
+            })
+        };
+
+        let canceled = cancel(rsvps[2].id, abi::SeriesScope::Following)
+            .await
+            .unwrap();
+        assert_eq!(canceled, rsvps[2..]);
+        let canceled = cancel(rsvps[0].id, abi::SeriesScope::One).await.unwrap();
+        assert_eq!(canceled, rsvps[..1]);
+        let canceled = cancel(rsvps[1].id, abi::SeriesScope::All).await.unwrap();
+        assert_eq!(canceled, rsvps[1..2]);
+        let err = cancel(rsvps[1].id, abi::SeriesScope::All)
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::RowNotFound);
+
+        // a reservation out of any series is a series of its own
+        let (rsvp, manager) = make_chalanzi_reservation(migrate_pool.clone()).await;
+        let canceled = manager
+            .cancel_series(abi::CancelSeriesRequest {
+                id: rsvp.id,
+                scope: abi::SeriesScope::All as i32,
+            })
+            .await
+            .unwrap();
+        assert_eq!(canceled, vec![rsvp]);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn check_should_not_make_reservation() {
+        let (rsvp, manager) = make_chalanzi_reservation(migrate_pool.clone()).await;
+        let rsvp2 = abi::Reservation::new_pending(
+            "wanerId",
+            "ixia-test-1",
+            "2022-12-26T15:00:00-0700".parse().unwrap(),
+            "2022-12-30T12:00:00-0700".parse().unwrap(),
+            "hello.",
+        );
+        assert_eq!(manager.check(rsvp2.clone()).await.unwrap(), rsvp2);
+        // nothing is left behind, the same window could still be reserved
+        let count: i64 = sqlx::query_scalar("SELECT count(*) FROM rsvp.reservations")
+            .fetch_one(&migrate_pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+        assert!(manager.reserve(rsvp2).await.unwrap().id != 0);
+
+        // the same conflict as reserve
+        let rsvp3 = abi::Reservation::new_pending(
+            "wanerId",
+            "ocean-view-room-713",
+            "2022-12-26T15:00:00-0700".parse().unwrap(),
+            "2022-12-30T12:00:00-0700".parse().unwrap(),
+            "hello.",
+        );
+        let err = manager.check(rsvp3.clone()).await.unwrap_err();
+        assert_eq!(err, manager.reserve(rsvp3).await.unwrap_err());
+        match err {
+            abi::Error::ConflictReservation(ReservationConflictInfo::Parsed(conflict)) => {
+                assert_eq!(conflict.old.id, rsvp.id)
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        let err = manager.check(Reservation::default()).await.unwrap_err();
+        assert_eq!(err, abi::Error::InvalidUserId("".into()));
+    }
+
     #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
     async fn reserve_should_reject_if_id_is_not_empty() {
-        let manager = ReservationManager::new(migrate_pool.clone());
+        let manager = new_manager(migrate_pool.clone()).await;
         let rsvp1 = abi::Reservation::new_pending(
             "chalanziId",
             "ocean-view-room-713",
@@ -188,8 +1397,14 @@ mod tests {
         println!("{:?}", err);
         if let abi::Error::ConflictReservation(abi::ReservationConflictInfo::Parsed(info)) = err {
             assert_eq!(info.old.rid, "ocean-view-room-713");
-            assert_eq!(info.old.start.to_rfc3339(), "2022-12-25T22:00:00+00:00");
-            assert_eq!(info.old.end.to_rfc3339(), "2022-12-28T19:00:00+00:00");
+            assert_eq!(
+                info.old.start.unwrap().to_rfc3339(),
+                "2022-12-25T22:00:00+00:00"
+            );
+            assert_eq!(
+                info.old.end.unwrap().to_rfc3339(),
+                "2022-12-28T19:00:00+00:00"
+            );
         } else {
             panic!("expect conflict reservation error");
         }
@@ -222,6 +1437,25 @@ mod tests {
         assert_eq!(rsvp.note, "hello world".to_string())
     }
 
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn confirm_with_note_should_change_both_or_neither() {
+        let (rsvp, manager) = make_waner_reservation(migrate_pool.clone()).await;
+        let rsvp = manager
+            .confirm_with_note(rsvp.id, "hello world".to_string())
+            .await
+            .unwrap();
+        assert_eq!(rsvp.status, abi::ReservationStatus::Confirmed as i32);
+        assert_eq!(rsvp.note, "hello world");
+
+        // not pending anymore, the note is kept
+        let err = manager
+            .confirm_with_note(rsvp.id, "hello again".to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::RowNotFound);
+        assert_eq!(manager.get(rsvp.id).await.unwrap().note, "hello world");
+    }
+
     #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
     async fn get_reservation_should_work() {
         let (rsvp, manager) = make_waner_reservation(migrate_pool.clone()).await;
@@ -232,9 +1466,12 @@ mod tests {
     #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
     async fn delete_reservation_should_work() {
         let (rsvp, manager) = make_waner_reservation(migrate_pool.clone()).await;
-        manager.delete(rsvp.id).await.unwrap();
+        let deleted = manager.delete(rsvp.id).await.unwrap();
+        assert_eq!(deleted, rsvp);
         let rsvp1 = manager.get(rsvp.id).await.unwrap_err();
         assert_eq!(rsvp1, abi::Error::RowNotFound);
+        let err = manager.delete(rsvp.id).await.unwrap_err();
+        assert_eq!(err, abi::Error::RowNotFound);
     }
 
     #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
@@ -287,6 +1524,139 @@ mod tests {
         assert_eq!(rsvps[0], rsvp)
     }
 
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn query_reservations_should_match_statuses() {
+        let (rsvp1, manager) = make_chalanzi_reservation(migrate_pool.clone()).await;
+        let (rsvp2, _) = make_waner_reservation(migrate_pool.clone()).await;
+        let rsvp2 = manager.change_status(rsvp2.id).await.unwrap();
+
+        // UNKNOWN matches all statuses
+        let query = ReservationQueryBuilder::default().build().unwrap();
+        let rsvps = manager.query(query).await.unwrap();
+        assert_eq!(rsvps, vec![rsvp1.clone(), rsvp2.clone()]);
+
+        let filter = ReservationFilterBuilder::default()
+            .statuses(vec![
+                abi::ReservationStatus::Pending as i32,
+                abi::ReservationStatus::Confirmed as i32,
+            ])
+            .build()
+            .unwrap();
+        let (_, rsvps) = manager.filter(filter).await.unwrap();
+        assert_eq!(rsvps, vec![rsvp1, rsvp2.clone()]);
+
+        let filter = ReservationFilterBuilder::default()
+            .statuses(vec![
+                abi::ReservationStatus::Confirmed as i32,
+                abi::ReservationStatus::Blocked as i32,
+            ])
+            .build()
+            .unwrap();
+        let (_, rsvps) = manager.filter(filter).await.unwrap();
+        assert_eq!(rsvps, vec![rsvp2]);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn query_reservations_should_match_window_by_mode() {
+        // 2022-12-25T15:00:00 ~ 2022-12-28T12:00:00
+        let (rsvp, manager) = make_chalanzi_reservation(migrate_pool.clone()).await;
+
+        let query = |mode: abi::ReservationMatchMode, start: &str, end: &str| {
+            ReservationQueryBuilder::default()
+                .start(start.parse::<Timestamp>().unwrap())
+                .end(end.parse::<Timestamp>().unwrap())
+                .match_mode(mode as i32)
+                .build()
+                .unwrap()
+        };
+
+        // the window only covers the end of the reservation
+        let (start, end) = ("2022-12-27T00:00:00-0700", "2023-01-03T00:00:00-0700");
+        let contained = query(abi::ReservationMatchMode::Contained, start, end);
+        let rsvps = manager.query(contained).await.unwrap();
+        assert!(rsvps.is_empty());
+
+        let overlapping = query(abi::ReservationMatchMode::Overlapping, start, end);
+        let rsvps = manager.query(overlapping).await.unwrap();
+        assert_eq!(rsvps, vec![rsvp.clone()]);
+
+        let starting = query(abi::ReservationMatchMode::StartingWithin, start, end);
+        let rsvps = manager.query(starting).await.unwrap();
+        assert!(rsvps.is_empty());
+
+        // the window only covers the start of the reservation
+        let (start, end) = ("2022-12-24T00:00:00-0700", "2022-12-26T00:00:00-0700");
+        let starting = query(abi::ReservationMatchMode::StartingWithin, start, end);
+        let rsvps = manager.query(starting).await.unwrap();
+        assert_eq!(rsvps, vec![rsvp]);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn query_reservations_should_be_paged() {
+        let manager = new_manager(migrate_pool.clone()).await;
+        for i in 0..25 {
+            let rsvp = abi::Reservation::new_pending(
+                "chalanziId",
+                format!("room-{}", i),
+                "2022-12-25T15:00:00-0700".parse().unwrap(),
+                "2022-12-28T12:00:00-0700".parse().unwrap(),
+                "",
+            );
+            manager.reserve(rsvp).await.unwrap();
+        }
+
+        let query = ReservationQueryBuilder::default()
+            .user_id("chalanziId")
+            .page(3)
+            .build()
+            .unwrap();
+        let (pager, stream) = manager.query_stream(query).await.unwrap();
+        let rsvps: Vec<_> = stream.map(Result::unwrap).collect().await;
+        assert_eq!(rsvps.len(), 5);
+        assert_eq!(rsvps[0].resource_id, "room-20");
+        assert_eq!(pager.prev, Some(2));
+        assert_eq!(pager.next, None);
+        assert_eq!(pager.total, Some(25));
+
+        // page_size 0 returns all
+        let query = ReservationQueryBuilder::default()
+            .user_id("chalanziId")
+            .page_size(0)
+            .build()
+            .unwrap();
+        let (pager, stream) = manager.query_stream(query).await.unwrap();
+        assert_eq!(stream.count().await, 25);
+        assert_eq!(pager, abi::FilterPager::default());
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn query_reservations_should_bind_quoted_ids() {
+        let (rsvp, manager) = make_reservation(
+            migrate_pool.clone(),
+            "o'brien",
+            "room 'a'",
+            "2022-12-25T15:00:00-0700",
+            "2022-12-28T12:00:00-0700",
+            "",
+        )
+        .await;
+
+        let query = ReservationQueryBuilder::default()
+            .user_id("o'brien")
+            .resource_id("room 'a'")
+            .build()
+            .unwrap();
+        let rsvps = manager.query(query).await.unwrap();
+        assert_eq!(rsvps, vec![rsvp.clone()]);
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("o'brien' OR '1'='1")
+            .build()
+            .unwrap();
+        let (_, rsvps) = manager.filter(filter).await.unwrap();
+        assert!(rsvps.is_empty());
+    }
+
     #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
     async fn filter_reservations_should_work() {
         let (rsvp, manager) = make_chalanzi_reservation(migrate_pool.clone()).await;
@@ -303,6 +1673,451 @@ mod tests {
         assert_eq!(rsvps[0], rsvp);
     }
 
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn listen_reservations_should_work() {
+        let manager = new_manager(migrate_pool.clone()).await;
+        let mut rx = manager.listen(abi::ListenRequest::default()).await.unwrap();
+
+        let (rsvp, manager) = make_waner_reservation(migrate_pool.clone()).await;
+        let confirmed = manager.change_status(rsvp.id).await.unwrap();
+        manager.delete(rsvp.id).await.unwrap();
+
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, abi::ReservationUpdateType::Create as i32);
+        assert_eq!(change.reservation, Some(rsvp));
+
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, abi::ReservationUpdateType::Update as i32);
+        assert_eq!(change.reservation, Some(confirmed.clone()));
+
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, abi::ReservationUpdateType::Delete as i32);
+        assert_eq!(change.reservation, Some(confirmed));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn listen_should_replay_changes_since_id() {
+        let (rsvp, manager) = make_waner_reservation(migrate_pool.clone()).await;
+        manager.change_status(rsvp.id).await.unwrap();
+
+        let request = abi::ListenRequest {
+            since: Some(0),
+            consumer: "".to_string(),
+        };
+        let mut rx = manager.listen(request).await.unwrap();
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, abi::ReservationUpdateType::Create as i32);
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, abi::ReservationUpdateType::Update as i32);
+
+        // switch to the live changes after replay
+        manager.delete(rsvp.id).await.unwrap();
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, abi::ReservationUpdateType::Delete as i32);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn listen_should_resume_from_consumer_offset() {
+        let manager = new_manager(migrate_pool.clone()).await;
+        let request = abi::ListenRequest {
+            since: None,
+            consumer: "billing".to_string(),
+        };
+        let mut rx = manager.listen(request.clone()).await.unwrap();
+
+        let (rsvp, manager) = make_waner_reservation(migrate_pool.clone()).await;
+        let change = rx.recv().await.unwrap().unwrap();
+        manager
+            .ack(abi::AckRequest {
+                consumer: "billing".to_string(),
+                id: change.id,
+            })
+            .await
+            .unwrap();
+        drop(rx);
+
+        // changes made while the consumer is away should be replayed
+        manager.change_status(rsvp.id).await.unwrap();
+        let mut rx = manager.listen(request).await.unwrap();
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, abi::ReservationUpdateType::Update as i32);
+        assert_eq!(change.reservation.unwrap().id, rsvp.id);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn filter_reservations_should_page_both_ways() {
+        let manager = new_manager(migrate_pool.clone()).await;
+        let mut ids = vec![];
+        for i in 0..25 {
+            let rsvp = abi::Reservation::new_pending(
+                "chalanziId",
+                format!("room-{}", i),
+                "2022-12-25T15:00:00-0700".parse().unwrap(),
+                "2022-12-28T12:00:00-0700".parse().unwrap(),
+                "",
+            );
+            ids.push(manager.reserve(rsvp).await.unwrap().id);
+        }
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("chalanziId")
+            .with_total(true)
+            .build()
+            .unwrap();
+        let (pager1, rsvps) = manager.filter(filter.clone()).await.unwrap();
+        assert_eq!(pager1.total, Some(25));
+        let page1: Vec<_> = rsvps.iter().map(|r| r.id).collect();
+        assert_eq!(page1, ids[0..10]);
+
+        let filter = filter.next_page(&pager1).unwrap();
+        let (pager2, rsvps) = manager.filter(filter.clone()).await.unwrap();
+        assert_eq!(rsvps[0].id, ids[10]);
+        assert_eq!(rsvps.len(), 10);
+
+        let filter = filter.prev_page(&pager2).unwrap();
+        let (pager, rsvps) = manager.filter(filter).await.unwrap();
+        let prev_page: Vec<_> = rsvps.iter().map(|r| r.id).collect();
+        assert_eq!(prev_page, page1);
+        assert_eq!(pager.prev, None);
+        assert_eq!(pager.next, pager1.next);
+        assert_eq!(pager.total, Some(25));
+
+        // an empty page still has the total
+        let filter = ReservationFilterBuilder::default()
+            .user_id("chalanziId")
+            .cursor(ids[24] + 1)
+            .with_total(true)
+            .build()
+            .unwrap();
+        let (pager, rsvps) = manager.filter(filter).await.unwrap();
+        assert!(rsvps.is_empty());
+        assert_eq!(pager.total, Some(25));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn availability_should_return_free_slots() {
+        let (_rsvp, manager) = make_chalanzi_reservation(migrate_pool.clone()).await;
+        let (rsvp, _) = make_reservation(
+            migrate_pool.clone(),
+            "wanerId",
+            "ocean-view-room-713",
+            "2022-12-29T15:00:00-0700",
+            "2022-12-30T12:00:00-0700",
+            "",
+        )
+        .await;
+        manager.change_status(rsvp.id).await.unwrap();
+
+        let query = AvailabilityQueryBuilder::default()
+            .resource_ids(vec![
+                "ocean-view-room-713".to_string(),
+                "ixia-test-1".to_string(),
+                "ocean-view-room-713".to_string(),
+            ])
+            .start("2022-12-24T00:00:00-0700".parse::<Timestamp>().unwrap())
+            .end("2023-01-01T00:00:00-0700".parse::<Timestamp>().unwrap())
+            .min_duration(3600)
+            .build()
+            .unwrap();
+        let slots = manager.availability(query).await.unwrap();
+        let slots: Vec<_> = slots
+            .iter()
+            .map(|slot| {
+                (
+                    slot.resource_id.as_str(),
+                    slot.start.clone().unwrap().to_string(),
+                    slot.end.clone().unwrap().to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            slots,
+            vec![
+                (
+                    "ocean-view-room-713",
+                    "2022-12-24T07:00:00Z".to_string(),
+                    "2022-12-25T22:00:00Z".to_string()
+                ),
+                (
+                    "ocean-view-room-713",
+                    "2022-12-28T19:00:00Z".to_string(),
+                    "2022-12-29T22:00:00Z".to_string()
+                ),
+                (
+                    "ocean-view-room-713",
+                    "2022-12-30T19:00:00Z".to_string(),
+                    "2023-01-01T07:00:00Z".to_string()
+                ),
+                (
+                    "ixia-test-1",
+                    "2022-12-24T07:00:00Z".to_string(),
+                    "2023-01-01T07:00:00Z".to_string()
+                ),
+            ]
+        );
+
+        // the search window is required
+        let query = abi::AvailabilityQuery {
+            resource_ids: vec!["ixia-test-1".to_string()],
+            ..Default::default()
+        };
+        let err = manager.availability(query).await.unwrap_err();
+        assert_eq!(err, abi::Error::InvalidTime);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn availability_should_check_resources_in_catalog() {
+        let manager = new_manager(migrate_pool.clone()).await;
+        manager.deactivate_resource("room-3".into()).await.unwrap();
+        let query = AvailabilityQueryBuilder::default()
+            .resource_ids(vec!["room-2".to_string(), "room-3".to_string()])
+            .start("2022-12-24T00:00:00-0700".parse::<Timestamp>().unwrap())
+            .end("2023-01-01T00:00:00-0700".parse::<Timestamp>().unwrap())
+            .build()
+            .unwrap();
+
+        // an inactive resource has no free slot
+        let slots = manager.availability(query.clone()).await.unwrap();
+        assert_eq!(slots.len(), 1);
+        assert_eq!(slots[0].resource_id, "room-2");
+
+        let query = abi::AvailabilityQuery {
+            resource_ids: vec!["room-2".to_string(), "room-404".to_string()],
+            ..query
+        };
+        let err = manager.availability(query).await.unwrap_err();
+        assert_eq!(err, abi::Error::ResourceNotFound("room-404".into()));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn ack_should_reject_change_not_made_yet() {
+        let (_rsvp, manager) = make_waner_reservation(migrate_pool.clone()).await;
+        let mut request = abi::AckRequest {
+            consumer: "billing".to_string(),
+            id: 2,
+        };
+        let err = manager.ack(request.clone()).await.unwrap_err();
+        assert_eq!(err, abi::Error::InvalidCursor(2));
+
+        request.id = 1;
+        manager.ack(request).await.unwrap();
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn listen_should_get_changes_committed_out_of_order() {
+        let manager = new_manager(migrate_pool.clone()).await;
+        let mut rx = manager.listen(abi::ListenRequest::default()).await.unwrap();
+
+        // the first reservation is made before the second one, but committed after it
+        let mut tx1 = migrate_pool.begin().await.unwrap();
+        let id1 = insert_reservation(&mut tx1, "room-1").await;
+        let mut tx2 = migrate_pool.begin().await.unwrap();
+        let id2 = insert_reservation(&mut tx2, "room-2").await;
+        assert!(id1 < id2);
+        tx2.commit().await.unwrap();
+        tx1.commit().await.unwrap();
+
+        let change1 = timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(change1.reservation.unwrap().id, id2);
+        let change2 = timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(change2.reservation.unwrap().id, id1);
+        assert!(change1.id < change2.id);
+    }
+
+    async fn insert_reservation(tx: &mut Transaction<'_, Postgres>, rid: &str) -> i64 {
+        let rsvp = abi::Reservation::new_pending(
+            "chalanziId",
+            rid,
+            "2022-12-25T15:00:00-0700".parse().unwrap(),
+            "2022-12-28T12:00:00-0700".parse().unwrap(),
+            "",
+        );
+        sqlx::query_scalar(
+            "INSERT INTO rsvp.reservations (user_id, resource_id, timespan, note) VALUES ($1, $2, $3, $4) RETURNING id",
+        )
+        .bind(&rsvp.user_id)
+        .bind(&rsvp.resource_id)
+        .bind(rsvp.get_timespan())
+        .bind(&rsvp.note)
+        .fetch_one(tx)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn availability_and_suggest_should_count_the_units_of_shared_resource() {
+        let manager = new_manager(migrate_pool.clone()).await;
+        let resource = abi::Resource {
+            capacity: 2,
+            ..abi::Resource::new("desk-zone-a", "desk")
+        };
+        manager.create_resource(resource).await.unwrap();
+        for (start, end) in [
+            ("2022-12-25T10:00:00Z", "2022-12-25T12:00:00Z"),
+            ("2022-12-25T11:00:00Z", "2022-12-25T13:00:00Z"),
+        ] {
+            let mut rsvp = standup("desk-zone-a");
+            rsvp.start = Some(start.parse().unwrap());
+            rsvp.end = Some(end.parse().unwrap());
+            manager.reserve(rsvp).await.unwrap();
+        }
+
+        let slots = |quantity: i32| {
+            let query = AvailabilityQueryBuilder::default()
+                .resource_ids(vec!["desk-zone-a".to_string()])
+                .start("2022-12-25T08:00:00Z".parse::<Timestamp>().unwrap())
+                .end("2022-12-25T18:00:00Z".parse::<Timestamp>().unwrap())
+                .quantity(quantity)
+                .build()
+                .unwrap();
+            let manager = &manager;
+            async move {
+                let slots = manager.availability(query).await.unwrap();
+                slots
+                    .iter()
+                    .map(|slot| slot.start.clone().unwrap().to_string())
+                    .collect::<Vec<_>>()
+            }
+        };
+        // both desks are only taken from 11:00 to 12:00
+        assert_eq!(
+            slots(0).await,
+            vec!["2022-12-25T08:00:00Z", "2022-12-25T12:00:00Z"]
+        );
+        assert_eq!(
+            slots(2).await,
+            vec!["2022-12-25T08:00:00Z", "2022-12-25T13:00:00Z"]
+        );
+
+        let mut rsvp = standup("desk-zone-a");
+        rsvp.start = Some("2022-12-25T11:00:00Z".parse().unwrap());
+        rsvp.end = Some("2022-12-25T12:00:00Z".parse().unwrap());
+        let suggestions = manager.suggest(&rsvp, &[]).await.unwrap();
+        let starts: Vec<_> = suggestions
+            .iter()
+            .map(|slot| slot.start.clone().unwrap().to_string())
+            .collect();
+        assert_eq!(starts, vec!["2022-12-25T10:00:00Z", "2022-12-25T12:00:00Z"]);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn suggest_should_return_nearest_windows_and_free_alternatives() {
+        let (_rsvp, manager) = make_chalanzi_reservation(migrate_pool.clone()).await;
+        for (rid, start, end) in [
+            (
+                "ocean-view-room-713",
+                "2022-12-29T15:00:00-0700",
+                "2022-12-30T12:00:00-0700",
+            ),
+            ("room-2", "2022-12-27T12:00:00Z", "2022-12-27T13:00:00Z"),
+        ] {
+            make_reservation(migrate_pool.clone(), "wanerId", rid, start, end, "").await;
+        }
+
+        let rsvp = abi::Reservation::new_pending(
+            "wanerId",
+            "ocean-view-room-713",
+            "2022-12-27T00:00:00Z".parse().unwrap(),
+            "2022-12-28T00:00:00Z".parse().unwrap(),
+            "",
+        );
+        let alternatives = [
+            "ocean-view-room-713".to_string(),
+            "ixia-test-1".to_string(),
+            "room-2".to_string(),
+        ];
+        let suggestions = manager.suggest(&rsvp, &alternatives).await.unwrap();
+        let suggestions: Vec<_> = suggestions
+            .iter()
+            .map(|slot| {
+                (
+                    slot.resource_id.as_str(),
+                    slot.start.clone().unwrap().to_string(),
+                    slot.end.clone().unwrap().to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            suggestions,
+            vec![
+                (
+                    "ocean-view-room-713",
+                    "2022-12-24T22:00:00Z".to_string(),
+                    "2022-12-25T22:00:00Z".to_string()
+                ),
+                (
+                    "ocean-view-room-713",
+                    "2022-12-28T19:00:00Z".to_string(),
+                    "2022-12-29T19:00:00Z".to_string()
+                ),
+                (
+                    "ixia-test-1",
+                    "2022-12-27T00:00:00Z".to_string(),
+                    "2022-12-28T00:00:00Z".to_string()
+                ),
+            ]
+        );
+
+        // the windows right next to the existing one
+        let rsvp = abi::Reservation::new_pending(
+            "wanerId",
+            "room-2",
+            "2022-12-27T12:30:00Z".parse().unwrap(),
+            "2022-12-27T13:30:00Z".parse().unwrap(),
+            "",
+        );
+        let suggestions = manager.suggest(&rsvp, &[]).await.unwrap();
+        let starts: Vec<_> = suggestions
+            .iter()
+            .map(|slot| slot.start.clone().unwrap().to_string())
+            .collect();
+        assert_eq!(starts, vec!["2022-12-27T11:00:00Z", "2022-12-27T13:00:00Z"]);
+
+        // the inactive resources aren't suggested, the unknown ones are rejected
+        manager.deactivate_resource("room-3".into()).await.unwrap();
+        let alternatives = ["room-3".to_string()];
+        let suggestions = manager.suggest(&rsvp, &alternatives).await.unwrap();
+        assert!(suggestions.iter().all(|slot| slot.resource_id == "room-2"));
+        let alternatives = ["room-404".to_string()];
+        let err = manager.suggest(&rsvp, &alternatives).await.unwrap_err();
+        assert_eq!(err, abi::Error::ResourceNotFound("room-404".into()));
+    }
+
+    /// a manager of the database seeded with the resources reserved in the tests
+    async fn new_manager(pool: PgPool) -> ReservationManager {
+        sqlx::query(include_str!("../../fixtures/resources.sql"))
+            .execute(&pool)
+            .await
+            .unwrap();
+        ReservationManager::new(pool)
+    }
+
+    fn standup(rid: &str) -> Reservation {
+        abi::Reservation::new_pending(
+            "chalanziId",
+            rid,
+            "2022-12-25T10:00:00Z".parse().unwrap(),
+            "2022-12-25T11:00:00Z".parse().unwrap(),
+            "standup",
+        )
+    }
+
+    fn daily_rule(count: i64) -> abi::RecurrenceRule {
+        abi::RecurrenceRule {
+            frequency: abi::RecurrenceFrequency::Daily as i32,
+            count,
+            ..Default::default()
+        }
+    }
+
     async fn make_chalanzi_reservation(pool: PgPool) -> (Reservation, ReservationManager) {
         make_reservation(
             pool,
@@ -335,7 +2150,7 @@ mod tests {
         end: &str,
         note: &str,
     ) -> (Reservation, ReservationManager) {
-        let manager = ReservationManager::new(pool.clone());
+        let manager = new_manager(pool).await;
         let rsvp = abi::Reservation::new_pending(
             uid,
             rid,
@@ -0,0 +1,415 @@
+use abi::convert_to_utc_time;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{postgres::types::PgRange, Acquire};
+
+use crate::{
+    manager::{insert, validate_new},
+    ReservationManager,
+};
+
+/// how far the usage and the free gaps of the resources are looked at around the window
+const POOL_HORIZON_DAYS: i64 = 7;
+
+/// a resource of the pool with the reservations around the window
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub id: abi::ResourceId,
+    pub capacity: i32,
+    /// units left in the window
+    pub available: i32,
+    /// unit seconds reserved in the horizon around the window
+    pub usage: i64,
+    /// seconds free before and after the window until the adjacent reservations, or the horizon
+    pub slack: i64,
+}
+
+/// how a resource is chosen among the free candidates of a pool
+pub trait Strategy: Send + Sync {
+    /// order the candidates by preference, they are tried in the order until one is reserved.
+    /// the candidates are given order by id
+    fn rank(&self, candidates: &mut [Candidate]);
+}
+
+/// the first free resource by id
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FirstFit;
+
+/// the free resource with the least reserved time around the window
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LeastUsed;
+
+/// the free resource whose gap around the window is the smallest, then the one with the fewest
+/// units left, so that the larger gaps are kept for the longer reservations
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BestFit;
+
+impl Strategy for FirstFit {
+    fn rank(&self, _candidates: &mut [Candidate]) {}
+}
+
+impl Strategy for LeastUsed {
+    fn rank(&self, candidates: &mut [Candidate]) {
+        candidates.sort_by_key(|c| c.usage);
+    }
+}
+
+impl Strategy for BestFit {
+    fn rank(&self, candidates: &mut [Candidate]) {
+        candidates.sort_by_key(|c| (c.slack, c.available));
+    }
+}
+
+/// the built-in strategy of the request
+pub fn strategy(strategy: abi::AllocationStrategy) -> &'static dyn Strategy {
+    match strategy {
+        abi::AllocationStrategy::FirstFit => &FirstFit,
+        abi::AllocationStrategy::LeastUsed => &LeastUsed,
+        abi::AllocationStrategy::BestFit => &BestFit,
+    }
+}
+
+impl Candidate {
+    /// `rsvps` are the reservations of the resource overlapping the horizon
+    fn new(
+        id: abi::ResourceId,
+        capacity: i32,
+        rsvps: &[abi::Reservation],
+        window: (DateTime<Utc>, DateTime<Utc>),
+        horizon: (DateTime<Utc>, DateTime<Utc>),
+    ) -> Self {
+        let (start, end) = window;
+        let spans: Vec<_> = rsvps
+            .iter()
+            .map(|r| {
+                let s = convert_to_utc_time(r.start.as_ref().unwrap());
+                let e = convert_to_utc_time(r.end.as_ref().unwrap());
+                (s, e, r.quantity)
+            })
+            .collect();
+
+        // the units reserved only change at the start of a reservation
+        let reserved = spans
+            .iter()
+            .filter(|(s, e, _)| *s < end && *e > start)
+            .map(|(s, _, _)| (*s).max(start))
+            .map(|at| {
+                spans
+                    .iter()
+                    .filter(|(s, e, _)| *s <= at && at < *e)
+                    .map(|(_, _, q)| q)
+                    .sum::<i32>()
+            })
+            .max()
+            .unwrap_or_default();
+
+        let usage = spans
+            .iter()
+            .map(|(s, e, q)| {
+                let seconds = ((*e).min(horizon.1) - (*s).max(horizon.0)).num_seconds();
+                seconds.max(0) * *q as i64
+            })
+            .sum();
+
+        let prev_end = spans
+            .iter()
+            .map(|(_, e, _)| *e)
+            .filter(|e| *e <= start)
+            .fold(horizon.0, DateTime::max);
+        let next_start = spans
+            .iter()
+            .map(|(s, _, _)| *s)
+            .filter(|s| *s >= end)
+            .fold(horizon.1, DateTime::min);
+
+        Self {
+            id,
+            capacity,
+            available: capacity - reserved,
+            usage,
+            slack: ((start - prev_end) + (next_start - end)).num_seconds(),
+        }
+    }
+}
+
+impl ReservationManager {
+    /// reserve a free resource of the type, tried in the order of the strategy. The resource_id of
+    /// the reservation must be empty, it's the chosen one if reserved. Nothing is made for a dry
+    /// run, and the id of the returned reservation is 0
+    pub async fn reserve_with(
+        &self,
+        mut rsvp: abi::Reservation,
+        r#type: &str,
+        strategy: &dyn Strategy,
+        dry_run: bool,
+    ) -> Result<abi::Reservation, abi::Error> {
+        if !rsvp.resource_id.is_empty() {
+            return Err(abi::Error::InvalidResourceId(rsvp.resource_id));
+        }
+        // the resource is chosen later, the rest is validated as for any resource
+        rsvp.resource_id = r#type.to_string();
+        validate_new(&mut rsvp)?;
+
+        let mut candidates = self.candidates(&rsvp, r#type).await?;
+        candidates.retain(|c| c.available >= rsvp.quantity);
+        strategy.rank(&mut candidates);
+
+        // a candidate may be taken since then, the next one is tried in its own savepoint
+        let mut tx = self.pool.begin().await?;
+        for candidate in candidates {
+            rsvp.resource_id = candidate.id;
+            let mut savepoint = tx.begin().await?;
+            match insert(&mut savepoint, &rsvp).await {
+                Ok(id) => {
+                    savepoint.commit().await?;
+                    if dry_run {
+                        tx.rollback().await?;
+                    } else {
+                        tx.commit().await?;
+                        rsvp.id = id;
+                    }
+                    return Ok(rsvp);
+                }
+                Err(
+                    abi::Error::ConflictReservation(_)
+                    | abi::Error::CapacityExceeded(_)
+                    | abi::Error::InactiveResource(_),
+                ) => savepoint.rollback().await?,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(abi::Error::NoAvailableResource(r#type.to_string()))
+    }
+
+    /// the active resources of the type order by id
+    async fn candidates(
+        &self,
+        rsvp: &abi::Reservation,
+        r#type: &str,
+    ) -> Result<Vec<Candidate>, abi::Error> {
+        let resources: Vec<(abi::ResourceId, i32)> = sqlx::query_as(
+            "SELECT id, capacity FROM rsvp.resources WHERE type = $1 AND active ORDER BY id",
+        )
+        .bind(r#type)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let start = convert_to_utc_time(rsvp.start.as_ref().unwrap());
+        let end = convert_to_utc_time(rsvp.end.as_ref().unwrap());
+        let horizon = Duration::days(POOL_HORIZON_DAYS);
+        let horizon = (start - horizon, end + horizon);
+        let ids: Vec<_> = resources.iter().map(|(id, _)| id.clone()).collect();
+        let rsvps: Vec<abi::Reservation> = sqlx::query_as(
+            "SELECT * FROM rsvp.reservations WHERE resource_id = ANY($1) AND timespan && $2",
+        )
+        .bind(ids)
+        .bind(PgRange::from(horizon.0..horizon.1))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let candidates = resources
+            .into_iter()
+            .map(|(id, capacity)| {
+                let rsvps: Vec<_> = rsvps
+                    .iter()
+                    .filter(|r| r.resource_id == id)
+                    .cloned()
+                    .collect();
+                Candidate::new(id, capacity, &rsvps, (start, end), horizon)
+            })
+            .collect();
+        Ok(candidates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use abi::Reservation;
+
+    use super::*;
+    use crate::{Catalog, Rsvp};
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn booking(start: &str, end: &str, quantity: i32) -> Reservation {
+        Reservation {
+            quantity,
+            ..Reservation::new_pending(
+                "chalanziId",
+                "room-1",
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            )
+        }
+    }
+
+    fn candidate(id: &str, available: i32, usage: i64, slack: i64) -> Candidate {
+        Candidate {
+            id: id.into(),
+            capacity: available,
+            available,
+            usage,
+            slack,
+        }
+    }
+
+    #[test]
+    fn candidate_should_summarize_reservations_around_window() {
+        let rsvps = [
+            booking("2022-12-25T08:00:00Z", "2022-12-25T10:00:00Z", 1),
+            booking("2022-12-25T11:00:00Z", "2022-12-25T13:00:00Z", 2),
+            booking("2022-12-25T12:00:00Z", "2022-12-25T15:00:00Z", 1),
+            booking("2022-12-25T16:00:00Z", "2022-12-25T17:00:00Z", 1),
+        ];
+        let window = (utc("2022-12-25T10:00:00Z"), utc("2022-12-25T14:00:00Z"));
+        let horizon = (utc("2022-12-25T00:00:00Z"), utc("2022-12-26T00:00:00Z"));
+        let c = Candidate::new("room-1".into(), 4, &rsvps, window, horizon);
+        assert_eq!(c.available, 1);
+        assert_eq!(c.usage, (2 + 2 * 2 + 3 + 1) * 3600);
+        // free from 10:00, the end of the first one, until 14:00 and from 14:00 to 16:00
+        assert_eq!(c.slack, 2 * 3600);
+
+        let window = (utc("2022-12-25T15:00:00Z"), utc("2022-12-25T16:00:00Z"));
+        let c = Candidate::new("room-1".into(), 4, &rsvps, window, horizon);
+        assert_eq!(c.available, 4);
+        assert_eq!(c.slack, 0);
+
+        let window = (utc("2022-12-25T18:00:00Z"), utc("2022-12-25T19:00:00Z"));
+        let c = Candidate::new("room-1".into(), 1, &[], window, horizon);
+        assert_eq!(c.available, 1);
+        assert_eq!(c.usage, 0);
+        assert_eq!(c.slack, 23 * 3600);
+    }
+
+    #[test]
+    fn strategies_should_rank_candidates() {
+        let candidates = vec![
+            candidate("room-1", 1, 300, 7200),
+            candidate("room-2", 1, 100, 3600),
+            candidate("room-3", 2, 200, 3600),
+            candidate("room-4", 1, 100, 0),
+        ];
+        let ranked = |s: &dyn Strategy| {
+            let mut candidates = candidates.clone();
+            s.rank(&mut candidates);
+            candidates.into_iter().map(|c| c.id).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ranked(&FirstFit),
+            vec!["room-1", "room-2", "room-3", "room-4"]
+        );
+        assert_eq!(
+            ranked(&LeastUsed),
+            vec!["room-2", "room-4", "room-3", "room-1"]
+        );
+        assert_eq!(
+            ranked(&BestFit),
+            vec!["room-4", "room-2", "room-3", "room-1"]
+        );
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn reserve_in_pool_should_pick_a_free_resource() {
+        let manager = ReservationManager::new(migrate_pool.clone());
+        for id in ["room-1", "room-2", "room-3"] {
+            let resource = abi::Resource::new(id, "room");
+            manager.create_resource(resource).await.unwrap();
+        }
+        let rsvp = |rid: &str, start: &str, end: &str| {
+            Reservation::new_pending(
+                "chalanziId",
+                rid,
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            )
+        };
+        // room-1 is taken in the window, room-2 is busy right after it
+        let busy = [
+            ("room-1", "2022-12-25T10:00:00Z", "2022-12-25T12:00:00Z"),
+            ("room-2", "2022-12-25T12:00:00Z", "2022-12-25T13:00:00Z"),
+            ("room-2", "2022-12-24T12:00:00Z", "2022-12-24T20:00:00Z"),
+        ];
+        for (rid, start, end) in busy {
+            manager.reserve(rsvp(rid, start, end)).await.unwrap();
+        }
+        let pool = |strategy: abi::AllocationStrategy| abi::ResourcePool {
+            r#type: "room".into(),
+            strategy: strategy as i32,
+        };
+        let window = || rsvp("", "2022-12-25T11:00:00Z", "2022-12-25T12:00:00Z");
+
+        let checked = manager
+            .reserve_in_pool(window(), pool(abi::AllocationStrategy::BestFit), true)
+            .await
+            .unwrap();
+        assert_eq!((checked.id, checked.resource_id.as_str()), (0, "room-2"));
+        let reserved = manager
+            .reserve_in_pool(window(), pool(abi::AllocationStrategy::LeastUsed), false)
+            .await
+            .unwrap();
+        assert!(reserved.id != 0);
+        assert_eq!(reserved.resource_id, "room-3");
+        assert_eq!(manager.get(reserved.id).await.unwrap(), reserved);
+        let reserved = manager
+            .reserve_in_pool(window(), pool(abi::AllocationStrategy::FirstFit), false)
+            .await
+            .unwrap();
+        assert_eq!(reserved.resource_id, "room-2");
+
+        let err = manager
+            .reserve_in_pool(window(), pool(abi::AllocationStrategy::FirstFit), false)
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::NoAvailableResource("room".into()));
+        let err = manager
+            .reserve_in_pool(
+                rsvp("room-1", "2022-12-26T11:00:00Z", "2022-12-26T12:00:00Z"),
+                pool(abi::AllocationStrategy::FirstFit),
+                false,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidResourceId("room-1".into()));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn concurrent_pool_reservations_should_take_different_resources() {
+        let manager = std::sync::Arc::new(ReservationManager::new(migrate_pool.clone()));
+        for id in ["desk-1", "desk-2", "desk-3"] {
+            let resource = abi::Resource::new(id, "desk");
+            manager.create_resource(resource).await.unwrap();
+        }
+
+        let tasks: Vec<_> = (0..5)
+            .map(|i| {
+                let manager = manager.clone();
+                let rsvp = Reservation::new_pending(
+                    format!("user-{}", i),
+                    "",
+                    "2022-12-25T09:00:00Z".parse().unwrap(),
+                    "2022-12-25T18:00:00Z".parse().unwrap(),
+                    "",
+                );
+                let pool = abi::ResourcePool {
+                    r#type: "desk".into(),
+                    ..Default::default()
+                };
+                tokio::spawn(async move { manager.reserve_in_pool(rsvp, pool, false).await })
+            })
+            .collect();
+
+        let mut rids = vec![];
+        for task in tasks {
+            match task.await.unwrap() {
+                Ok(rsvp) => rids.push(rsvp.resource_id),
+                Err(e) => assert_eq!(e, abi::Error::NoAvailableResource("desk".into())),
+            }
+        }
+        rids.sort();
+        assert_eq!(rids, vec!["desk-1", "desk-2", "desk-3"]);
+    }
+}
@@ -0,0 +1,759 @@
+use abi::{Normalizer, ToSql, Validator};
+use async_trait::async_trait;
+use sqlx::{types::Json, Executor, Postgres, Transaction};
+
+use crate::{Catalog, ReservationManager};
+
+#[async_trait]
+impl Catalog for ReservationManager {
+    async fn create_resource(
+        &self,
+        mut resource: abi::Resource,
+    ) -> Result<abi::Resource, abi::Error> {
+        resource.normalize()?;
+        validate_timezone(&self.pool, &resource.timezone).await?;
+
+        let resource = sqlx::query_as(
+            "INSERT INTO rsvp.resources (id, name, type, attributes, timezone, capacity, parent_id) VALUES ($1, $2, $3, $4, $5, $6, NULLIF($7, '')) RETURNING *",
+        )
+        .bind(&resource.id)
+        .bind(&resource.name)
+        .bind(&resource.r#type)
+        .bind(Json(&resource.attributes))
+        .bind(&resource.timezone)
+        .bind(resource.capacity)
+        .bind(&resource.parent_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(resource)
+    }
+
+    async fn update_resource(
+        &self,
+        request: abi::UpdateResourceRequest,
+    ) -> Result<abi::Resource, abi::Error> {
+        request.validate()?;
+
+        let mut tx = self.pool.begin().await?;
+        let resource: Option<abi::Resource> =
+            sqlx::query_as("SELECT * FROM rsvp.resources WHERE id = $1 FOR UPDATE")
+                .bind(&request.id)
+                .fetch_optional(&mut tx)
+                .await?;
+        let mut resource =
+            resource.ok_or_else(|| abi::Error::ResourceNotFound(request.id.clone()))?;
+
+        let moved = request
+            .parent_id
+            .as_ref()
+            .is_some_and(|parent_id| *parent_id != resource.parent_id);
+        resource.update(request);
+        resource.normalize()?;
+        validate_timezone(&mut tx, &resource.timezone).await?;
+        if moved {
+            lock_trees(&mut tx, &resource).await?;
+        }
+
+        // the reservations are checked against a new capacity by the trigger
+        let resource: abi::Resource = sqlx::query_as(
+            "UPDATE rsvp.resources SET name = $1, type = $2, attributes = $3, timezone = $4, capacity = $5, parent_id = NULLIF($6, ''), updated_at = now() WHERE id = $7 RETURNING *",
+        )
+        .bind(&resource.name)
+        .bind(&resource.r#type)
+        .bind(Json(&resource.attributes))
+        .bind(&resource.timezone)
+        .bind(resource.capacity)
+        .bind(&resource.parent_id)
+        .bind(&resource.id)
+        .fetch_one(&mut tx)
+        .await?;
+        if moved {
+            check_ancestors(&mut tx, &resource.id).await?;
+        }
+        tx.commit().await?;
+
+        Ok(resource)
+    }
+
+    async fn deactivate_resource(&self, id: abi::ResourceId) -> Result<abi::Resource, abi::Error> {
+        if id.is_empty() {
+            return Err(abi::Error::InvalidResourceId(id));
+        }
+        let resource = sqlx::query_as(
+            "UPDATE rsvp.resources SET active = FALSE, updated_at = now() WHERE id = $1 RETURNING *",
+        )
+        .bind(&id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        resource.ok_or(abi::Error::ResourceNotFound(id))
+    }
+
+    async fn list_resources(
+        &self,
+        request: abi::ListResourcesRequest,
+    ) -> Result<Vec<abi::Resource>, abi::Error> {
+        let resources = request
+            .to_query()
+            .build_query_as()
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(resources)
+    }
+}
+
+/// a resource couldn't be moved under itself or any of its descendants. The trees it leaves and
+/// joins are locked, so that no reservation is made in them until it's moved
+async fn lock_trees(
+    tx: &mut Transaction<'_, Postgres>,
+    resource: &abi::Resource,
+) -> Result<(), abi::Error> {
+    if resource.parent_id.is_empty() {
+        return Ok(());
+    }
+    let cyclic: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM rsvp.resource_family($1) WHERE id = $2 AND relation = 'descendant')",
+    )
+    .bind(&resource.id)
+    .bind(&resource.parent_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    if cyclic {
+        return Err(abi::Error::InvalidParent(resource.parent_id.clone()));
+    }
+
+    sqlx::query(
+        "SELECT id FROM rsvp.resources WHERE id IN (rsvp.resource_root($1), rsvp.resource_root($2)) ORDER BY id FOR NO KEY UPDATE",
+    )
+    .bind(&resource.id)
+    .bind(&resource.parent_id)
+    .fetch_all(&mut *tx)
+    .await?;
+    Ok(())
+}
+
+/// the reservations of a moved resource and its descendants must not overlap the ones of its new
+/// ancestors. The first one overlapping is reported as a conflict with the ancestor
+async fn check_ancestors(tx: &mut Transaction<'_, Postgres>, id: &str) -> Result<(), abi::Error> {
+    let pair: Option<(i64, i64)> = sqlx::query_as(
+        "SELECT n.id, o.id FROM rsvp.reservations n JOIN rsvp.reservations o ON o.timespan && n.timespan WHERE (n.resource_id = $1 OR n.resource_id IN (SELECT id FROM rsvp.resource_family($1) WHERE relation = 'descendant')) AND o.resource_id IN (SELECT id FROM rsvp.resource_family($1) WHERE relation = 'ancestor') ORDER BY lower(n.timespan), lower(o.timespan) LIMIT 1",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some((new, old)) = pair else {
+        return Ok(());
+    };
+
+    let get = |id: i64| {
+        sqlx::query_as::<_, abi::Reservation>("SELECT * FROM rsvp.reservations WHERE id = $1")
+            .bind(id)
+    };
+    let new = get(new).fetch_one(&mut *tx).await?;
+    let old = get(old).fetch_one(&mut *tx).await?;
+    let conflict = abi::ReservationConflict {
+        new: (&new).into(),
+        old: (&old).into(),
+        relation: abi::ResourceRelation::Ancestor,
+        // the reservations may be of different users, the note is hidden
+        conflicts: vec![abi::Reservation {
+            note: String::new(),
+            ..old
+        }],
+        suggestions: vec![],
+    };
+    Err(abi::Error::ConflictReservation(
+        abi::ReservationConflictInfo::Parsed(Box::new(conflict)),
+    ))
+}
+
+/// the timezone is known by postgres
+async fn validate_timezone<'e, E>(executor: E, timezone: &str) -> Result<(), abi::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let known: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1)")
+            .bind(timezone)
+            .fetch_one(executor)
+            .await?;
+    if !known {
+        return Err(abi::Error::InvalidTimezone(timezone.to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use abi::Reservation;
+
+    use super::*;
+    use crate::Rsvp;
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn create_resource_should_work() {
+        let manager = ReservationManager::new(migrate_pool.clone());
+        let resource = abi::Resource {
+            attributes: HashMap::from([("seats".to_string(), "8".to_string())]),
+            timezone: "Asia/Shanghai".into(),
+            active: false,
+            ..abi::Resource::new("room-1", "room")
+        };
+        let created = manager.create_resource(resource.clone()).await.unwrap();
+        assert_eq!(
+            created,
+            abi::Resource {
+                name: "room-1".into(),
+                active: true,
+                ..resource.clone()
+            }
+        );
+
+        let err = manager.create_resource(resource).await.unwrap_err();
+        assert_eq!(err, abi::Error::ResourceExists("room-1".into()));
+        let err = manager
+            .create_resource(abi::Resource {
+                timezone: "Mars/Olympus".into(),
+                ..abi::Resource::new("room-2", "room")
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidTimezone("Mars/Olympus".into()));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn update_resource_should_work() {
+        let manager = ReservationManager::new(migrate_pool.clone());
+        manager
+            .create_resource(abi::Resource::new("room-1", "room"))
+            .await
+            .unwrap();
+
+        let updated = manager
+            .update_resource(abi::UpdateResourceRequest {
+                id: "room-1".into(),
+                name: Some("Room 1".into()),
+                attributes: HashMap::from([("seats".to_string(), "8".to_string())]),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(updated.name, "Room 1");
+        assert_eq!(updated.r#type, "room");
+        assert_eq!(updated.attributes["seats"], "8");
+
+        let err = manager
+            .update_resource(abi::UpdateResourceRequest {
+                id: "room-1".into(),
+                timezone: Some("Mars/Olympus".into()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidTimezone("Mars/Olympus".into()));
+        let err = manager
+            .update_resource(abi::UpdateResourceRequest {
+                id: "room-2".into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::ResourceNotFound("room-2".into()));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn only_active_resource_in_catalog_could_be_reserved() {
+        let manager = ReservationManager::new(migrate_pool.clone());
+        for (id, r#type) in [("room-1", "room"), ("room-2", "room"), ("desk-1", "desk")] {
+            manager
+                .create_resource(abi::Resource::new(id, r#type))
+                .await
+                .unwrap();
+        }
+        let rsvp = |rid: &str| {
+            Reservation::new_pending(
+                "chalanziId",
+                rid,
+                "2022-12-25T15:00:00-0700".parse().unwrap(),
+                "2022-12-28T12:00:00-0700".parse().unwrap(),
+                "",
+            )
+        };
+        assert!(manager.reserve(rsvp("room-1")).await.unwrap().id != 0);
+        let err = manager.reserve(rsvp("room-404")).await.unwrap_err();
+        assert_eq!(err, abi::Error::ResourceNotFound("room-404".into()));
+
+        let deactivated = manager.deactivate_resource("room-2".into()).await.unwrap();
+        assert!(!deactivated.active);
+        let err = manager.reserve(rsvp("room-2")).await.unwrap_err();
+        assert_eq!(err, abi::Error::InactiveResource("room-2".into()));
+        let err = manager.check(rsvp("room-2")).await.unwrap_err();
+        assert_eq!(err, abi::Error::InactiveResource("room-2".into()));
+        let err = manager
+            .deactivate_resource("room-404".into())
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::ResourceNotFound("room-404".into()));
+
+        let ids = |resources: Vec<abi::Resource>| -> Vec<String> {
+            resources.into_iter().map(|r| r.id).collect()
+        };
+        let resources = manager
+            .list_resources(abi::ListResourcesRequest::default())
+            .await
+            .unwrap();
+        assert_eq!(ids(resources), vec!["desk-1", "room-1"]);
+        let resources = manager
+            .list_resources(abi::ListResourcesRequest {
+                r#type: "room".into(),
+                include_inactive: true,
+            })
+            .await
+            .unwrap();
+        assert_eq!(ids(resources), vec!["room-1", "room-2"]);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn reservation_should_wait_for_the_resource_being_deactivated() {
+        let manager = Arc::new(ReservationManager::new(migrate_pool.clone()));
+        manager
+            .create_resource(abi::Resource::new("room-1", "room"))
+            .await
+            .unwrap();
+
+        let mut tx = migrate_pool.begin().await.unwrap();
+        sqlx::query("UPDATE rsvp.resources SET active = FALSE WHERE id = 'room-1'")
+            .execute(&mut tx)
+            .await
+            .unwrap();
+        let task = {
+            let manager = manager.clone();
+            let rsvp = Reservation::new_pending(
+                "chalanziId",
+                "room-1",
+                "2022-12-25T15:00:00-0700".parse().unwrap(),
+                "2022-12-28T12:00:00-0700".parse().unwrap(),
+                "",
+            );
+            tokio::spawn(async move { manager.reserve(rsvp).await })
+        };
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        assert!(!task.is_finished());
+
+        tx.commit().await.unwrap();
+        let err = task.await.unwrap().unwrap_err();
+        assert_eq!(err, abi::Error::InactiveResource("room-1".into()));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn shared_resource_should_be_reserved_up_to_capacity() {
+        let manager = ReservationManager::new(migrate_pool.clone());
+        let resource = abi::Resource {
+            capacity: 3,
+            ..abi::Resource::new("class-1", "class")
+        };
+        manager.create_resource(resource).await.unwrap();
+        let rsvp = |start: &str, end: &str, quantity| Reservation {
+            quantity,
+            ..Reservation::new_pending(
+                "chalanziId",
+                "class-1",
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            )
+        };
+
+        // 2 units from 10:00 to 12:00, 1 unit from 11:00 to 13:00, 3 units at 11:00
+        let first = rsvp("2022-12-25T10:00:00Z", "2022-12-25T12:00:00Z", 2);
+        assert_eq!(manager.reserve(first).await.unwrap().quantity, 2);
+        let second = rsvp("2022-12-25T11:00:00Z", "2022-12-25T13:00:00Z", 0);
+        assert_eq!(manager.reserve(second).await.unwrap().quantity, 1);
+
+        // the window from 09:00 to 14:00 is fully reserved at 11:00
+        let err = manager
+            .reserve(rsvp("2022-12-25T09:00:00Z", "2022-12-25T14:00:00Z", 1))
+            .await
+            .unwrap_err();
+        let conflict = abi::CapacityConflict {
+            new: abi::ReservationWindow {
+                id: 0,
+                rid: "class-1".into(),
+                start: Some("2022-12-25T09:00:00Z".parse().unwrap()),
+                end: Some("2022-12-25T14:00:00Z".parse().unwrap()),
+            },
+            quantity: 1,
+            capacity: 3,
+            reserved: 3,
+        };
+        assert_eq!(err, abi::Error::CapacityExceeded(conflict));
+        let err = manager
+            .check(rsvp("2022-12-25T12:00:00Z", "2022-12-25T14:00:00Z", 3))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, abi::Error::CapacityExceeded(c) if c.reserved == 1));
+
+        // only 1 unit is left from 10:00 to 11:00, 2 units after 12:00
+        let rsvp3 = rsvp("2022-12-25T10:00:00Z", "2022-12-25T11:00:00Z", 1);
+        assert!(manager.reserve(rsvp3).await.is_ok());
+        let rsvp4 = rsvp("2022-12-25T12:00:00Z", "2022-12-25T14:00:00Z", 2);
+        assert!(manager.reserve(rsvp4).await.is_ok());
+
+        let err = manager
+            .reserve(rsvp("2022-12-26T10:00:00Z", "2022-12-26T12:00:00Z", -1))
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidQuantity(-1));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn concurrent_reservations_should_not_exceed_capacity() {
+        let manager = Arc::new(ReservationManager::new(migrate_pool.clone()));
+        let resource = abi::Resource {
+            capacity: 3,
+            ..abi::Resource::new("desk-zone-a", "desk")
+        };
+        manager.create_resource(resource).await.unwrap();
+
+        // the windows overlap each other in the afternoon
+        let tasks: Vec<_> = (0..10)
+            .map(|i| {
+                let manager = manager.clone();
+                let rsvp = Reservation::new_pending(
+                    format!("user-{}", i),
+                    "desk-zone-a",
+                    format!("2022-12-25T{:02}:00:00Z", 8 + i % 5)
+                        .parse()
+                        .unwrap(),
+                    "2022-12-25T18:00:00Z".parse().unwrap(),
+                    "",
+                );
+                tokio::spawn(async move { manager.reserve(rsvp).await })
+            })
+            .collect();
+
+        let mut reserved = 0;
+        for task in tasks {
+            match task.await.unwrap() {
+                Ok(_) => reserved += 1,
+                Err(abi::Error::CapacityExceeded(conflict)) => assert_eq!(conflict.capacity, 3),
+                Err(e) => panic!("unexpected error: {:?}", e),
+            }
+        }
+        assert_eq!(reserved, 3);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn capacity_should_hold_the_existing_reservations() {
+        let manager = ReservationManager::new(migrate_pool.clone());
+        let resource = abi::Resource {
+            capacity: 2,
+            ..abi::Resource::new("parking-a", "parking")
+        };
+        manager.create_resource(resource).await.unwrap();
+        let rsvp = |uid: &str| {
+            Reservation::new_pending(
+                uid,
+                "parking-a",
+                "2022-12-25T15:00:00-0700".parse().unwrap(),
+                "2022-12-28T12:00:00-0700".parse().unwrap(),
+                "",
+            )
+        };
+        manager.reserve(rsvp("chalanziId")).await.unwrap();
+        manager.reserve(rsvp("wanerId")).await.unwrap();
+
+        let update = |capacity| abi::UpdateResourceRequest {
+            id: "parking-a".into(),
+            capacity: Some(capacity),
+            ..Default::default()
+        };
+        let err = manager.update_resource(update(1)).await.unwrap_err();
+        assert!(matches!(err, abi::Error::ConflictReservation(_)));
+        let err = manager.update_resource(update(0)).await.unwrap_err();
+        assert_eq!(err, abi::Error::InvalidCapacity(0));
+
+        let updated = manager.update_resource(update(3)).await.unwrap();
+        assert_eq!(updated.capacity, 3);
+        manager.reserve(rsvp("tyrId")).await.unwrap();
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn reservations_of_ancestors_and_descendants_should_conflict() {
+        let manager = ReservationManager::new(migrate_pool.clone());
+        create_tree(&manager).await;
+        let rsvp = |rid: &str, start: &str, end: &str| {
+            Reservation::new_pending(
+                "chalanziId",
+                rid,
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "hello.",
+            )
+        };
+
+        let room = manager
+            .reserve(rsvp(
+                "room-1",
+                "2022-12-25T10:00:00Z",
+                "2022-12-25T12:00:00Z",
+            ))
+            .await
+            .unwrap();
+        // a sibling is not affected
+        manager
+            .reserve(rsvp(
+                "room-2",
+                "2022-12-25T10:00:00Z",
+                "2022-12-25T12:00:00Z",
+            ))
+            .await
+            .unwrap();
+
+        // the floor is not free while a room of it is reserved
+        let err = manager
+            .reserve(rsvp(
+                "floor-1",
+                "2022-12-25T11:00:00Z",
+                "2022-12-25T13:00:00Z",
+            ))
+            .await
+            .unwrap_err();
+        let conflict = match err {
+            abi::Error::ConflictReservation(abi::ReservationConflictInfo::Parsed(conflict)) => {
+                conflict
+            }
+            err => panic!("expect conflict reservation error, got {:?}", err),
+        };
+        assert_eq!(conflict.new.rid, "floor-1");
+        assert!(["room-1", "room-2"].contains(&conflict.old.rid.as_str()));
+        assert_ne!(conflict.old.id, 0);
+        assert_eq!(conflict.relation, abi::ResourceRelation::Descendant);
+        let rids: Vec<_> = conflict
+            .conflicts
+            .iter()
+            .map(|r| r.resource_id.as_str())
+            .collect();
+        assert_eq!(rids.len(), 2);
+        assert!(rids.contains(&"room-1") && rids.contains(&"room-2"));
+
+        // a room is not free while its building is reserved
+        let building = manager
+            .reserve(rsvp(
+                "building-1",
+                "2022-12-25T12:00:00Z",
+                "2022-12-25T14:00:00Z",
+            ))
+            .await
+            .unwrap();
+        let err = manager
+            .check(rsvp(
+                "room-1",
+                "2022-12-25T13:00:00Z",
+                "2022-12-25T15:00:00Z",
+            ))
+            .await
+            .unwrap_err();
+        let info = abi::ReservationConflictInfo::Parsed(Box::new(abi::ReservationConflict {
+            new: abi::ReservationWindow {
+                id: 0,
+                rid: "room-1".into(),
+                start: Some("2022-12-25T13:00:00Z".parse().unwrap()),
+                end: Some("2022-12-25T15:00:00Z".parse().unwrap()),
+            },
+            old: (&building).into(),
+            relation: abi::ResourceRelation::Ancestor,
+            conflicts: vec![building.clone()],
+            suggestions: vec![],
+        }));
+        assert_eq!(err, abi::Error::ConflictReservation(info));
+
+        // a reservation moved onto the building's time is checked as well
+        let err = manager
+            .update_series(abi::UpdateSeriesRequest {
+                id: room.id,
+                start: Some("2022-12-25T11:00:00Z".parse().unwrap()),
+                end: Some("2022-12-25T13:00:00Z".parse().unwrap()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        let conflicts = match err {
+            abi::Error::BatchConflict(conflicts) => conflicts,
+            err => panic!("expect batch conflict error, got {:?}", err),
+        };
+        assert!(matches!(
+            &conflicts[..],
+            [(0, abi::BatchConflictInfo::Reservation(abi::ReservationConflictInfo::Parsed(c)))]
+                if c.old.id == building.id && c.relation == abi::ResourceRelation::Ancestor
+        ));
+
+        // the free slots of a room exclude the reservations of its building
+        let query = abi::AvailabilityQueryBuilder::default()
+            .resource_ids(vec!["room-2".to_string()])
+            .start(
+                "2022-12-25T08:00:00Z"
+                    .parse::<prost_types::Timestamp>()
+                    .unwrap(),
+            )
+            .end(
+                "2022-12-25T18:00:00Z"
+                    .parse::<prost_types::Timestamp>()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        let slots: Vec<_> = manager
+            .availability(query)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|slot| (slot.start.unwrap().seconds, slot.end.unwrap().seconds))
+            .collect();
+        let ts = |s: &str| s.parse::<prost_types::Timestamp>().unwrap().seconds;
+        assert_eq!(
+            slots,
+            vec![
+                (ts("2022-12-25T08:00:00Z"), ts("2022-12-25T10:00:00Z")),
+                (ts("2022-12-25T14:00:00Z"), ts("2022-12-25T18:00:00Z")),
+            ]
+        );
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn concurrent_reservations_in_a_tree_should_not_overlap() {
+        let manager = Arc::new(ReservationManager::new(migrate_pool.clone()));
+        create_tree(&manager).await;
+
+        // the whole tree is wanted for the same window, only one of them could be reserved
+        let tasks: Vec<_> = ["building-1", "floor-1", "room-1", "room-2"]
+            .into_iter()
+            .cycle()
+            .take(12)
+            .enumerate()
+            .map(|(i, rid)| {
+                let manager = manager.clone();
+                let rsvp = Reservation::new_pending(
+                    format!("user-{}", i),
+                    rid,
+                    "2022-12-25T10:00:00Z".parse().unwrap(),
+                    "2022-12-25T12:00:00Z".parse().unwrap(),
+                    "",
+                );
+                tokio::spawn(async move { manager.reserve(rsvp).await })
+            })
+            .collect();
+
+        let mut reserved = vec![];
+        for task in tasks {
+            match task.await.unwrap() {
+                Ok(rsvp) => reserved.push(rsvp.resource_id),
+                Err(abi::Error::ConflictReservation(_)) => {}
+                Err(e) => panic!("unexpected error: {:?}", e),
+            }
+        }
+        // both rooms could be reserved together, but not with the floor or the building
+        reserved.sort();
+        assert!(
+            reserved == ["building-1"]
+                || reserved == ["floor-1"]
+                || reserved == ["room-1"]
+                || reserved == ["room-2"]
+                || reserved == ["room-1", "room-2"],
+            "{:?}",
+            reserved
+        );
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn moved_resource_should_keep_the_tree_free_of_conflicts() {
+        let manager = ReservationManager::new(migrate_pool.clone());
+        create_tree(&manager).await;
+        manager
+            .create_resource(abi::Resource::new("building-2", "building"))
+            .await
+            .unwrap();
+        let rsvp = |rid: &str| {
+            Reservation::new_pending(
+                "chalanziId",
+                rid,
+                "2022-12-25T10:00:00Z".parse().unwrap(),
+                "2022-12-25T12:00:00Z".parse().unwrap(),
+                "",
+            )
+        };
+        let building = manager.reserve(rsvp("building-2")).await.unwrap();
+        let room = manager.reserve(rsvp("room-1")).await.unwrap();
+        let moved = |id: &str, parent_id: &str| abi::UpdateResourceRequest {
+            id: id.into(),
+            parent_id: Some(parent_id.into()),
+            ..Default::default()
+        };
+
+        // a resource couldn't be moved under itself or its descendants
+        let err = manager
+            .update_resource(moved("floor-1", "floor-1"))
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidParent("floor-1".into()));
+        let err = manager
+            .update_resource(moved("building-1", "room-1"))
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidParent("room-1".into()));
+        let err = manager
+            .update_resource(moved("floor-1", "building-404"))
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::ResourceNotFound("building-404".into()));
+
+        // the room of the floor is reserved at the same time as the new building
+        let err = manager
+            .update_resource(moved("floor-1", "building-2"))
+            .await
+            .unwrap_err();
+        let info = abi::ReservationConflictInfo::Parsed(Box::new(abi::ReservationConflict {
+            new: (&room).into(),
+            old: (&building).into(),
+            relation: abi::ResourceRelation::Ancestor,
+            conflicts: vec![building.clone()],
+            suggestions: vec![],
+        }));
+        assert_eq!(err, abi::Error::ConflictReservation(info));
+
+        // a root resource is out of the tree
+        let updated = manager.update_resource(moved("room-1", "")).await.unwrap();
+        assert_eq!(updated.parent_id, "");
+        let updated = manager
+            .update_resource(moved("floor-1", "building-2"))
+            .await
+            .unwrap();
+        assert_eq!(updated.parent_id, "building-2");
+        let err = manager.reserve(rsvp("room-2")).await.unwrap_err();
+        assert!(matches!(
+            err,
+            abi::Error::ConflictReservation(abi::ReservationConflictInfo::Parsed(c))
+                if c.old.rid == "building-2"
+        ));
+    }
+
+    /// building-1 -> floor-1 -> room-1, room-2
+    async fn create_tree(manager: &ReservationManager) {
+        for (id, r#type, parent_id) in [
+            ("building-1", "building", ""),
+            ("floor-1", "floor", "building-1"),
+            ("room-1", "room", "floor-1"),
+            ("room-2", "room", "floor-1"),
+        ] {
+            let resource = abi::Resource {
+                parent_id: parent_id.into(),
+                ..abi::Resource::new(id, r#type)
+            };
+            let created = manager.create_resource(resource).await.unwrap();
+            assert_eq!(created.parent_id, parent_id);
+        }
+    }
+}
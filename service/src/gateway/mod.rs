@@ -0,0 +1,633 @@
+mod types;
+
+use std::sync::Arc;
+
+use abi::{CapacityConflict, ErrorDetail, ReservationConflict};
+use axum::{
+    extract::{
+        rejection::{JsonRejection, PathRejection},
+        Path, State,
+    },
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use futures::{StreamExt, TryStreamExt};
+use reservation::Rsvp;
+use tonic::Code;
+
+use crate::RsvpService;
+use types::{
+    AvailabilityParams, AvailabilityResult, BatchParams, BatchResult, ErrorBody, FilterParams,
+    FilterResult, NewReservation, QueryParams, QueryResult, ReservationJson, ReservationPatch,
+    Status,
+};
+
+/// the openapi document of the gateway
+const OPENAPI: &str = include_str!("../../openapi.yaml");
+
+/// most reservations returned by a query of page size 0
+const MAX_UNPAGED_ROWS: usize = 1000;
+
+/// http/json gateway of the reservation service, it shares the service with grpc
+pub fn router(svc: Arc<RsvpService>) -> Router {
+    Router::new()
+        .route("/openapi.yaml", get(openapi))
+        .route("/reservations", post(reserve))
+        .route("/reservations/batch", post(reserve_batch))
+        .route("/reservations/query", post(query))
+        .route("/reservations/filter", post(filter))
+        .route("/availability", post(availability))
+        .route(
+            "/reservations/:id",
+            get(get_reservation).patch(update).delete(cancel),
+        )
+        .with_state(svc)
+}
+
+/// abi::Error is converted to the grpc status first, so that the http status code is the same
+/// as the grpc one
+#[derive(Debug)]
+struct ApiError(tonic::Status);
+
+type ApiResult<T> = Result<T, ApiError>;
+
+async fn openapi() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "application/yaml")], OPENAPI)
+}
+
+async fn reserve(
+    State(svc): State<Arc<RsvpService>>,
+    body: Result<Json<NewReservation>, JsonRejection>,
+) -> ApiResult<(StatusCode, Json<ReservationJson>)> {
+    let Json(body) = body?;
+    body.validate_status()?;
+    // nothing is created by a dry run
+    let status = if body.dry_run {
+        StatusCode::OK
+    } else {
+        StatusCode::CREATED
+    };
+    let rsvp = svc.make_reservation(body.into()).await.map_err(ApiError)?;
+    Ok((status, Json((&rsvp).into())))
+}
+
+/// make the reservations in one transaction, either all of them or none
+async fn reserve_batch(
+    State(svc): State<Arc<RsvpService>>,
+    body: Result<Json<BatchParams>, JsonRejection>,
+) -> ApiResult<(StatusCode, Json<BatchResult>)> {
+    let Json(body) = body?;
+    for rsvp in &body.reservations {
+        rsvp.validate_status()?;
+    }
+    let rsvps = body.reservations.into_iter().map(Into::into).collect();
+    let rsvps = svc.manager.reserve_many(rsvps).await?;
+    Ok((
+        StatusCode::CREATED,
+        Json(BatchResult {
+            reservations: rsvps.iter().map(Into::into).collect(),
+        }),
+    ))
+}
+
+async fn get_reservation(
+    State(svc): State<Arc<RsvpService>>,
+    id: Result<Path<i64>, PathRejection>,
+) -> ApiResult<Json<ReservationJson>> {
+    let Path(id) = id?;
+    let rsvp = svc.manager.get(id).await?;
+    Ok(Json((&rsvp).into()))
+}
+
+/// update the note and/or confirm the reservation
+async fn update(
+    State(svc): State<Arc<RsvpService>>,
+    id: Result<Path<i64>, PathRejection>,
+    body: Result<Json<ReservationPatch>, JsonRejection>,
+) -> ApiResult<Json<ReservationJson>> {
+    let Path(id) = id?;
+    let Json(body) = body?;
+    let rsvp = match (body.status, body.note) {
+        (Some(Status::Confirmed), Some(note)) => svc.manager.confirm_with_note(id, note).await?,
+        (Some(Status::Confirmed), None) => svc.manager.change_status(id).await?,
+        (Some(status), _) => {
+            let status = abi::ReservationStatus::from(status) as i32;
+            return Err(abi::Error::InvalidStatus(status).into());
+        }
+        (None, Some(note)) => svc.manager.update_note(id, note).await?,
+        (None, None) => svc.manager.get(id).await?,
+    };
+    Ok(Json((&rsvp).into()))
+}
+
+/// delete the reservation, return the deleted one
+async fn cancel(
+    State(svc): State<Arc<RsvpService>>,
+    id: Result<Path<i64>, PathRejection>,
+) -> ApiResult<Json<ReservationJson>> {
+    let Path(id) = id?;
+    let rsvp = svc.manager.delete(id).await?;
+    Ok(Json((&rsvp).into()))
+}
+
+async fn query(
+    State(svc): State<Arc<RsvpService>>,
+    body: Result<Json<QueryParams>, JsonRejection>,
+) -> ApiResult<Json<QueryResult>> {
+    let Json(body) = body?;
+    let (pager, stream) = svc.manager.query_stream(body.try_into()?).await?;
+    // a page of size 0 is unbounded, stop buffering it past the limit
+    let reservations: Vec<abi::Reservation> =
+        stream.take(MAX_UNPAGED_ROWS + 1).try_collect().await?;
+    if reservations.len() > MAX_UNPAGED_ROWS {
+        return Err(abi::Error::InvalidPageSize(0).into());
+    }
+    Ok(Json(QueryResult {
+        reservations: reservations.iter().map(Into::into).collect(),
+        pager: pager.into(),
+    }))
+}
+
+async fn filter(
+    State(svc): State<Arc<RsvpService>>,
+    body: Result<Json<FilterParams>, JsonRejection>,
+) -> ApiResult<Json<FilterResult>> {
+    let Json(body) = body?;
+    let response = svc
+        .filter_page((&body).try_into()?, &body.page_token)
+        .await?;
+    Ok(Json(response.into()))
+}
+
+async fn availability(
+    State(svc): State<Arc<RsvpService>>,
+    body: Result<Json<AvailabilityParams>, JsonRejection>,
+) -> ApiResult<Json<AvailabilityResult>> {
+    let Json(body) = body?;
+    let slots = svc.manager.availability(body.try_into()?).await?;
+    Ok(Json(AvailabilityResult {
+        slots: slots.iter().map(Into::into).collect(),
+    }))
+}
+
+impl From<abi::Error> for ApiError {
+    fn from(e: abi::Error) -> Self {
+        Self(e.into())
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(e: JsonRejection) -> Self {
+        Self(tonic::Status::invalid_argument(e.body_text()))
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(e: PathRejection) -> Self {
+        Self(tonic::Status::invalid_argument(e.body_text()))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let detail = ErrorDetail::from_status(&self.0);
+        let conflict = detail
+            .as_ref()
+            .and_then(|d| d.conflict())
+            .and_then(|d| ReservationConflict::try_from(d).ok());
+        let body = ErrorBody {
+            code: format!("{:?}", self.0.code()),
+            message: self.0.message().to_string(),
+            field: detail
+                .as_ref()
+                .and_then(|d| d.invalid_argument())
+                .map(|d| d.field.clone()),
+            conflict: conflict.map(Into::into),
+            batch_conflicts: detail
+                .as_ref()
+                .and_then(|d| d.batch_conflict())
+                .map(|d| d.conflicts.iter().map(Into::into).collect()),
+            capacity_conflict: detail
+                .as_ref()
+                .and_then(|d| d.capacity_conflict())
+                .and_then(|d| CapacityConflict::try_from(d).ok())
+                .map(Into::into),
+        };
+        (http_status(self.0.code()), Json(body)).into_response()
+    }
+}
+
+fn http_status(code: Code) -> StatusCode {
+    match code {
+        Code::Ok => StatusCode::OK,
+        Code::InvalidArgument | Code::OutOfRange => StatusCode::BAD_REQUEST,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        // the reservation conflicts with an existing one
+        Code::FailedPrecondition | Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        Code::Cancelled | Code::Unknown | Code::Internal | Code::DataLoss => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use serde::de::DeserializeOwned;
+    use serde_json::{json, Value};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::test_utils::{new_service, seed_resources};
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn gateway_should_manage_reservation() {
+        let app = new_router(migrate_pool.clone()).await;
+
+        // nothing is created by a dry run
+        let mut body = new_reservation("room-1");
+        body["dry_run"] = json!(true);
+        let (status, rsvp): (_, ReservationJson) =
+            call(&app, "POST", "/reservations", Some(body.clone())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(rsvp.id, 0);
+
+        let (status, rsvp): (_, ReservationJson) = call(
+            &app,
+            "POST",
+            "/reservations",
+            Some(new_reservation("room-1")),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(rsvp.status, Status::Pending);
+        let (status, _): (_, ErrorBody) = call(&app, "POST", "/reservations", Some(body)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(
+            rsvp.start.unwrap().to_rfc3339(),
+            "2022-12-25T22:00:00+00:00"
+        );
+
+        let uri = format!("/reservations/{}", rsvp.id);
+        let (status, rsvp1): (_, ReservationJson) = call(&app, "GET", &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(rsvp1, rsvp);
+
+        let patch = json!({"note": "hello world", "status": "confirmed"});
+        let (status, rsvp1): (_, ReservationJson) = call(&app, "PATCH", &uri, Some(patch)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(rsvp1.note, "hello world");
+        assert_eq!(rsvp1.status, Status::Confirmed);
+
+        // already confirmed, the note isn't changed either
+        let patch = json!({"note": "hello again", "status": "confirmed"});
+        let (status, _): (_, ErrorBody) = call(&app, "PATCH", &uri, Some(patch)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (_, rsvp3): (_, ReservationJson) = call(&app, "GET", &uri, None).await;
+        assert_eq!(rsvp3, rsvp1);
+
+        let (status, rsvp2): (_, ReservationJson) = call(&app, "DELETE", &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(rsvp2, rsvp1);
+
+        let (status, err): (_, ErrorBody) = call(&app, "GET", &uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(err.code, "NotFound");
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn gateway_should_map_errors() {
+        let app = new_router(migrate_pool.clone()).await;
+        let body = new_reservation("room-1");
+        let (status, rsvp): (_, ReservationJson) =
+            call(&app, "POST", "/reservations", Some(body)).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        // conflict reservation
+        let body = new_reservation("room-1");
+        let (status, err): (_, ErrorBody) = call(&app, "POST", "/reservations", Some(body)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(err.code, "FailedPrecondition");
+        assert!(err
+            .message
+            .starts_with("Conflict reservation: Key (resource_id, timespan)"));
+        let conflict = err.conflict.unwrap();
+        assert_eq!(conflict.existing.id, rsvp.id);
+        assert_eq!(conflict.conflicts, vec![rsvp.clone()]);
+        assert_eq!(conflict.new.id, 0);
+        assert_eq!(conflict.new.resource_id, "room-1");
+        assert_eq!(conflict.relation, types::Relation::Same);
+        assert!(conflict.suggestions.is_empty());
+
+        // suggest the free windows
+        let mut body = new_reservation("room-1");
+        body["suggest"] = json!(true);
+        body["alternative_resource_ids"] = json!(["room-2"]);
+        let (status, err): (_, ErrorBody) = call(&app, "POST", "/reservations", Some(body)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let suggestions = err.conflict.unwrap().suggestions;
+        assert_eq!(suggestions.len(), 3);
+        assert_eq!(suggestions[2].resource_id, "room-2");
+        assert_eq!(suggestions[2].start, rsvp.start);
+
+        let (status, err): (_, ErrorBody) = call(&app, "GET", "/reservations/0", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(err.message, "Invalid reservation id: 0");
+        assert_eq!(err.field.as_deref(), Some("id"));
+
+        let (status, err): (_, ErrorBody) = call(&app, "GET", "/reservations/abc", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(err.code, "InvalidArgument");
+
+        let body = json!({"user_id": "chalanziId"});
+        let (status, _): (_, ErrorBody) = call(&app, "POST", "/reservations", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let body = json!({"status": "blocked"});
+        let (status, err): (_, ErrorBody) =
+            call(&app, "PATCH", "/reservations/1", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(err.message, "Invalid reservation status: 3");
+
+        // a new reservation is either pending or confirmed
+        let mut body = new_reservation("room-2");
+        body["status"] = json!("blocked");
+        let (status, err): (_, ErrorBody) = call(&app, "POST", "/reservations", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(err.message, "Invalid reservation status: 3");
+
+        // the quantity exceeds the capacity of the resource
+        let mut body = new_reservation("room-2");
+        body["quantity"] = json!(2);
+        let (status, err): (_, ErrorBody) = call(&app, "POST", "/reservations", Some(body)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let conflict = err.capacity_conflict.unwrap();
+        assert_eq!(conflict.new.resource_id, "room-2");
+        assert_eq!((conflict.quantity, conflict.capacity), (2, 1));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn gateway_should_query_and_filter() {
+        let app = new_router(migrate_pool.clone()).await;
+        for i in 0..15 {
+            let body = new_reservation(&format!("room-{}", i));
+            let (status, _): (_, Value) = call(&app, "POST", "/reservations", Some(body)).await;
+            assert_eq!(status, StatusCode::CREATED);
+        }
+
+        let body = json!({
+            "user_id": "chalanziId",
+            "start": "2022-12-26T00:00:00-07:00",
+            "end": "2022-12-27T00:00:00-07:00",
+            "match_mode": "overlapping",
+            "statuses": ["pending"],
+        });
+        let (status, result): (_, QueryResult) =
+            call(&app, "POST", "/reservations/query", Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(result.reservations.len(), 10);
+        assert_eq!(result.pager.total, Some(15));
+        assert_eq!(result.pager.next, Some(2));
+
+        let body = json!({"user_id": "chalanziId", "page_size": 10});
+        let (status, page1): (_, FilterResult) =
+            call(&app, "POST", "/reservations/filter", Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(page1.reservations.len(), 10);
+        assert!(page1.prev_page_token.is_empty());
+
+        let body =
+            json!({"user_id": "chalanziId", "page_size": 10, "page_token": page1.next_page_token});
+        let (status, page2): (_, FilterResult) =
+            call(&app, "POST", "/reservations/filter", Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(page2.reservations.len(), 5);
+        assert!(page2.next_page_token.is_empty());
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn gateway_should_search_availability() {
+        let app = new_router(migrate_pool.clone()).await;
+        let body = new_reservation("room-1");
+        let (status, _): (_, Value) = call(&app, "POST", "/reservations", Some(body)).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let body = json!({
+            "resource_ids": ["room-1", "room-2"],
+            "start": "2022-12-25T00:00:00-07:00",
+            "end": "2022-12-29T00:00:00-07:00",
+            "min_duration": 3600 * 12,
+            "granularity": 3600,
+        });
+        let (status, result): (_, AvailabilityResult) =
+            call(&app, "POST", "/availability", Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        let slots: Vec<_> = result
+            .slots
+            .iter()
+            .map(|slot| {
+                (
+                    slot.resource_id.as_str(),
+                    slot.start.unwrap().to_rfc3339(),
+                    slot.end.unwrap().to_rfc3339(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            slots,
+            vec![
+                (
+                    "room-1",
+                    "2022-12-25T07:00:00+00:00".to_string(),
+                    "2022-12-25T22:00:00+00:00".to_string()
+                ),
+                (
+                    "room-1",
+                    "2022-12-28T19:00:00+00:00".to_string(),
+                    "2022-12-29T07:00:00+00:00".to_string()
+                ),
+                (
+                    "room-2",
+                    "2022-12-25T07:00:00+00:00".to_string(),
+                    "2022-12-29T07:00:00+00:00".to_string()
+                ),
+            ]
+        );
+
+        let body = json!({
+            "resource_ids": [],
+            "start": "2022-12-25T00:00:00-07:00",
+            "end": "2022-12-29T00:00:00-07:00",
+        });
+        let (status, err): (_, ErrorBody) = call(&app, "POST", "/availability", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(err.field.as_deref(), Some("resource_id"));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn gateway_should_reserve_batch() {
+        let app = new_router(migrate_pool.clone()).await;
+        let body =
+            json!({"reservations": [new_reservation("room-1"), new_reservation("projector-1")]});
+        let (status, result): (_, BatchResult) =
+            call(&app, "POST", "/reservations/batch", Some(body)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(result.reservations.len(), 2);
+        assert_eq!(result.reservations[1].resource_id, "projector-1");
+
+        let mut over = new_reservation("room-2");
+        over["quantity"] = json!(2);
+        let body = json!({"reservations": [new_reservation("parking-1"), new_reservation("room-1"), over]});
+        let (status, err): (_, ErrorBody) =
+            call(&app, "POST", "/reservations/batch", Some(body)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let conflicts = err.batch_conflicts.unwrap();
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].index, 1);
+        let conflict = conflicts[0].conflict.as_ref().unwrap();
+        assert_eq!(conflict.existing.id, result.reservations[0].id);
+        assert_eq!(conflicts[1].index, 2);
+        assert!(conflicts[1].conflict.is_none());
+        let conflict = conflicts[1].capacity_conflict.as_ref().unwrap();
+        assert_eq!((conflict.quantity, conflict.capacity), (2, 1));
+
+        // the parking is not made
+        let body = json!({"reservations": [new_reservation("parking-1")]});
+        let (status, _): (_, BatchResult) =
+            call(&app, "POST", "/reservations/batch", Some(body)).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        // the options of a single reservation are rejected instead of ignored
+        for (option, value) in [
+            ("dry_run", json!(true)),
+            ("suggest", json!(true)),
+            ("alternative_resource_ids", json!(["room-2"])),
+        ] {
+            let mut rsvp = new_reservation("room-2");
+            rsvp[option] = value;
+            let body = json!({ "reservations": [rsvp] });
+            let (status, _): (_, ErrorBody) =
+                call(&app, "POST", "/reservations/batch", Some(body)).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+        }
+        let mut rsvp = new_reservation("room-2");
+        rsvp["status"] = json!("blocked");
+        let body = json!({ "reservations": [rsvp] });
+        let (status, err): (_, ErrorBody) =
+            call(&app, "POST", "/reservations/batch", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(err.message, "Invalid reservation status: 3");
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn gateway_should_reserve_in_pool() {
+        let app = new_router(migrate_pool.clone()).await;
+        sqlx::query("UPDATE rsvp.resources SET type = 'room' WHERE id IN ('room-1', 'room-2')")
+            .execute(&migrate_pool)
+            .await
+            .unwrap();
+        let (status, _): (_, ReservationJson) = call(
+            &app,
+            "POST",
+            "/reservations",
+            Some(new_reservation("room-1")),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let mut body = new_reservation("");
+        body["pool"] = json!({"type": "room", "strategy": "best_fit"});
+        let (status, rsvp): (_, ReservationJson) =
+            call(&app, "POST", "/reservations", Some(body.clone())).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(rsvp.resource_id, "room-2");
+        let (status, err): (_, ErrorBody) =
+            call(&app, "POST", "/reservations", Some(body.clone())).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(err.message, "No available resource of type: room");
+
+        body["resource_id"] = json!("room-3");
+        let (status, err): (_, ErrorBody) = call(&app, "POST", "/reservations", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(err.field.as_deref(), Some("resource_id"));
+    }
+
+    #[test]
+    fn openapi_should_document_all_routes() {
+        let doc: serde_yaml::Value = serde_yaml::from_str(OPENAPI).unwrap();
+        let paths: Vec<&str> = doc["paths"]
+            .as_mapping()
+            .unwrap()
+            .keys()
+            .map(|k| k.as_str().unwrap())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/reservations",
+                "/reservations/batch",
+                "/reservations/query",
+                "/reservations/filter",
+                "/reservations/{id}",
+                "/availability",
+            ]
+        );
+        let methods: Vec<&str> = doc["paths"]["/reservations/{id}"]
+            .as_mapping()
+            .unwrap()
+            .keys()
+            .filter_map(|k| k.as_str())
+            .filter(|k| *k != "parameters")
+            .collect();
+        assert_eq!(methods, vec!["get", "patch", "delete"]);
+    }
+
+    /// the gateway of the database seeded with the resources reserved in the tests
+    async fn new_router(pool: PgPool) -> Router {
+        seed_resources(&pool).await;
+        router(Arc::new(new_service(pool)))
+    }
+
+    fn new_reservation(rid: &str) -> Value {
+        json!({
+            "user_id": "chalanziId",
+            "resource_id": rid,
+            "start": "2022-12-25T15:00:00-07:00",
+            "end": "2022-12-28T12:00:00-07:00",
+            "note": "我将与下午3点到达，请帮忙预约",
+        })
+    }
+
+    async fn call<T: DeserializeOwned>(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, T) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json");
+        let body = match body {
+            Some(body) => Body::from(body.to_string()),
+            None => Body::empty(),
+        };
+        let response = app
+            .clone()
+            .oneshot(request.body(body).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+}
@@ -0,0 +1,572 @@
+use abi::{
+    convert_to_timestamp, convert_to_utc_time, AllocationStrategy, AvailabilityQuery,
+    AvailabilityQueryBuilder, BatchConflict, CapacityConflict, Error, FilterPager, FilterResponse,
+    FreeSlot, Reservation, ReservationConflict, ReservationFilter, ReservationFilterBuilder,
+    ReservationMatchMode, ReservationQuery, ReservationQueryBuilder, ReservationRequest,
+    ReservationStatus, ReservationWindow, ResourcePool, ResourceRelation,
+};
+use chrono::{DateTime, FixedOffset, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Unknown,
+    Pending,
+    Confirmed,
+    Blocked,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Relation {
+    Unknown,
+    Same,
+    Ancestor,
+    Descendant,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchMode {
+    #[default]
+    Contained,
+    Overlapping,
+    StartingWithin,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReservationJson {
+    pub id: i64,
+    pub user_id: String,
+    pub resource_id: String,
+    pub status: Status,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub note: String,
+    #[serde(default)]
+    pub series_id: i64,
+    #[serde(default)]
+    pub quantity: i32,
+}
+
+/// body of POST /reservations
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewReservation {
+    pub user_id: String,
+    /// empty if a pool is given
+    #[serde(default)]
+    pub resource_id: String,
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+    #[serde(default)]
+    pub note: String,
+    /// pending if not set
+    #[serde(default)]
+    pub status: Option<Status>,
+    /// units of the resource reserved, 1 if not set
+    #[serde(default)]
+    pub quantity: i32,
+    /// reserve any free resource of the pool, the resource_id must be empty
+    #[serde(default)]
+    pub pool: Option<PoolJson>,
+    /// suggest the free windows of the same length if the reservation conflicts
+    #[serde(default)]
+    pub suggest: bool,
+    /// the resources to try the same window on when suggesting
+    #[serde(default)]
+    pub alternative_resource_ids: Vec<String>,
+    /// only check the reservation against the existing ones, it's not made
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl NewReservation {
+    pub fn validate_status(&self) -> Result<(), Error> {
+        validate_new_status(self.status)
+    }
+}
+
+impl BatchReservation {
+    pub fn validate_status(&self) -> Result<(), Error> {
+        validate_new_status(self.status)
+    }
+}
+
+/// a new reservation could only be pending or confirmed
+fn validate_new_status(status: Option<Status>) -> Result<(), Error> {
+    match status {
+        None | Some(Status::Pending | Status::Confirmed) => Ok(()),
+        Some(status) => Err(Error::InvalidStatus(ReservationStatus::from(status) as i32)),
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PoolJson {
+    /// type of the resources in the catalog
+    pub r#type: String,
+    #[serde(default)]
+    pub strategy: Strategy,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    #[default]
+    FirstFit,
+    LeastUsed,
+    BestFit,
+}
+
+/// body of POST /reservations/batch
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchParams {
+    pub reservations: Vec<BatchReservation>,
+}
+
+/// a reservation of the batch, the options of POST /reservations aren't supported
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchReservation {
+    pub user_id: String,
+    pub resource_id: String,
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+    #[serde(default)]
+    pub note: String,
+    /// pending if not set
+    #[serde(default)]
+    pub status: Option<Status>,
+    /// units of the resource reserved, 1 if not set
+    #[serde(default)]
+    pub quantity: i32,
+}
+
+/// body of PATCH /reservations/{id}, the fields not set are kept
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReservationPatch {
+    pub note: Option<String>,
+    /// only a pending reservation could be changed to confirmed
+    pub status: Option<Status>,
+}
+
+/// body of POST /reservations/query
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QueryParams {
+    pub user_id: String,
+    pub resource_id: String,
+    pub start: Option<DateTime<FixedOffset>>,
+    pub end: Option<DateTime<FixedOffset>>,
+    pub statuses: Vec<Status>,
+    pub match_mode: MatchMode,
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+    pub desc: bool,
+}
+
+/// body of POST /reservations/filter
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilterParams {
+    pub user_id: String,
+    pub resource_id: String,
+    pub statuses: Vec<Status>,
+    pub page_size: Option<i64>,
+    pub desc: bool,
+    /// prev_page_token or next_page_token of the last result
+    pub page_token: String,
+}
+
+/// body of POST /availability
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AvailabilityParams {
+    pub resource_ids: Vec<String>,
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+    /// in seconds
+    #[serde(default)]
+    pub min_duration: i64,
+    /// in seconds, the slots are not aligned if it's 0
+    #[serde(default)]
+    pub granularity: i64,
+    /// units of a shared resource needed in the slots, 1 if not set
+    #[serde(default)]
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PagerJson {
+    pub prev: Option<i64>,
+    pub next: Option<i64>,
+    pub total: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryResult {
+    pub reservations: Vec<ReservationJson>,
+    pub pager: PagerJson,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterResult {
+    pub reservations: Vec<ReservationJson>,
+    pub total: Option<i64>,
+    pub prev_page_token: String,
+    pub next_page_token: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchResult {
+    pub reservations: Vec<ReservationJson>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AvailabilityResult {
+    pub slots: Vec<SlotJson>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotJson {
+    pub resource_id: String,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorBody {
+    /// name of the grpc status code, e.g. InvalidArgument
+    pub code: String,
+    pub message: String,
+    /// the invalid field of the request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    /// the existing reservation which the new one conflicts with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conflict: Option<ConflictJson>,
+    /// the conflicts of a batch, none of the reservations is made
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_conflicts: Option<Vec<BatchConflictJson>>,
+    /// the new reservation exceeds the capacity of the resource
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity_conflict: Option<CapacityConflictJson>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchConflictJson {
+    /// index of the reservation in the batch
+    pub index: i64,
+    pub description: String,
+    /// None if the conflict couldn't be parsed, or the reservation exceeds the capacity
+    pub conflict: Option<ConflictJson>,
+    /// the reservation exceeds the capacity of the resource
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity_conflict: Option<CapacityConflictJson>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConflictJson {
+    pub new: WindowJson,
+    pub existing: WindowJson,
+    /// the resource of the existing reservation relative to the new one
+    pub relation: Relation,
+    /// all the existing reservations overlapping the new one
+    pub conflicts: Vec<ReservationJson>,
+    /// the free windows of the same length, only if asked by the request
+    pub suggestions: Vec<SlotJson>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapacityConflictJson {
+    pub new: WindowJson,
+    pub quantity: i32,
+    pub capacity: i32,
+    /// the most units reserved at the same time by the existing reservations
+    pub reserved: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowJson {
+    /// id of the existing reservation, 0 for the new one
+    pub id: i64,
+    pub resource_id: String,
+    /// None if the window is unbounded at the start
+    pub start: Option<DateTime<Utc>>,
+    /// None if the window is unbounded at the end
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl From<ReservationStatus> for Status {
+    fn from(status: ReservationStatus) -> Self {
+        match status {
+            ReservationStatus::Unknown => Status::Unknown,
+            ReservationStatus::Pending => Status::Pending,
+            ReservationStatus::Confirmed => Status::Confirmed,
+            ReservationStatus::Blocked => Status::Blocked,
+        }
+    }
+}
+
+impl From<Status> for ReservationStatus {
+    fn from(status: Status) -> Self {
+        match status {
+            Status::Unknown => ReservationStatus::Unknown,
+            Status::Pending => ReservationStatus::Pending,
+            Status::Confirmed => ReservationStatus::Confirmed,
+            Status::Blocked => ReservationStatus::Blocked,
+        }
+    }
+}
+
+impl From<PoolJson> for ResourcePool {
+    fn from(pool: PoolJson) -> Self {
+        let strategy = match pool.strategy {
+            Strategy::FirstFit => AllocationStrategy::FirstFit,
+            Strategy::LeastUsed => AllocationStrategy::LeastUsed,
+            Strategy::BestFit => AllocationStrategy::BestFit,
+        };
+        Self {
+            r#type: pool.r#type,
+            strategy: strategy as i32,
+        }
+    }
+}
+
+impl From<MatchMode> for ReservationMatchMode {
+    fn from(mode: MatchMode) -> Self {
+        match mode {
+            MatchMode::Contained => ReservationMatchMode::Contained,
+            MatchMode::Overlapping => ReservationMatchMode::Overlapping,
+            MatchMode::StartingWithin => ReservationMatchMode::StartingWithin,
+        }
+    }
+}
+
+impl From<&Reservation> for ReservationJson {
+    fn from(rsvp: &Reservation) -> Self {
+        let status = ReservationStatus::from_i32(rsvp.status).unwrap_or(ReservationStatus::Unknown);
+        Self {
+            id: rsvp.id,
+            user_id: rsvp.user_id.clone(),
+            resource_id: rsvp.resource_id.clone(),
+            status: status.into(),
+            start: rsvp.start.as_ref().map(convert_to_utc_time),
+            end: rsvp.end.as_ref().map(convert_to_utc_time),
+            note: rsvp.note.clone(),
+            series_id: rsvp.series_id,
+            quantity: rsvp.quantity,
+        }
+    }
+}
+
+impl From<NewReservation> for ReservationRequest {
+    fn from(mut body: NewReservation) -> Self {
+        Self {
+            suggest: body.suggest,
+            alternative_resource_ids: std::mem::take(&mut body.alternative_resource_ids),
+            dry_run: body.dry_run,
+            pool: body.pool.take().map(Into::into),
+            reservation: Some(body.into()),
+        }
+    }
+}
+
+impl From<NewReservation> for Reservation {
+    fn from(body: NewReservation) -> Self {
+        let mut rsvp = Reservation::new_pending(
+            body.user_id,
+            body.resource_id,
+            body.start,
+            body.end,
+            body.note,
+        );
+        if let Some(status) = body.status {
+            rsvp.status = ReservationStatus::from(status) as i32;
+        }
+        rsvp.quantity = body.quantity;
+        rsvp
+    }
+}
+
+impl From<BatchReservation> for Reservation {
+    fn from(body: BatchReservation) -> Self {
+        let mut rsvp = Reservation::new_pending(
+            body.user_id,
+            body.resource_id,
+            body.start,
+            body.end,
+            body.note,
+        );
+        if let Some(status) = body.status {
+            rsvp.status = ReservationStatus::from(status) as i32;
+        }
+        rsvp.quantity = body.quantity;
+        rsvp
+    }
+}
+
+impl TryFrom<QueryParams> for ReservationQuery {
+    type Error = Error;
+
+    fn try_from(params: QueryParams) -> Result<Self, Self::Error> {
+        let mut builder = ReservationQueryBuilder::default();
+        builder
+            .user_id(params.user_id)
+            .resource_id(params.resource_id)
+            .statuses(statuses(&params.statuses))
+            .match_mode(ReservationMatchMode::from(params.match_mode) as i32)
+            .desc(params.desc);
+        if let Some(start) = params.start {
+            builder.start(convert_to_timestamp(&start.with_timezone(&Utc)));
+        }
+        if let Some(end) = params.end {
+            builder.end(convert_to_timestamp(&end.with_timezone(&Utc)));
+        }
+        if let Some(page) = params.page {
+            builder.page(page);
+        }
+        if let Some(page_size) = params.page_size {
+            builder.page_size(page_size);
+        }
+        builder.build()
+    }
+}
+
+impl TryFrom<&FilterParams> for ReservationFilter {
+    type Error = Error;
+
+    fn try_from(params: &FilterParams) -> Result<Self, Self::Error> {
+        let mut builder = ReservationFilterBuilder::default();
+        builder
+            .user_id(params.user_id.clone())
+            .resource_id(params.resource_id.clone())
+            .statuses(statuses(&params.statuses))
+            .desc(params.desc);
+        if let Some(page_size) = params.page_size {
+            builder.page_size(page_size);
+        }
+        builder.build()
+    }
+}
+
+impl TryFrom<AvailabilityParams> for AvailabilityQuery {
+    type Error = Error;
+
+    fn try_from(params: AvailabilityParams) -> Result<Self, Self::Error> {
+        AvailabilityQueryBuilder::default()
+            .resource_ids(params.resource_ids)
+            .start(convert_to_timestamp(&params.start.with_timezone(&Utc)))
+            .end(convert_to_timestamp(&params.end.with_timezone(&Utc)))
+            .min_duration(params.min_duration)
+            .granularity(params.granularity)
+            .quantity(params.quantity)
+            .build()
+    }
+}
+
+impl From<&FreeSlot> for SlotJson {
+    fn from(slot: &FreeSlot) -> Self {
+        Self {
+            resource_id: slot.resource_id.clone(),
+            start: slot.start.as_ref().map(convert_to_utc_time),
+            end: slot.end.as_ref().map(convert_to_utc_time),
+        }
+    }
+}
+
+impl From<ReservationConflict> for ConflictJson {
+    fn from(conflict: ReservationConflict) -> Self {
+        Self {
+            new: conflict.new.into(),
+            existing: conflict.old.into(),
+            relation: conflict.relation.into(),
+            conflicts: conflict.conflicts.iter().map(Into::into).collect(),
+            suggestions: conflict.suggestions.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<ResourceRelation> for Relation {
+    fn from(relation: ResourceRelation) -> Self {
+        match relation {
+            ResourceRelation::Unknown => Relation::Unknown,
+            ResourceRelation::Same => Relation::Same,
+            ResourceRelation::Ancestor => Relation::Ancestor,
+            ResourceRelation::Descendant => Relation::Descendant,
+        }
+    }
+}
+
+impl From<CapacityConflict> for CapacityConflictJson {
+    fn from(conflict: CapacityConflict) -> Self {
+        Self {
+            new: conflict.new.into(),
+            quantity: conflict.quantity,
+            capacity: conflict.capacity,
+            reserved: conflict.reserved,
+        }
+    }
+}
+
+impl From<&BatchConflict> for BatchConflictJson {
+    fn from(conflict: &BatchConflict) -> Self {
+        Self {
+            index: conflict.index,
+            description: conflict.description.clone(),
+            conflict: conflict
+                .conflict
+                .as_ref()
+                .and_then(|c| ReservationConflict::try_from(c).ok())
+                .map(Into::into),
+            capacity_conflict: conflict
+                .capacity_conflict
+                .as_ref()
+                .and_then(|c| CapacityConflict::try_from(c).ok())
+                .map(Into::into),
+        }
+    }
+}
+
+impl From<ReservationWindow> for WindowJson {
+    fn from(window: ReservationWindow) -> Self {
+        Self {
+            id: window.id,
+            resource_id: window.rid,
+            start: window.start,
+            end: window.end,
+        }
+    }
+}
+
+impl From<FilterPager> for PagerJson {
+    fn from(pager: FilterPager) -> Self {
+        Self {
+            prev: pager.prev,
+            next: pager.next,
+            total: pager.total,
+        }
+    }
+}
+
+impl From<FilterResponse> for FilterResult {
+    fn from(response: FilterResponse) -> Self {
+        Self {
+            reservations: response.reservations.iter().map(Into::into).collect(),
+            total: response.pager.and_then(|pager| pager.total),
+            prev_page_token: response.prev_page_token,
+            next_page_token: response.next_page_token,
+        }
+    }
+}
+
+fn statuses(statuses: &[Status]) -> Vec<i32> {
+    statuses
+        .iter()
+        .map(|s| ReservationStatus::from(*s) as i32)
+        .collect()
+}
@@ -1,16 +1,50 @@
+mod gateway;
+mod resource;
 mod service;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 
-use std::pin::Pin;
+pub use gateway::router;
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
 
-use abi::Reservation;
 use futures::Stream;
 use reservation::ReservationManager;
+use tokio::sync::mpsc;
 use tonic::Status;
 
-pub use service::*;
-
 pub struct RsvpService {
     pub manager: ReservationManager,
+    /// secret to sign the page tokens of filter
+    pub page_token_secret: String,
+}
+
+/// forward the items received from the manager to the client, convert abi::Error to Status
+pub struct TonicReceiverStream<T> {
+    inner: mpsc::Receiver<Result<T, abi::Error>>,
+}
+
+impl<T> TonicReceiverStream<T> {
+    pub fn new(inner: mpsc::Receiver<Result<T, abi::Error>>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T> Stream for TonicReceiverStream<T> {
+    type Item = Result<T, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.inner.poll_recv(cx) {
+            Poll::Ready(Some(Ok(item))) => Poll::Ready(Some(Ok(item))),
+            Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e.into()))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
 }
 
-type ReservationStream = Pin<Box<dyn Stream<Item = Result<Reservation, Status>> + Send>>;
+type ReservationStream = TonicReceiverStream<abi::Reservation>;
+type ListenStream = TonicReceiverStream<abi::ListenResponse>;
@@ -1,8 +1,11 @@
-use std::path::Path;
+use std::{net::SocketAddr, path::Path, sync::Arc};
 
-use abi::{reservation_service_server::ReservationServiceServer, Config};
+use abi::{
+    reservation_service_server::ReservationServiceServer,
+    resource_service_server::ResourceServiceServer, Config,
+};
 use anyhow::Result;
-use reservation_service::RsvpService;
+use reservation_service::{router, RsvpService};
 use tonic::transport::Server;
 
 #[tokio::main]
@@ -28,9 +31,23 @@ async fn main() -> Result<()> {
     let addr = format!("{}:{}", config.server.host, config.server.port).parse()?;
     println!("server addr:{:?}", addr);
     //let addr = config.server.url(false).parse()?;
-    let svc = RsvpService::from_config(&config).await?;
-    let svc = ReservationServiceServer::new(svc);
-    Server::builder().add_service(svc).serve(addr).await?;
+    let svc = Arc::new(RsvpService::from_config(&config).await?);
+    let grpc = Server::builder()
+        .add_service(ReservationServiceServer::from_arc(svc.clone()))
+        .add_service(ResourceServiceServer::from_arc(svc.clone()))
+        .serve(addr);
+
+    match config.server.http_port {
+        Some(port) => {
+            let http_addr: SocketAddr = format!("{}:{}", config.server.host, port).parse()?;
+            println!("http addr:{:?}", http_addr);
+            let http = axum::Server::try_bind(&http_addr)?.serve(router(svc).into_make_service());
+            tokio::try_join!(async { grpc.await.map_err(anyhow::Error::from) }, async {
+                http.await.map_err(anyhow::Error::from)
+            },)?;
+        }
+        None => grpc.await?,
+    }
 
     Ok(())
 }
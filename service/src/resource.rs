@@ -0,0 +1,142 @@
+use abi::{
+    resource_service_server::ResourceService, CreateResourceRequest, CreateResourceResponse,
+    DeactivateResourceRequest, DeactivateResourceResponse, ListResourcesRequest,
+    ListResourcesResponse, UpdateResourceRequest, UpdateResourceResponse,
+};
+use reservation::Catalog;
+use tonic::{async_trait, Request, Response, Status};
+
+use crate::RsvpService;
+
+#[async_trait]
+impl ResourceService for RsvpService {
+    /// add a resource to the catalog
+    async fn create(
+        &self,
+        request: Request<CreateResourceRequest>,
+    ) -> Result<Response<CreateResourceResponse>, Status> {
+        let Some(resource) = request.into_inner().resource else {
+            return Err(Status::invalid_argument("missing resource"));
+        };
+        let resource = self.manager.create_resource(resource).await?;
+        Ok(Response::new(CreateResourceResponse {
+            resource: Some(resource),
+        }))
+    }
+
+    /// change a resource, the attributes are merged
+    async fn update(
+        &self,
+        request: Request<UpdateResourceRequest>,
+    ) -> Result<Response<UpdateResourceResponse>, Status> {
+        let resource = self.manager.update_resource(request.into_inner()).await?;
+        Ok(Response::new(UpdateResourceResponse {
+            resource: Some(resource),
+        }))
+    }
+
+    /// stop the resource from being reserved
+    async fn deactivate(
+        &self,
+        request: Request<DeactivateResourceRequest>,
+    ) -> Result<Response<DeactivateResourceResponse>, Status> {
+        let resource = self
+            .manager
+            .deactivate_resource(request.into_inner().id)
+            .await?;
+        Ok(Response::new(DeactivateResourceResponse {
+            resource: Some(resource),
+        }))
+    }
+
+    /// list the resources order by id
+    async fn list(
+        &self,
+        request: Request<ListResourcesRequest>,
+    ) -> Result<Response<ListResourcesResponse>, Status> {
+        let resources = self.manager.list_resources(request.into_inner()).await?;
+        Ok(Response::new(ListResourcesResponse { resources }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use abi::{resource_service_client::ResourceServiceClient, Resource};
+    use sqlx::PgPool;
+    use tonic::{transport::Channel, Code};
+
+    use super::*;
+    use crate::test_utils::serve;
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn rpc_resources_should_work() {
+        let mut client = start_server(migrate_pool.clone()).await;
+        let resource = client
+            .create(CreateResourceRequest {
+                resource: Some(Resource::new("room-1", "room")),
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .resource
+            .unwrap();
+        assert_eq!(resource.name, "room-1");
+        assert!(resource.active);
+        let err = client
+            .create(CreateResourceRequest {
+                resource: Some(Resource::new("room-1", "room")),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::AlreadyExists);
+
+        let resource = client
+            .update(UpdateResourceRequest {
+                id: "room-1".into(),
+                timezone: Some("Asia/Shanghai".into()),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .resource
+            .unwrap();
+        assert_eq!(resource.timezone, "Asia/Shanghai");
+
+        let resource = client
+            .deactivate(DeactivateResourceRequest {
+                id: "room-1".into(),
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .resource
+            .unwrap();
+        assert!(!resource.active);
+        let err = client
+            .deactivate(DeactivateResourceRequest {
+                id: "room-2".into(),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::NotFound);
+
+        let resources = client
+            .list(ListResourcesRequest {
+                include_inactive: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .resources;
+        assert_eq!(resources, vec![resource]);
+    }
+
+    /// a client of the service served on a random local port, the catalog is empty
+    async fn start_server(pool: PgPool) -> ResourceServiceClient<Channel> {
+        ResourceServiceClient::connect(serve(pool).await)
+            .await
+            .unwrap()
+    }
+}
@@ -1,22 +1,113 @@
 use abi::{
-    reservation_service_server::ReservationService, CancelRequest, CancelResponse, ConfirmRequest,
-    ConfirmResponse, FilterRequest, FilterResponse, GetRequest, GetResponse, ListenRequest,
-    QueryRequest, ReservationRequest, ReservationResponse, UpdateRequest, UpdateResponse,
+    reservation_service_server::ReservationService, AckRequest, AckResponse, AvailabilityRequest,
+    AvailabilityResponse, CancelRequest, CancelResponse, CancelSeriesRequest, CancelSeriesResponse,
+    ConfirmRequest, ConfirmResponse, FilterRequest, FilterResponse, GetRequest, GetResponse,
+    ListenRequest, QueryRequest, ReservationRequest, ReservationResponse, ReserveBatchRequest,
+    ReserveBatchResponse, ReserveSeriesRequest, ReserveSeriesResponse, UpdateRequest,
+    UpdateResponse, UpdateSeriesRequest, UpdateSeriesResponse,
 };
 
 use reservation::{ReservationManager, Rsvp};
 use tonic::{async_trait, Request, Response, Status};
 
-use abi::Config;
+use abi::{
+    Config, FilterPager, Normalizer, PageToken, Reservation, ReservationConflictInfo,
+    ReservationFilter,
+};
 
-use crate::{ReservationStream, RsvpService};
+use crate::{ListenStream, ReservationStream, RsvpService, TonicReceiverStream};
 
 impl RsvpService {
     pub async fn from_config(config: &Config) -> Result<Self, anyhow::Error> {
+        if config.server.page_token_secret.is_empty() {
+            anyhow::bail!("page_token_secret is not set in the server config");
+        }
+
         Ok(Self {
             manager: ReservationManager::from_config(&config.db).await?,
+            page_token_secret: config.server.page_token_secret.clone(),
+        })
+    }
+
+    /// get a page of the filter, the cursor is only accepted from the page token
+    pub async fn filter_page(
+        &self,
+        mut filter: ReservationFilter,
+        page_token: &str,
+    ) -> Result<FilterResponse, abi::Error> {
+        filter.normalize()?;
+
+        filter.cursor = None;
+        filter.backward = false;
+        if !page_token.is_empty() {
+            PageToken::decode(page_token, self.page_token_secret.as_bytes())?.apply(&mut filter)?;
+        }
+
+        let (pager, reservations) = self.manager.filter(filter.clone()).await?;
+        Ok(FilterResponse {
+            reservations,
+            pager: Some(FilterPager {
+                prev: None,
+                next: None,
+                total: pager.total,
+            }),
+            prev_page_token: self.page_token(filter.prev_page(&pager)),
+            next_page_token: self.page_token(filter.next_page(&pager)),
         })
     }
+
+    /// make the reservation of the request, or only check it if `dry_run` is set. If it conflicts
+    /// and `suggest` is set, the free windows of the same length are suggested in the conflict,
+    /// see `Rsvp::suggest`
+    pub async fn make_reservation(
+        &self,
+        request: ReservationRequest,
+    ) -> Result<Reservation, Status> {
+        let Some(rsvp) = request.reservation else {
+            return Err(Status::invalid_argument("missing reservation"));
+        };
+        if let Some(pool) = request.pool {
+            if request.suggest || !request.alternative_resource_ids.is_empty() {
+                return Err(Status::invalid_argument(
+                    "a pool can't be combined with suggestions or alternative resources",
+                ));
+            }
+            let ret = self.manager.reserve_in_pool(rsvp, pool, request.dry_run);
+            return Ok(ret.await?);
+        }
+        let alternatives = &request.alternative_resource_ids;
+        if let Some(rid) = alternatives.iter().find(|rid| rid.is_empty()) {
+            return Err(abi::Error::InvalidResourceId(rid.clone()).into());
+        }
+
+        let ret = if request.dry_run {
+            self.manager.check(rsvp.clone()).await
+        } else {
+            self.manager.reserve(rsvp.clone()).await
+        };
+        let mut conflict = match ret {
+            Err(abi::Error::ConflictReservation(ReservationConflictInfo::Parsed(conflict)))
+                if request.suggest =>
+            {
+                conflict
+            }
+            ret => return Ok(ret?),
+        };
+        // the conflict is returned anyway, the suggestions are the best effort
+        conflict.suggestions = self
+            .manager
+            .suggest(&rsvp, alternatives)
+            .await
+            .unwrap_or_default();
+        Err(abi::Error::ConflictReservation(ReservationConflictInfo::Parsed(conflict)).into())
+    }
+
+    fn page_token(&self, filter: Option<ReservationFilter>) -> String {
+        filter
+            .and_then(|filter| PageToken::new(&filter))
+            .map(|token| token.encode(self.page_token_secret.as_bytes()))
+            .unwrap_or_default()
+    }
 }
 
 #[async_trait]
@@ -26,70 +117,584 @@ impl ReservationService for RsvpService {
         &self,
         request: Request<ReservationRequest>,
     ) -> Result<Response<ReservationResponse>, Status> {
-        let request = request.into_inner();
-        if request.reservation.is_none() {
-            return Err(Status::invalid_argument("missing reservation"));
-        }
-        let reservation = self.manager.reserve(request.reservation.unwrap()).await?;
+        let reservation = self.make_reservation(request.into_inner()).await?;
         Ok(Response::new(ReservationResponse {
             reservation: Some(reservation),
         }))
     }
 
+    /// make the reservations in one transaction, either all of them or none
+    async fn reserve_batch(
+        &self,
+        request: Request<ReserveBatchRequest>,
+    ) -> Result<Response<ReserveBatchResponse>, Status> {
+        let reservations = self
+            .manager
+            .reserve_many(request.into_inner().reservations)
+            .await?;
+        Ok(Response::new(ReserveBatchResponse { reservations }))
+    }
+
+    /// make the occurrences of a recurring reservation, either all of them or none
+    async fn reserve_series(
+        &self,
+        request: Request<ReserveSeriesRequest>,
+    ) -> Result<Response<ReserveSeriesResponse>, Status> {
+        let request = request.into_inner();
+        let Some(rsvp) = request.reservation else {
+            return Err(Status::invalid_argument("missing reservation"));
+        };
+        let Some(rule) = request.rule else {
+            return Err(Status::invalid_argument("missing recurrence rule"));
+        };
+        let reservations = self.manager.reserve_series(rsvp, rule).await?;
+        Ok(Response::new(ReserveSeriesResponse { reservations }))
+    }
+
+    /// change the occurrences of a series in the scope
+    async fn update_series(
+        &self,
+        request: Request<UpdateSeriesRequest>,
+    ) -> Result<Response<UpdateSeriesResponse>, Status> {
+        let reservations = self.manager.update_series(request.into_inner()).await?;
+        Ok(Response::new(UpdateSeriesResponse { reservations }))
+    }
+
+    /// cancel the occurrences of a series in the scope, the canceled ones are returned
+    async fn cancel_series(
+        &self,
+        request: Request<CancelSeriesRequest>,
+    ) -> Result<Response<CancelSeriesResponse>, Status> {
+        let reservations = self.manager.cancel_series(request.into_inner()).await?;
+        Ok(Response::new(CancelSeriesResponse { reservations }))
+    }
+
+    /// confirm a pending reservation
     async fn confirm(
         &self,
-        _request: Request<ConfirmRequest>,
+        request: Request<ConfirmRequest>,
     ) -> Result<Response<ConfirmResponse>, Status> {
-        todo!()
+        let request = request.into_inner();
+        let reservation = self.manager.change_status(request.id).await?;
+        Ok(Response::new(ConfirmResponse {
+            reservation: Some(reservation),
+        }))
     }
 
+    /// update the note of a reservation
     async fn update(
         &self,
-        _request: Request<UpdateRequest>,
+        request: Request<UpdateRequest>,
     ) -> Result<Response<UpdateResponse>, Status> {
-        todo!()
+        let request = request.into_inner();
+        let reservation = self.manager.update_note(request.id, request.note).await?;
+        Ok(Response::new(UpdateResponse {
+            reservation: Some(reservation),
+        }))
     }
 
+    /// cancel a reservation, the canceled one is returned
     async fn cancel(
         &self,
-        _request: Request<CancelRequest>,
+        request: Request<CancelRequest>,
     ) -> Result<Response<CancelResponse>, Status> {
-        todo!()
+        let request = request.into_inner();
+        let reservation = self.manager.delete(request.id).await?;
+        Ok(Response::new(CancelResponse {
+            reservation: Some(reservation),
+        }))
     }
 
-    async fn get(&self, _request: Request<GetRequest>) -> Result<Response<GetResponse>, Status> {
-        todo!()
+    /// get a reservation by id
+    async fn get(&self, request: Request<GetRequest>) -> Result<Response<GetResponse>, Status> {
+        let request = request.into_inner();
+        let reservation = self.manager.get(request.id).await?;
+        Ok(Response::new(GetResponse {
+            reservation: Some(reservation),
+        }))
     }
 
     type queryStream = ReservationStream;
 
+    /// stream the reservations matching the query
     async fn query(
         &self,
-        _request: Request<QueryRequest>,
+        request: Request<QueryRequest>,
     ) -> Result<Response<Self::queryStream>, Status> {
-        todo!()
+        let Some(query) = request.into_inner().query else {
+            return Err(Status::invalid_argument("missing query params"));
+        };
+        let (pager, stream) = self.manager.query_stream(query).await?;
+        let mut response = Response::new(TonicReceiverStream::new(stream.into_inner()));
+        pager.to_metadata(response.metadata_mut());
+        Ok(response)
     }
 
+    /// filter reservations page by page, the pages are given as signed page tokens
     async fn filter(
         &self,
-        _request: Request<FilterRequest>,
+        request: Request<FilterRequest>,
     ) -> Result<Response<FilterResponse>, Status> {
-        todo!()
+        let request = request.into_inner();
+        if request.filter.is_none() {
+            return Err(Status::invalid_argument("missing filter"));
+        }
+        let response = self
+            .filter_page(request.filter.unwrap(), &request.page_token)
+            .await?;
+        Ok(Response::new(response))
     }
 
-    type listenStream = ReservationStream;
+    type listenStream = ListenStream;
 
+    /// stream the changes of reservations
     async fn listen(
         &self,
-        _request: Request<ListenRequest>,
+        request: Request<ListenRequest>,
     ) -> Result<Response<Self::listenStream>, Status> {
-        todo!()
+        let rx = self.manager.listen(request.into_inner()).await?;
+        Ok(Response::new(TonicReceiverStream::new(rx)))
+    }
+
+    /// acknowledge the changes processed by a durable consumer
+    async fn ack(&self, request: Request<AckRequest>) -> Result<Response<AckResponse>, Status> {
+        self.manager.ack(request.into_inner()).await?;
+        Ok(Response::new(AckResponse {}))
+    }
+
+    /// the free slots of the resources in the window
+    async fn availability(
+        &self,
+        request: Request<AvailabilityRequest>,
+    ) -> Result<Response<AvailabilityResponse>, Status> {
+        let request = request.into_inner();
+        if request.query.is_none() {
+            return Err(Status::invalid_argument("missing availability query"));
+        }
+        let slots = self.manager.availability(request.query.unwrap()).await?;
+        Ok(Response::new(AvailabilityResponse { slots }))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use abi::{
+        reservation_service_client::ReservationServiceClient, AllocationStrategy, ErrorDetail,
+        RecurrenceFrequency, RecurrenceRule, Reservation, ResourcePool, SeriesScope,
+    };
+    use sqlx::PgPool;
+    use tonic::{transport::Channel, Code};
+
+    use super::*;
+    use crate::test_utils::{new_reservation, seed_resources, serve};
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn rpc_reserve_should_work() {
+        let mut client = start_server(migrate_pool.clone()).await;
+        // a dry run doesn't make the reservation
+        let dry_run = ReservationRequest {
+            reservation: Some(new_reservation("ocean-view-room-713")),
+            dry_run: true,
+            ..Default::default()
+        };
+        let checked = client
+            .reserve(dry_run.clone())
+            .await
+            .unwrap()
+            .into_inner()
+            .reservation
+            .unwrap();
+        assert_eq!(checked, new_reservation("ocean-view-room-713"));
+
+        let rsvp = reserve(&mut client).await;
+        assert!(rsvp.id != 0);
+
+        let err = client.reserve(dry_run).await.unwrap_err();
+        assert_eq!(err.code(), Code::FailedPrecondition);
+        let detail = ErrorDetail::from_status(&err).unwrap();
+        assert_eq!(
+            detail.conflict().unwrap().existing.as_ref().unwrap().id,
+            rsvp.id
+        );
+
+        let err = client
+            .reserve(ReservationRequest {
+                reservation: None,
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+
+        // the same resource at the same time conflicts
+        let err = client
+            .reserve(ReservationRequest {
+                reservation: Some(new_reservation("ocean-view-room-713")),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::FailedPrecondition);
+        let detail = ErrorDetail::from_status(&err).unwrap();
+        assert!(detail.conflict().unwrap().suggestions.is_empty());
+
+        // the free windows are suggested if asked
+        let err = client
+            .reserve(ReservationRequest {
+                reservation: Some(new_reservation("ocean-view-room-713")),
+                suggest: true,
+                alternative_resource_ids: vec!["room-2".to_string()],
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        let detail = ErrorDetail::from_status(&err).unwrap();
+        let suggestions: Vec<_> = detail
+            .conflict()
+            .unwrap()
+            .suggestions
+            .iter()
+            .map(|slot| {
+                (
+                    slot.resource_id.as_str(),
+                    slot.start.clone().unwrap().to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            suggestions,
+            vec![
+                ("ocean-view-room-713", "2022-12-23T01:00:00Z".to_string()),
+                ("ocean-view-room-713", "2022-12-28T19:00:00Z".to_string()),
+                ("room-2", "2022-12-25T22:00:00Z".to_string()),
+            ]
+        );
+
+        let err = client
+            .reserve(ReservationRequest {
+                reservation: Some(new_reservation("ocean-view-room-713")),
+                suggest: true,
+                alternative_resource_ids: vec!["".to_string()],
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn rpc_reserve_in_pool_should_reject_suggestions() {
+        let mut client = start_server(migrate_pool.clone()).await;
+        let pool = ResourcePool {
+            r#type: "room".to_string(),
+            strategy: AllocationStrategy::FirstFit as i32,
+        };
+        let with_suggest = ReservationRequest {
+            reservation: Some(new_reservation("")),
+            pool: Some(pool.clone()),
+            suggest: true,
+            ..Default::default()
+        };
+        let err = client.reserve(with_suggest).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+
+        let with_alternatives = ReservationRequest {
+            reservation: Some(new_reservation("")),
+            pool: Some(pool),
+            alternative_resource_ids: vec!["room-2".to_string()],
+            ..Default::default()
+        };
+        let err = client.reserve(with_alternatives).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn rpc_reserve_batch_should_work() {
+        let mut client = start_server(migrate_pool.clone()).await;
+        let rsvp = reserve(&mut client).await;
+
+        let projector = Reservation {
+            resource_id: "projector-1".to_string(),
+            ..new_reservation("ocean-view-room-713")
+        };
+        let reservations = client
+            .reserve_batch(ReserveBatchRequest {
+                reservations: vec![projector.clone()],
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .reservations;
+        assert_eq!(reservations.len(), 1);
+        assert!(reservations[0].id != 0);
+
+        // the room conflicts, the parking is not made either
+        let parking = Reservation {
+            resource_id: "parking-1".to_string(),
+            ..new_reservation("ocean-view-room-713")
+        };
+        let err = client
+            .reserve_batch(ReserveBatchRequest {
+                reservations: vec![
+                    parking.clone(),
+                    new_reservation("ocean-view-room-713"),
+                    projector,
+                ],
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::FailedPrecondition);
+        let detail = ErrorDetail::from_status(&err).unwrap();
+        let conflicts = &detail.batch_conflict().unwrap().conflicts;
+        let indexes: Vec<_> = conflicts.iter().map(|c| c.index).collect();
+        assert_eq!(indexes, vec![1, 2]);
+        let existing = conflicts[0].conflict.as_ref().unwrap().existing.as_ref();
+        assert_eq!(existing.unwrap().id, rsvp.id);
+
+        let reservations = client
+            .reserve_batch(ReserveBatchRequest {
+                reservations: vec![parking],
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .reservations;
+        assert_eq!(reservations.len(), 1);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn rpc_series_should_work() {
+        let mut client = start_server(migrate_pool.clone()).await;
+        let rule = RecurrenceRule {
+            frequency: RecurrenceFrequency::Weekly as i32,
+            count: 3,
+            ..Default::default()
+        };
+        let reservations = client
+            .reserve_series(ReserveSeriesRequest {
+                reservation: Some(new_reservation("ocean-view-room-713")),
+                rule: Some(rule.clone()),
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .reservations;
+        assert_eq!(reservations.len(), 3);
+        assert!(reservations[0].series_id != 0);
+
+        let err = client
+            .reserve_series(ReserveSeriesRequest {
+                reservation: Some(new_reservation("ocean-view-room-713")),
+                rule: Some(rule),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::FailedPrecondition);
+        let detail = ErrorDetail::from_status(&err).unwrap();
+        assert_eq!(detail.batch_conflict().unwrap().conflicts.len(), 3);
+
+        let updated = client
+            .update_series(UpdateSeriesRequest {
+                id: reservations[1].id,
+                scope: SeriesScope::Following as i32,
+                note: Some("hello".into()),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .reservations;
+        assert_eq!(updated.len(), 2);
+        assert!(updated.iter().all(|r| r.note == "hello"));
+
+        let canceled = client
+            .cancel_series(CancelSeriesRequest {
+                id: reservations[0].id,
+                scope: SeriesScope::All as i32,
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .reservations;
+        assert_eq!(canceled.len(), 3);
+
+        let err = client
+            .reserve_series(ReserveSeriesRequest {
+                reservation: Some(new_reservation("ocean-view-room-713")),
+                rule: None,
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+    }
 
-    #[tokio::test]
-    async fn rpc_reserve_should_work() {}
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn rpc_confirm_should_work() {
+        let mut client = start_server(migrate_pool.clone()).await;
+        let rsvp = reserve(&mut client).await;
+
+        let confirmed = client
+            .confirm(ConfirmRequest { id: rsvp.id })
+            .await
+            .unwrap()
+            .into_inner()
+            .reservation
+            .unwrap();
+        assert_eq!(confirmed.id, rsvp.id);
+        assert_eq!(confirmed.status, abi::ReservationStatus::Confirmed as i32);
+
+        // only a pending reservation can be confirmed
+        let err = client
+            .confirm(ConfirmRequest { id: rsvp.id })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::NotFound);
+
+        let err = client.confirm(ConfirmRequest { id: 0 }).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn rpc_update_should_work() {
+        let mut client = start_server(migrate_pool.clone()).await;
+        let rsvp = reserve(&mut client).await;
+
+        let updated = client
+            .update(UpdateRequest {
+                id: rsvp.id,
+                note: "hello world".to_string(),
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .reservation
+            .unwrap();
+        assert_eq!(updated.id, rsvp.id);
+        assert_eq!(updated.note, "hello world");
+
+        let err = client
+            .update(UpdateRequest {
+                id: rsvp.id + 1,
+                note: "hello world".to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::NotFound);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn rpc_cancel_should_work() {
+        let mut client = start_server(migrate_pool.clone()).await;
+        let rsvp = reserve(&mut client).await;
+
+        let canceled = client
+            .cancel(CancelRequest { id: rsvp.id })
+            .await
+            .unwrap()
+            .into_inner()
+            .reservation
+            .unwrap();
+        assert_eq!(canceled, rsvp);
+
+        let err = client
+            .cancel(CancelRequest { id: rsvp.id })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::NotFound);
+
+        let err = client.cancel(CancelRequest { id: -1 }).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn rpc_get_should_work() {
+        let mut client = start_server(migrate_pool.clone()).await;
+        let rsvp = reserve(&mut client).await;
+
+        let rsvp1 = client
+            .get(GetRequest { id: rsvp.id })
+            .await
+            .unwrap()
+            .into_inner()
+            .reservation
+            .unwrap();
+        assert_eq!(rsvp1, rsvp);
+
+        let err = client
+            .get(GetRequest { id: rsvp.id + 1 })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::NotFound);
+
+        let err = client.get(GetRequest { id: 0 }).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrate_pool", migrations = "../migrations"))]
+    async fn rpc_availability_should_work() {
+        let mut client = start_server(migrate_pool.clone()).await;
+        let rsvp = reserve(&mut client).await;
+
+        let query = abi::AvailabilityQueryBuilder::default()
+            .resource_ids(vec![rsvp.resource_id.clone()])
+            .start(abi::convert_to_timestamp(
+                &"2022-12-25T00:00:00Z".parse().unwrap(),
+            ))
+            .end(abi::convert_to_timestamp(
+                &"2022-12-29T00:00:00Z".parse().unwrap(),
+            ))
+            .build()
+            .unwrap();
+        let slots = client
+            .availability(AvailabilityRequest { query: Some(query) })
+            .await
+            .unwrap()
+            .into_inner()
+            .slots;
+        let windows: Vec<_> = slots
+            .iter()
+            .map(|slot| (slot.start.clone(), slot.end.clone()))
+            .collect();
+        assert_eq!(
+            windows,
+            vec![
+                (
+                    Some("2022-12-25T00:00:00Z".parse().unwrap()),
+                    rsvp.start.clone()
+                ),
+                (
+                    rsvp.end.clone(),
+                    Some("2022-12-29T00:00:00Z".parse().unwrap())
+                ),
+            ]
+        );
+
+        let err = client
+            .availability(AvailabilityRequest { query: None })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+    }
+
+    /// a client of the service served on a random local port, with the resources reserved in
+    /// the tests
+    async fn start_server(pool: PgPool) -> ReservationServiceClient<Channel> {
+        seed_resources(&pool).await;
+        ReservationServiceClient::connect(serve(pool).await)
+            .await
+            .unwrap()
+    }
+
+    async fn reserve(client: &mut ReservationServiceClient<Channel>) -> Reservation {
+        client
+            .reserve(ReservationRequest {
+                reservation: Some(new_reservation("ocean-view-room-713")),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .reservation
+            .unwrap()
+    }
 }
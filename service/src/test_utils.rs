@@ -0,0 +1,59 @@
+//! helpers shared by the tests of the service and of its clients
+
+use std::sync::Arc;
+
+use abi::{
+    reservation_service_server::ReservationServiceServer,
+    resource_service_server::ResourceServiceServer, Reservation,
+};
+use reservation::ReservationManager;
+use sqlx::PgPool;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Server;
+
+use crate::RsvpService;
+
+/// the service of the database, the page tokens are signed by a fixed secret
+pub fn new_service(pool: PgPool) -> RsvpService {
+    RsvpService {
+        manager: ReservationManager::new(pool),
+        page_token_secret: "secret".to_string(),
+    }
+}
+
+/// add the resources reserved in the tests to the catalog, it could be done more than once
+pub async fn seed_resources(pool: &PgPool) {
+    sqlx::query(include_str!("../../fixtures/resources.sql"))
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+/// serve the services on a random local port, return the url to connect to
+pub async fn serve(pool: PgPool) -> String {
+    let svc = Arc::new(new_service(pool));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(ReservationServiceServer::from_arc(svc.clone()))
+            .add_service(ResourceServiceServer::from_arc(svc))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    format!("http://{}", addr)
+}
+
+/// a pending reservation of the resource
+pub fn new_reservation(rid: impl Into<String>) -> Reservation {
+    Reservation::new_pending(
+        "chalanziId",
+        rid,
+        "2022-12-25T15:00:00-0700".parse().unwrap(),
+        "2022-12-28T12:00:00-0700".parse().unwrap(),
+        "我将与下午3点到达，请帮忙预约",
+    )
+}